@@ -1,10 +1,29 @@
 //! `AdHash` — incremental structural hashing.
 //!
 //! Computes `φ(S) = Σ f(k, v)` over all entries using wrapping arithmetic.
-//! Two mixing seeds prevent degeneration when `hash(v) = 0`.
+//! `f` mixes the key and value hashes non-linearly and is never 0, so every
+//! entry — even one whose key and value both hash to 0 — changes `φ`.
+//!
+//! With the `strong-hash` feature, contributions are instead combined by
+//! [`mix_in`], multiplication of odd words: `φ(S) = Π f(k, v) mod 2⁶⁴`. All `AdHash`
+//! arithmetic in the crate goes through [`combine`] and [`difference`], so
+//! either combiner keeps `φ` independent of insertion order.
 
 use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+
+/// Default hasher of [`ChampMap`](crate::ChampMap) and
+/// [`ChampMapSync`](crate::ChampMapSync): the standard library's
+/// `DefaultHasher` with fixed zero keys.
+///
+/// Unlike `RandomState` it is unseeded, so every map built with it hashes a
+/// key to the same `u64`. Equality, set operations and subset tests compare
+/// tries of different maps by position and `AdHash` when both maps hash
+/// alike, and fall back to O(n) entry lookups otherwise. The standard
+/// library does not pin the
+/// algorithm across Rust releases, so `AdHash` values persisted under this
+/// hasher may not match after a toolchain upgrade.
+pub type DefaultHashBuilder = BuildHasherDefault<DefaultHasher>;
 
 /// First mixing seed (golden ratio constant).
 const SEED_1: u64 = 0x9E37_79B9_7F4A_7C15;
@@ -12,18 +31,144 @@ const SEED_1: u64 = 0x9E37_79B9_7F4A_7C15;
 /// Second mixing seed (large prime).
 const SEED_2: u64 = 0x517C_C1B7_2722_0A95;
 
+/// `AdHash` of the empty map: the identity of [`combine`].
+#[cfg(not(feature = "strong-hash"))]
+pub const EMPTY: u64 = 0;
+
+/// `AdHash` of the empty map: the identity of [`combine`].
+#[cfg(feature = "strong-hash")]
+pub const EMPTY: u64 = 1;
+
 /// Computes the 64-bit hash of a value using the standard hasher.
+///
+/// Equivalent to hashing with [`DefaultHashBuilder`]. Accepts unsized
+/// values, so a borrowed key form (e.g. `str` for `String`) hashes to the
+/// same `u64` as the owned key.
 #[must_use]
-pub fn hash_one<T: Hash>(value: &T) -> u64 {
+pub fn hash_one<T: Hash + ?Sized>(value: &T) -> u64 {
     let mut hasher = DefaultHasher::new();
     value.hash(&mut hasher);
     hasher.finish()
 }
 
+/// Returns `true` if `a` and `b` hash alike: two instances of one unseeded
+/// or equally seeded hasher do, two freshly seeded `RandomState`s do not.
+///
+/// Compares the hashes of one fixed probe value, so two hashers that
+/// differ elsewhere agree on it with probability 2⁻⁶⁴ — the same odds
+/// `AdHash` equality relies on.
+#[must_use]
+pub(crate) fn hash_alike<A: BuildHasher, B: BuildHasher>(a: &A, b: &B) -> bool {
+    a.hash_one(SEED_1) == b.hash_one(SEED_1)
+}
+
 /// Computes the `AdHash` contribution of a single entry.
 ///
-/// `f(k, v) = key_hash · SEED₁ ⊕ value_hash · SEED₂`
+/// `f(k, v) = fmix((k ⊕ SEED₁) · SEED₂ ⊕ rotl(v ⊕ SEED₂, 32) · SEED₁)`,
+/// where `fmix` is the `MurmurHash3` 64-bit finalizer. Offsetting by the
+/// seeds before multiplying keeps zero hashes from vanishing; `fmix` is a
+/// bijection with `fmix(0) = 0`, so that single preimage is remapped to
+/// `SEED₁` and the contribution is never 0.
+///
+/// With `strong-hash`, the lowest bit is forced to 1 so the contribution is
+/// invertible under [`mix_in`].
 #[must_use]
 pub const fn entry_adhash(key_hash: u64, value_hash: u64) -> u64 {
-    key_hash.wrapping_mul(SEED_1) ^ value_hash.wrapping_mul(SEED_2)
+    let k = (key_hash ^ SEED_1).wrapping_mul(SEED_2);
+    let v = (value_hash ^ SEED_2).rotate_left(32).wrapping_mul(SEED_1);
+    let h = match fmix(k ^ v) {
+        0 => SEED_1,
+        h => h,
+    };
+    if cfg!(feature = "strong-hash") {
+        h | 1
+    } else {
+        h
+    }
+}
+
+/// `MurmurHash3` 64-bit finalizer: an avalanching bijection on `u64`.
+const fn fmix(mut h: u64) -> u64 {
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+    h ^ (h >> 33)
+}
+
+/// Combines the `AdHash` values of two entry sets.
+///
+/// For disjoint key sets, the `AdHash` of the union equals the combination
+/// of the parts, so aggregates can be tracked without building the merged
+/// map. Keys present in both sets are counted twice — in general
+/// `combine(φ(A), φ(B)) = combine(φ(A ∪ B), φ(A ∩ B))` — so take the
+/// overlap out with [`remove_contribution`] first.
+///
+/// Wrapping addition by default; [`mix_in`] with `strong-hash`.
+#[must_use]
+pub const fn combine(a: u64, b: u64) -> u64 {
+    #[cfg(not(feature = "strong-hash"))]
+    {
+        a.wrapping_add(b)
+    }
+    #[cfg(feature = "strong-hash")]
+    {
+        mix_in(a, b)
+    }
+}
+
+/// Inverse of [`combine`]: returns `d` such that `combine(b, d) == a`.
+///
+/// Used for deltas — the change of a replaced value, the contribution of a
+/// removed entry or subtree.
+#[must_use]
+pub const fn difference(a: u64, b: u64) -> u64 {
+    #[cfg(not(feature = "strong-hash"))]
+    {
+        a.wrapping_sub(b)
+    }
+    #[cfg(feature = "strong-hash")]
+    {
+        mix_in(a, inverse(b))
+    }
+}
+
+/// Removes one entry's contribution from an `AdHash` total: the inverse of
+/// combining in its [`entry_adhash`].
+#[must_use]
+pub const fn remove_contribution(total: u64, key_hash: u64, value_hash: u64) -> u64 {
+    difference(total, entry_adhash(key_hash, value_hash))
+}
+
+/// Multiplicative `AdHash` combiner, enabled by the `strong-hash` feature.
+///
+/// Multiplies modulo 2⁶⁴. Odd words form a commutative group under this
+/// product, so the result is still independent of insertion order and every
+/// contribution can be taken out again. Unlike addition, it does not
+/// preserve linear relations between contributions: entries whose
+/// contributions sum alike — e.g. `f₁ + f₂ = f₃ + f₄` — no longer cancel
+/// into equal totals.
+///
+/// The cost is one bit per contribution (all are odd) and a modular inverse
+/// on every removal or value replacement. There is no rotation step:
+/// rotation does not commute with multiplication and would make the total
+/// depend on insertion order. This is not a cryptographic commitment —
+/// against adversarial keys, pair it with a keyed hasher.
+#[cfg(feature = "strong-hash")]
+#[must_use]
+pub const fn mix_in(acc: u64, contribution: u64) -> u64 {
+    acc.wrapping_mul(contribution)
+}
+
+/// Multiplicative inverse of an odd word modulo 2⁶⁴, by Newton iteration:
+/// each step doubles the number of correct low bits, starting from 3.
+#[cfg(feature = "strong-hash")]
+const fn inverse(a: u64) -> u64 {
+    let mut x = a;
+    let mut i = 0;
+    while i < 5 {
+        x = x.wrapping_mul(2_u64.wrapping_sub(a.wrapping_mul(x)));
+        i += 1;
+    }
+    x
 }
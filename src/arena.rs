@@ -3,13 +3,14 @@
 use safe_bump::{Arena, Idx};
 
 use crate::node::{Entry, Node};
-use crate::store::{ChampStore, StoreCheckpoint};
+use crate::store::{ChampStore, Generation, Generations, StoreCheckpoint};
 
 /// Single-threaded storage backend using three [`Arena`]s.
 pub struct ChampArena<K, V> {
     nodes: Arena<Node<K, V>>,
     entries: Arena<Entry<K, V>>,
     children: Arena<Idx<Node<K, V>>>,
+    generations: Generations,
 }
 
 impl<K, V> ChampArena<K, V> {
@@ -19,6 +20,7 @@ impl<K, V> ChampArena<K, V> {
             nodes: Arena::new(),
             entries: Arena::new(),
             children: Arena::new(),
+            generations: Generations::new(),
         }
     }
 }
@@ -29,7 +31,26 @@ impl<K, V> Default for ChampArena<K, V> {
     }
 }
 
+/// Copies all three arenas item by item, dead COW copies included, so every
+/// index (and every checkpoint) stays valid in the copy.
+impl<K: Clone, V: Clone> Clone for ChampArena<K, V> {
+    fn clone(&self) -> Self {
+        let mut out = Self::new();
+        out.nodes
+            .alloc_extend((0..self.nodes.len()).map(|i| *self.nodes.get(Idx::from_raw(i))));
+        out.entries.alloc_extend(
+            (0..self.entries.len()).map(|i| self.entries.get(Idx::from_raw(i)).clone()),
+        );
+        out.children
+            .alloc_extend((0..self.children.len()).map(|i| *self.children.get(Idx::from_raw(i))));
+        out.generations = self.generations.clone();
+        out
+    }
+}
+
 impl<K, V> ChampStore<K, V> for ChampArena<K, V> {
+    type IntoEntries = <Arena<Entry<K, V>> as IntoIterator>::IntoIter;
+
     fn alloc_node(&mut self, node: Node<K, V>) -> Idx<Node<K, V>> {
         self.nodes.alloc(node)
     }
@@ -61,20 +82,62 @@ impl<K, V> ChampStore<K, V> for ChampArena<K, V> {
     }
 
     fn checkpoint(&self) -> StoreCheckpoint<K, V> {
+        let (nodes, entries, children) = self.arena_len();
         StoreCheckpoint {
             nodes: self.nodes.checkpoint(),
             entries: self.entries.checkpoint(),
             children: self.children.checkpoint(),
+            generation: self.generations.stamp(nodes + entries + children),
         }
     }
 
     fn rollback(&mut self, cp: StoreCheckpoint<K, V>) {
+        self.generations.rollback(cp.generation);
         self.nodes.rollback(cp.nodes);
         self.entries.rollback(cp.entries);
         self.children.rollback(cp.children);
     }
 
+    fn is_valid(&self, generation: Generation) -> bool {
+        self.generations.is_valid(generation)
+    }
+
+    fn clear(&mut self) {
+        self.generations.clear();
+        self.nodes.reset();
+        self.entries.reset();
+        self.children.reset();
+    }
+
+    fn supersede(&mut self, old: &Self) {
+        self.generations = old.generations.cleared();
+    }
+
     fn arena_len(&self) -> (usize, usize, usize) {
         (self.nodes.len(), self.entries.len(), self.children.len())
     }
+
+    fn arena_capacity(&self) -> (usize, usize, usize) {
+        (
+            self.nodes.capacity(),
+            self.entries.capacity(),
+            self.children.capacity(),
+        )
+    }
+
+    fn reserve(&mut self, nodes: usize, entries: usize, children: usize) {
+        self.nodes.reserve(nodes);
+        self.entries.reserve(entries);
+        self.children.reserve(children);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+        self.entries.shrink_to_fit();
+        self.children.shrink_to_fit();
+    }
+
+    fn into_entries(self) -> Self::IntoEntries {
+        self.entries.into_iter()
+    }
 }
@@ -1,26 +1,110 @@
 //! Shared-arena-backed storage (multi-threaded).
 
-use safe_bump::{Idx, SharedArena};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use safe_bump::{Checkpoint, Idx, SharedArena};
 
 use crate::node::{Entry, Node};
-use crate::store::{ChampStore, StoreCheckpoint};
+use crate::store::{ChampStore, Generation, Generations, StoreCheckpoint};
 
 /// Thread-safe storage backend using three [`SharedArena`]s.
+///
+/// The arenas sit behind an [`Arc`], so [`share`](Self::share) can hand
+/// them to a second store in O(1): both then allocate into the same
+/// arenas and read each other's nodes in place. While the arenas are
+/// shared, nothing is freed — rollback and clear leave items in place, as
+/// they may belong to the other store — and allocating a run of entries or
+/// children takes a lock, so runs from two threads never interleave.
 pub struct ChampArenaSync<K, V> {
+    /// `None` until the first allocation, so that `new` stays `const`.
+    arenas: Option<Arc<Arenas<K, V>>>,
+    generations: Generations,
+}
+
+/// Clones an entry; recorded by [`ChampArenaSync::share`].
+type CloneEntry<K, V> = fn(&Entry<K, V>) -> Entry<K, V>;
+
+struct Arenas<K, V> {
     nodes: SharedArena<Node<K, V>>,
     entries: SharedArena<Entry<K, V>>,
     children: SharedArena<Idx<Node<K, V>>>,
+    /// Held while a shared store allocates a contiguous run.
+    run_lock: Mutex<()>,
+    /// Set by [`ChampArenaSync::share`], whose callers can clone entries:
+    /// a store consumed while its arenas are shared yields copies.
+    clone_entry: OnceLock<CloneEntry<K, V>>,
 }
 
-impl<K, V> ChampArenaSync<K, V> {
-    /// Creates an empty store.
-    pub const fn new() -> Self {
+impl<K, V> Arenas<K, V> {
+    const fn new() -> Self {
         Self {
             nodes: SharedArena::new(),
             entries: SharedArena::new(),
             children: SharedArena::new(),
+            run_lock: Mutex::new(()),
+            clone_entry: OnceLock::new(),
+        }
+    }
+}
+
+impl<K, V> ChampArenaSync<K, V> {
+    /// Creates an empty store.
+    pub const fn new() -> Self {
+        Self {
+            arenas: None,
+            generations: Generations::new(),
         }
     }
+
+    /// Returns a store over the same arenas, with a copy of this one's
+    /// rollback history. O(1).
+    ///
+    /// Every index valid here is valid in the new store, and stays so: items
+    /// are only freed once a store holds the arenas alone.
+    pub fn share(&self) -> Self
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let arenas = self.arenas.as_ref().map(|arenas| {
+            arenas.clone_entry.get_or_init(|| Entry::clone);
+            Arc::clone(arenas)
+        });
+        Self {
+            arenas,
+            generations: self.generations.clone(),
+        }
+    }
+
+    fn arenas(&self) -> &Arenas<K, V> {
+        self.arenas
+            .as_deref()
+            .expect("index into a store that has allocated nothing")
+    }
+
+    /// Returns the arenas to allocate into, creating them on first use.
+    fn alloc_arenas(&mut self) -> &Arenas<K, V> {
+        self.arenas.get_or_insert_with(|| Arc::new(Arenas::new()))
+    }
+
+    /// Allocates a contiguous run with `alloc`, under the run lock if the
+    /// arenas are shared.
+    fn alloc_run<T>(&mut self, alloc: impl FnOnce(&Arenas<K, V>) -> T) -> T {
+        let arenas = self.arenas.get_or_insert_with(|| Arc::new(Arenas::new()));
+        if Arc::get_mut(arenas).is_some() {
+            return alloc(arenas);
+        }
+        let _run = arenas
+            .run_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        alloc(arenas)
+    }
+
+    /// Returns the arenas if no other store shares them.
+    fn owned_arenas(&mut self) -> Option<&mut Arenas<K, V>> {
+        self.arenas.as_mut().and_then(Arc::get_mut)
+    }
 }
 
 impl<K, V> Default for ChampArenaSync<K, V> {
@@ -29,52 +113,176 @@ impl<K, V> Default for ChampArenaSync<K, V> {
     }
 }
 
+/// Copies all three arenas item by item, dead COW copies included, so every
+/// index (and every checkpoint) stays valid in the copy. The copy holds its
+/// arenas alone, even if this store shares its own.
+impl<K: Clone, V: Clone> Clone for ChampArenaSync<K, V> {
+    fn clone(&self) -> Self {
+        let arenas = self.arenas.as_deref().map(|arenas| {
+            let copy = Arenas::new();
+            copy.nodes.alloc_extend(arenas.nodes.iter().copied());
+            copy.entries.alloc_extend(arenas.entries.iter().cloned());
+            copy.children.alloc_extend(arenas.children.iter().copied());
+            Arc::new(copy)
+        });
+        Self {
+            arenas,
+            generations: self.generations.clone(),
+        }
+    }
+}
+
 impl<K, V> ChampStore<K, V> for ChampArenaSync<K, V> {
+    type IntoEntries = IntoEntries<K, V>;
+
     fn alloc_node(&mut self, node: Node<K, V>) -> Idx<Node<K, V>> {
-        self.nodes.alloc(node)
+        self.alloc_arenas().nodes.alloc(node)
     }
 
     fn get_node(&self, idx: Idx<Node<K, V>>) -> &Node<K, V> {
-        self.nodes.get(idx)
+        self.arenas().nodes.get(idx)
     }
 
     fn alloc_entries(
         &mut self,
         iter: impl IntoIterator<Item = Entry<K, V>>,
     ) -> Option<Idx<Entry<K, V>>> {
-        self.entries.alloc_extend(iter)
+        self.alloc_run(|arenas| arenas.entries.alloc_extend(iter))
     }
 
     fn get_entry(&self, idx: Idx<Entry<K, V>>) -> &Entry<K, V> {
-        self.entries.get(idx)
+        self.arenas().entries.get(idx)
     }
 
     fn alloc_children(
         &mut self,
         iter: impl IntoIterator<Item = Idx<Node<K, V>>>,
     ) -> Option<Idx<Idx<Node<K, V>>>> {
-        self.children.alloc_extend(iter)
+        self.alloc_run(|arenas| arenas.children.alloc_extend(iter))
     }
 
     fn get_child(&self, idx: Idx<Idx<Node<K, V>>>) -> &Idx<Node<K, V>> {
-        self.children.get(idx)
+        self.arenas().children.get(idx)
     }
 
     fn checkpoint(&self) -> StoreCheckpoint<K, V> {
+        let (nodes, entries, children) = self.arena_len();
         StoreCheckpoint {
-            nodes: self.nodes.checkpoint(),
-            entries: self.entries.checkpoint(),
-            children: self.children.checkpoint(),
+            nodes: Checkpoint::from_len(nodes),
+            entries: Checkpoint::from_len(entries),
+            children: Checkpoint::from_len(children),
+            generation: self.generations.stamp(nodes + entries + children),
         }
     }
 
+    /// Frees the items allocated after `cp` only if no other store shares
+    /// the arenas; otherwise they stay in place.
     fn rollback(&mut self, cp: StoreCheckpoint<K, V>) {
-        self.nodes.rollback(cp.nodes);
-        self.entries.rollback(cp.entries);
-        self.children.rollback(cp.children);
+        self.generations.rollback(cp.generation);
+        if let Some(arenas) = self.owned_arenas() {
+            arenas.nodes.rollback(cp.nodes);
+            arenas.entries.rollback(cp.entries);
+            arenas.children.rollback(cp.children);
+        }
+    }
+
+    fn is_valid(&self, generation: Generation) -> bool {
+        self.generations.is_valid(generation)
     }
 
+    /// Resets the arenas if no other store shares them; otherwise lets go
+    /// of them and starts afresh.
+    fn clear(&mut self) {
+        self.generations.clear();
+        if let Some(arenas) = self.owned_arenas() {
+            arenas.nodes.reset();
+            arenas.entries.reset();
+            arenas.children.reset();
+        } else {
+            self.arenas = None;
+        }
+    }
+
+    fn supersede(&mut self, old: &Self) {
+        self.generations = old.generations.cleared();
+    }
+
+    /// Counts the items of every store sharing the arenas.
     fn arena_len(&self) -> (usize, usize, usize) {
-        (self.nodes.len(), self.entries.len(), self.children.len())
+        self.arenas.as_deref().map_or((0, 0, 0), |arenas| {
+            (
+                arenas.nodes.len(),
+                arenas.entries.len(),
+                arenas.children.len(),
+            )
+        })
+    }
+
+    /// `SharedArena` does not expose its chunk capacity, so this reports
+    /// the allocated lengths: no spare room is ever claimed.
+    fn arena_capacity(&self) -> (usize, usize, usize) {
+        self.arena_len()
+    }
+
+    /// No-op: `SharedArena` allocates in chunks on demand and cannot be
+    /// pre-sized.
+    fn reserve(&mut self, _nodes: usize, _entries: usize, _children: usize) {}
+
+    /// No-op: `SharedArena` grows in chunks it neither reports nor trims.
+    fn shrink_to_fit(&mut self) {}
+
+    /// Moves the entries out if no other store shares the arenas; otherwise
+    /// yields clones, leaving the other store's items in place.
+    fn into_entries(self) -> Self::IntoEntries {
+        match self.arenas.map(Arc::try_unwrap) {
+            None => IntoEntries(Source::Owned(Vec::new().into_iter())),
+            Some(Ok(arenas)) => IntoEntries(Source::Owned(arenas.entries.into_iter())),
+            Some(Err(arenas)) => {
+                let clone = *arenas
+                    .clone_entry
+                    .get()
+                    .expect("shared arenas record how to clone entries");
+                IntoEntries(Source::Shared {
+                    arenas,
+                    clone,
+                    pos: 0,
+                })
+            }
+        }
+    }
+}
+
+/// Entries of a consumed [`ChampArenaSync`], in index order.
+pub struct IntoEntries<K, V>(Source<K, V>);
+
+enum Source<K, V> {
+    /// Moved out of arenas the store held alone.
+    Owned(std::vec::IntoIter<Entry<K, V>>),
+    /// Cloned out of arenas still shared with another store, from `pos` on.
+    Shared {
+        arenas: Arc<Arenas<K, V>>,
+        clone: CloneEntry<K, V>,
+        pos: usize,
+    },
+}
+
+impl<K, V> Iterator for IntoEntries<K, V> {
+    type Item = Entry<K, V>;
+
+    fn next(&mut self) -> Option<Entry<K, V>> {
+        self.nth(0)
+    }
+
+    /// Skips `n` entries without cloning them.
+    fn nth(&mut self, n: usize) -> Option<Entry<K, V>> {
+        match &mut self.0 {
+            Source::Owned(entries) => entries.nth(n),
+            Source::Shared { arenas, clone, pos } => {
+                *pos += n;
+                let e = arenas.entries.try_get(Idx::from_raw(*pos))?;
+                *pos += 1;
+                Some(clone(e))
+            }
+        }
     }
 }
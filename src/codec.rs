@@ -0,0 +1,120 @@
+//! Binary framing of a map's logical entry set.
+//!
+//! Layout, all integers little-endian:
+//!
+//! ```text
+//! magic   b"CHMP"
+//! version u8 (1)
+//! count   u64
+//! count × { key_len u32, key bytes, value_len u32, value bytes }
+//! ```
+//!
+//! Entries are written in ascending key order, so the bytes depend only on
+//! the contents and the caller's encoders — not on insertion order, dead
+//! COW copies or the hasher. Keys and values are encoded by caller-supplied
+//! closures; this module only frames them.
+
+use std::hash::{BuildHasher, Hash};
+use std::io::{self, Read, Write};
+
+use crate::transient::Transient;
+
+const MAGIC: &[u8; 4] = b"CHMP";
+const VERSION: u8 = 1;
+
+/// Writes `pairs`, which must be sorted by key, framing each encoded key
+/// and value with its length.
+pub fn write_entries<'a, K: 'a, V: 'a, W, FK, FV>(
+    w: &mut W,
+    pairs: &[(&'a K, &'a V)],
+    mut encode_key: FK,
+    mut encode_value: FV,
+) -> io::Result<()>
+where
+    W: Write,
+    FK: FnMut(&K, &mut Vec<u8>),
+    FV: FnMut(&V, &mut Vec<u8>),
+{
+    w.write_all(MAGIC)?;
+    w.write_all(&[VERSION])?;
+    w.write_all(&(pairs.len() as u64).to_le_bytes())?;
+    let mut buf = Vec::new();
+    for (k, v) in pairs {
+        buf.clear();
+        encode_key(k, &mut buf);
+        write_blob(w, &buf)?;
+        buf.clear();
+        encode_value(v, &mut buf);
+        write_blob(w, &buf)?;
+    }
+    Ok(())
+}
+
+/// Reads an entry set written by [`write_entries`] into `transient`.
+///
+/// Rejects input whose keys are not strictly ascending: such bytes were not
+/// produced by `write_entries`, and accepting them would give one map
+/// several encodings.
+pub fn read_entries<K, V, S, R, FK, FV>(
+    r: &mut R,
+    transient: &mut Transient<K, V, S>,
+    mut decode_key: FK,
+    mut decode_value: FV,
+) -> io::Result<()>
+where
+    K: Hash + Eq + Ord + Clone,
+    V: Hash,
+    S: BuildHasher,
+    R: Read,
+    FK: FnMut(&[u8]) -> io::Result<K>,
+    FV: FnMut(&[u8]) -> io::Result<V>,
+{
+    let mut magic = [0; 4];
+    r.read_exact(&mut magic)?;
+    let mut version = [0; 1];
+    r.read_exact(&mut version)?;
+    if &magic != MAGIC || version[0] != VERSION {
+        return Err(invalid("not a champ-trie entry dump"));
+    }
+    let mut count = [0; 8];
+    r.read_exact(&mut count)?;
+    let count = u64::from_le_bytes(count);
+    let mut buf = Vec::new();
+    let mut prev: Option<K> = None;
+    for _ in 0..count {
+        read_blob(r, &mut buf)?;
+        let key = decode_key(&buf)?;
+        read_blob(r, &mut buf)?;
+        let value = decode_value(&buf)?;
+        if prev.as_ref().is_some_and(|p| *p >= key) {
+            return Err(invalid("keys are not strictly ascending"));
+        }
+        prev = Some(key.clone());
+        transient.insert(key, value);
+    }
+    Ok(())
+}
+
+fn write_blob<W: Write>(w: &mut W, bytes: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "encoded item over 4 GiB"))?;
+    w.write_all(&len.to_le_bytes())?;
+    w.write_all(bytes)
+}
+
+fn read_blob<R: Read>(r: &mut R, buf: &mut Vec<u8>) -> io::Result<()> {
+    let mut len = [0; 4];
+    r.read_exact(&mut len)?;
+    buf.clear();
+    let len = u64::from(u32::from_le_bytes(len));
+    // `take` keeps a corrupt length from allocating more than the input holds.
+    let read = r.by_ref().take(len).read_to_end(buf)?;
+    if read as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
@@ -0,0 +1,381 @@
+//! Read-only cursor for walking the trie structure.
+//!
+//! A [`Cursor`] points at one node and exposes its kind, inline entries and
+//! child subtrees without handing out arena indices. It is meant for
+//! structural analytics — fan-out histograms, depth distributions — rather
+//! than key lookup.
+
+use std::borrow::Borrow;
+use std::fmt;
+
+use safe_bump::Idx;
+
+use crate::arena::ChampArena;
+use crate::node::{self, Entry, Node};
+use crate::store::ChampStore;
+
+/// Kind of the node a [`Cursor`] points at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    /// Bitmap-compressed inner node.
+    Inner,
+    /// Collision node for keys sharing one full 64-bit hash.
+    Collision,
+}
+
+/// Read-only view of one trie node, created by
+/// [`ChampMap::cursor`](crate::ChampMap::cursor).
+pub struct Cursor<'a, K, V, S = ChampArena<K, V>> {
+    store: &'a S,
+    node: &'a Node<K, V>,
+    depth: usize,
+}
+
+impl<K, V, S> Clone for Cursor<'_, K, V, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V, S> Copy for Cursor<'_, K, V, S> {}
+
+impl<'a, K, V, S: ChampStore<K, V>> Cursor<'a, K, V, S> {
+    /// Creates a cursor at `idx`, which sits `depth` levels below the root.
+    pub(crate) fn new(store: &'a S, idx: Idx<Node<K, V>>, depth: usize) -> Self {
+        Self {
+            store,
+            node: store.get_node(idx),
+            depth,
+        }
+    }
+
+    /// Returns whether the node is an inner or a collision node.
+    #[must_use]
+    pub const fn kind(&self) -> NodeKind {
+        match self.node {
+            Node::Inner { .. } => NodeKind::Inner,
+            Node::Collision { .. } => NodeKind::Collision,
+        }
+    }
+
+    /// Returns the node's depth; the root is at depth 0.
+    #[must_use]
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the `AdHash` of the node's subtree.
+    #[must_use]
+    pub const fn adhash(&self) -> u64 {
+        self.node.adhash()
+    }
+
+    /// Returns the entries stored inline in this node, in bitmap order (or
+    /// insertion order for a collision node).
+    #[must_use]
+    pub fn entries(&self) -> impl ExactSizeIterator<Item = &'a Entry<K, V>> + use<'a, K, V, S> {
+        let store = self.store;
+        let start = match *self.node {
+            Node::Inner { data_start, .. } => data_start,
+            Node::Collision { entries_start, .. } => entries_start,
+        };
+        (0..self.node.data_len()).map(move |i| store.get_entry(node::offset(start, i)))
+    }
+
+    /// Returns cursors to the node's child subtrees, in bitmap order. A
+    /// collision node has none.
+    #[must_use]
+    pub fn children(&self) -> impl ExactSizeIterator<Item = Self> + use<'a, K, V, S> {
+        let store = self.store;
+        let depth = self.depth + 1;
+        let start = match *self.node {
+            Node::Inner { children_start, .. } => children_start,
+            Node::Collision { .. } => Idx::from_raw(0),
+        };
+        (0..self.node.children_len())
+            .map(move |i| Self::new(store, *store.get_child(node::offset(start, i)), depth))
+    }
+}
+
+/// Shape statistics of a whole trie, returned by
+/// [`ChampMap::structure_stats`](crate::ChampMap::structure_stats).
+///
+/// Histograms are indexed by the measured quantity: `fan_out[3]` is the
+/// number of inner nodes with exactly three children.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StructureStats {
+    /// Number of inner nodes.
+    pub inner_nodes: usize,
+    /// Number of collision nodes.
+    pub collision_nodes: usize,
+    /// Total number of entries reached; equals the map's `len()`.
+    pub entries: usize,
+    /// Depth of the deepest node; the root is at depth 0.
+    pub max_depth: usize,
+    /// Number of entries stored at each depth.
+    pub entries_by_depth: Vec<usize>,
+    /// Number of inner nodes by count of inline entries.
+    pub entries_per_node: Vec<usize>,
+    /// Number of inner nodes by count of children.
+    pub fan_out: Vec<usize>,
+    /// Entry count of each collision node, in traversal order.
+    pub collision_sizes: Vec<usize>,
+}
+
+impl StructureStats {
+    /// Collects statistics for the trie under `root`.
+    pub(crate) fn collect<K, V, S: ChampStore<K, V>>(root: Option<Cursor<'_, K, V, S>>) -> Self {
+        let mut stats = Self::default();
+        let mut stack: Vec<_> = root.into_iter().collect();
+        while let Some(cursor) = stack.pop() {
+            let depth = cursor.depth();
+            let entries = cursor.entries().len();
+            stats.max_depth = stats.max_depth.max(depth);
+            stats.entries += entries;
+            bump(&mut stats.entries_by_depth, depth, entries);
+            match cursor.kind() {
+                NodeKind::Inner => {
+                    let children = cursor.children();
+                    stats.inner_nodes += 1;
+                    bump(&mut stats.entries_per_node, entries, 1);
+                    bump(&mut stats.fan_out, children.len(), 1);
+                    stack.extend(children);
+                }
+                NodeKind::Collision => {
+                    stats.collision_nodes += 1;
+                    stats.collision_sizes.push(entries);
+                }
+            }
+        }
+        stats
+    }
+
+    /// Returns the total number of nodes of either kind.
+    #[must_use]
+    pub const fn nodes(&self) -> usize {
+        self.inner_nodes + self.collision_nodes
+    }
+
+    /// Returns the mean entry depth — the average number of hops below the
+    /// root a lookup takes — or `0.0` for an empty trie.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn avg_depth(&self) -> f64 {
+        if self.entries == 0 {
+            return 0.0;
+        }
+        let total: usize = self
+            .entries_by_depth
+            .iter()
+            .enumerate()
+            .map(|(depth, n)| depth * n)
+            .sum();
+        total as f64 / self.entries as f64
+    }
+}
+
+/// Adds `by` to `hist[at]`, growing the histogram as needed.
+fn bump(hist: &mut Vec<usize>, at: usize, by: usize) {
+    if hist.len() <= at {
+        hist.resize(at + 1, 0);
+    }
+    hist[at] += by;
+}
+
+/// Route from the root to one key, returned by
+/// [`ChampMap::key_path`](crate::ChampMap::key_path).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyPath {
+    /// Hash fragment taken at each inner node on the way down, root first.
+    /// Its length is the number of inner nodes traversed.
+    pub fragments: Vec<u32>,
+    /// Where the key is stored: [`NodeKind::Inner`] for an inline entry of
+    /// the last inner node, [`NodeKind::Collision`] for a collision node
+    /// below it.
+    pub terminal: NodeKind,
+}
+
+/// Walks from `root` towards `key`, as `get_recursive` does, recording
+/// every fragment taken. Returns `None` if the key is absent.
+pub(crate) fn key_path<K, V, S, Q>(
+    store: &S,
+    root: Idx<Node<K, V>>,
+    hash: u64,
+    key: &Q,
+) -> Option<KeyPath>
+where
+    K: Borrow<Q>,
+    S: ChampStore<K, V>,
+    Q: Eq + ?Sized,
+{
+    let mut fragments = Vec::new();
+    let mut node_idx = root;
+    let mut shift = 0;
+    loop {
+        match *store.get_node(node_idx) {
+            Node::Inner {
+                data_map,
+                node_map,
+                data_start,
+                children_start,
+                ..
+            } => {
+                let frag = node::fragment(hash, shift);
+                let bit = node::mask(frag);
+                fragments.push(frag);
+                if data_map & bit != 0 {
+                    let entry =
+                        store.get_entry(node::offset(data_start, node::index(data_map, bit)));
+                    return (entry.hash == hash && entry.key.borrow() == key).then_some(KeyPath {
+                        fragments,
+                        terminal: NodeKind::Inner,
+                    });
+                }
+                if node_map & bit == 0 {
+                    return None;
+                }
+                node_idx =
+                    *store.get_child(node::offset(children_start, node::index(node_map, bit)));
+                shift += node::BITS_PER_LEVEL;
+            }
+            Node::Collision {
+                hash: node_hash,
+                entries_start,
+                entries_len,
+                ..
+            } => {
+                let found = node_hash == hash
+                    && (0..entries_len as usize).any(|i| {
+                        store.get_entry(node::offset(entries_start, i)).key.borrow() == key
+                    });
+                return found.then_some(KeyPath {
+                    fragments,
+                    terminal: NodeKind::Collision,
+                });
+            }
+        }
+    }
+}
+
+/// Where a hash-fragment prefix leads, found by [`prefix_target`].
+pub(crate) enum PrefixTarget<'a, K, V> {
+    /// A subtree all of whose entries have hashes starting with the prefix.
+    Subtree(Idx<Node<K, V>>),
+    /// The one inline entry whose hash starts with the prefix.
+    Entry(&'a Entry<K, V>),
+}
+
+/// Follows `fragments` from `root`, one per level. Returns `None` if no
+/// entry's hash starts with them.
+///
+/// A prefix that ends on an inline entry, or reaches a collision node,
+/// still has to match the rest of that hash.
+pub(crate) fn prefix_target<'a, K, V, S: ChampStore<K, V>>(
+    store: &'a S,
+    root: Idx<Node<K, V>>,
+    fragments: &[u32],
+) -> Option<PrefixTarget<'a, K, V>> {
+    let mut idx = root;
+    let mut shift = 0;
+    for (level, &frag) in fragments.iter().enumerate() {
+        match *store.get_node(idx) {
+            Node::Inner {
+                data_map,
+                node_map,
+                data_start,
+                children_start,
+                ..
+            } => {
+                if frag as usize >= node::BRANCHING {
+                    return None;
+                }
+                let bit = node::mask(frag);
+                if data_map & bit != 0 {
+                    let e = store.get_entry(node::offset(data_start, node::index(data_map, bit)));
+                    let rest = &fragments[level + 1..];
+                    return hash_starts_with(e.hash, rest, shift + node::BITS_PER_LEVEL)
+                        .then_some(PrefixTarget::Entry(e));
+                }
+                if node_map & bit == 0 {
+                    return None;
+                }
+                idx = *store.get_child(node::offset(children_start, node::index(node_map, bit)));
+                shift += node::BITS_PER_LEVEL;
+            }
+            Node::Collision { hash, .. } => {
+                return hash_starts_with(hash, &fragments[level..], shift)
+                    .then_some(PrefixTarget::Subtree(idx));
+            }
+        }
+    }
+    Some(PrefixTarget::Subtree(idx))
+}
+
+/// Returns `true` if `hash` has `fragments` at consecutive levels from
+/// bit-shift `shift` on. No hash has fragments past [`node::MAX_SHIFT`].
+fn hash_starts_with(hash: u64, fragments: &[u32], shift: u32) -> bool {
+    let mut shift = shift;
+    fragments.iter().all(|&frag| {
+        let ok = shift <= node::MAX_SHIFT && node::fragment(hash, shift) == frag;
+        shift += node::BITS_PER_LEVEL;
+        ok
+    })
+}
+
+/// Entries grouped by root-level slot, one group per fragment.
+pub(crate) type TopGroups<'a, K, V> = [Vec<(&'a K, &'a V)>; node::BRANCHING];
+
+/// Groups the entries under `root` by their root fragment. A root slot holds
+/// either one inline entry or a child subtree whose entries all share the
+/// slot's fragment, so each group is exactly one slot, in DFS order.
+pub(crate) fn top_fragment_groups<K, V, S: ChampStore<K, V>>(
+    root: Option<Cursor<'_, K, V, S>>,
+) -> TopGroups<'_, K, V> {
+    fn walk<'a, K, V, S: ChampStore<K, V>>(
+        cursor: Cursor<'a, K, V, S>,
+        groups: &mut TopGroups<'a, K, V>,
+    ) {
+        for e in cursor.entries() {
+            groups[node::fragment(e.hash, 0) as usize].push((&e.key, &e.value));
+        }
+        for child in cursor.children() {
+            walk(child, groups);
+        }
+    }
+
+    let mut groups = std::array::from_fn(|_| Vec::new());
+    if let Some(root) = root {
+        walk(root, &mut groups);
+    }
+    groups
+}
+
+/// Folds the subtree under `cursor` bottom-up: each node combines the
+/// leaves of its inline entries followed by the folds of its children.
+pub(crate) fn fold_subtrees<K, V, S, A, L, C>(
+    cursor: Cursor<'_, K, V, S>,
+    leaf: &L,
+    combine: &C,
+) -> A
+where
+    S: ChampStore<K, V>,
+    L: Fn(&K, &V) -> A,
+    C: Fn(Vec<A>) -> A,
+{
+    let mut parts: Vec<A> = cursor.entries().map(|e| leaf(&e.key, &e.value)).collect();
+    parts.extend(
+        cursor
+            .children()
+            .map(|child| fold_subtrees(child, leaf, combine)),
+    );
+    combine(parts)
+}
+
+impl<K, V, S> fmt::Debug for Cursor<'_, K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cursor")
+            .field("depth", &self.depth)
+            .field("node", self.node)
+            .finish()
+    }
+}
@@ -0,0 +1,97 @@
+//! Change sets — what changed between a checkpoint and the current map.
+
+use std::slice;
+use std::vec;
+
+/// A single key-level change, as reported by
+/// [`ChampMap::diff_since`](crate::ChampMap::diff_since).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change<K> {
+    /// The key is present now but was absent at the checkpoint.
+    Added(K),
+    /// The key was present at the checkpoint but is absent now.
+    Removed(K),
+    /// The key is present in both, with a different value.
+    Updated(K),
+}
+
+impl<K> Change<K> {
+    /// Returns the changed key.
+    #[must_use]
+    pub const fn key(&self) -> &K {
+        match self {
+            Self::Added(k) | Self::Removed(k) | Self::Updated(k) => k,
+        }
+    }
+}
+
+/// Key-level changes between two states of a map, in trie order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangeSet<K> {
+    changes: Vec<Change<K>>,
+}
+
+impl<K> ChangeSet<K> {
+    pub(crate) const fn new(changes: Vec<Change<K>>) -> Self {
+        Self { changes }
+    }
+
+    /// Returns the number of changed keys.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Returns `true` if nothing changed.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Iterates over all changes.
+    pub fn iter(&self) -> slice::Iter<'_, Change<K>> {
+        self.changes.iter()
+    }
+
+    /// Iterates over the added keys.
+    pub fn added(&self) -> impl Iterator<Item = &K> {
+        self.changes.iter().filter_map(|c| match c {
+            Change::Added(k) => Some(k),
+            _ => None,
+        })
+    }
+
+    /// Iterates over the removed keys.
+    pub fn removed(&self) -> impl Iterator<Item = &K> {
+        self.changes.iter().filter_map(|c| match c {
+            Change::Removed(k) => Some(k),
+            _ => None,
+        })
+    }
+
+    /// Iterates over the keys whose value changed.
+    pub fn updated(&self) -> impl Iterator<Item = &K> {
+        self.changes.iter().filter_map(|c| match c {
+            Change::Updated(k) => Some(k),
+            _ => None,
+        })
+    }
+}
+
+impl<K> IntoIterator for ChangeSet<K> {
+    type Item = Change<K>;
+    type IntoIter = vec::IntoIter<Change<K>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.into_iter()
+    }
+}
+
+impl<'a, K> IntoIterator for &'a ChangeSet<K> {
+    type Item = &'a Change<K>;
+    type IntoIter = slice::Iter<'a, Change<K>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.iter()
+    }
+}
@@ -0,0 +1,300 @@
+//! Entry API — single-descent insert-or-update.
+
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+use crate::adhash::DefaultHashBuilder;
+use crate::arena::ChampArena;
+use crate::ops::path::Path;
+use crate::store::ChampStore;
+use crate::value_mut::{MapParts, ValueMut};
+
+/// A view into a single slot of a map, either occupied or vacant.
+///
+/// Created by [`ChampMap::entry`](crate::ChampMap::entry). The trie is
+/// descended once when the entry is created; the route is path-copied only
+/// when a write happens.
+pub enum Entry<'a, K, V, S = ChampArena<K, V>, H = DefaultHashBuilder>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    /// The key is present.
+    Occupied(OccupiedEntry<'a, K, V, S, H>),
+    /// The key is absent.
+    Vacant(VacantEntry<'a, K, V, S, H>),
+}
+
+/// An occupied entry. Mutations are written back when the entry (or the
+/// guard obtained from it) is dropped.
+pub struct OccupiedEntry<'a, K, V, S = ChampArena<K, V>, H = DefaultHashBuilder>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    guard: ValueMut<'a, K, V, S, H>,
+}
+
+/// A vacant entry. Nothing is allocated until a value is inserted.
+pub struct VacantEntry<'a, K, V, S = ChampArena<K, V>, H = DefaultHashBuilder>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    parts: MapParts<'a, K, V, S, H>,
+    path: Option<Path<K, V>>,
+    hash: u64,
+    key: K,
+}
+
+/// The error returned by [`ChampMap::try_insert`](crate::ChampMap::try_insert)
+/// when the key is already present. The map is left unchanged.
+pub struct OccupiedError<'a, K, V> {
+    /// The key that was not inserted.
+    pub key: K,
+    /// The value that was not inserted.
+    pub value: V,
+    /// The value already stored for the key.
+    pub existing: &'a V,
+}
+
+// ---------------------------------------------------------------------------
+// Entry
+// ---------------------------------------------------------------------------
+
+impl<'a, K, V, S, H> Entry<'a, K, V, S, H>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    /// Returns a reference to the entry's key.
+    #[must_use]
+    pub fn key(&self) -> &K {
+        match self {
+            Self::Occupied(e) => e.key(),
+            Self::Vacant(e) => e.key(),
+        }
+    }
+
+    /// Inserts `default` if vacant. Returns a guard to the value.
+    pub fn or_insert(self, default: V) -> ValueMut<'a, K, V, S, H> {
+        match self {
+            Self::Occupied(e) => e.into_mut(),
+            Self::Vacant(e) => e.insert(default),
+        }
+    }
+
+    /// Inserts the result of `default` if vacant. Returns a guard to the value.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> ValueMut<'a, K, V, S, H> {
+        match self {
+            Self::Occupied(e) => e.into_mut(),
+            Self::Vacant(e) => e.insert(default()),
+        }
+    }
+
+    /// Inserts the result of `default`, called with the key, if vacant.
+    /// Returns a guard to the value.
+    ///
+    /// Lets the default depend on the key without cloning it first. Nothing
+    /// is called, and the `AdHash` is unchanged, if the entry is occupied.
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> ValueMut<'a, K, V, S, H> {
+        match self {
+            Self::Occupied(e) => e.into_mut(),
+            Self::Vacant(e) => {
+                let value = default(e.key());
+                e.insert(value)
+            }
+        }
+    }
+
+    /// Applies `f` to the value if occupied. The `AdHash` is corrected by
+    /// the entry's contribution delta when the write lands.
+    #[must_use]
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Self::Occupied(mut e) => {
+                f(e.get_mut());
+                Self::Occupied(e)
+            }
+            Self::Vacant(e) => Self::Vacant(e),
+        }
+    }
+}
+
+impl<'a, K, V, S, H> Entry<'a, K, V, S, H>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone + Default,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    /// Inserts `V::default()` if vacant. Returns a guard to the value.
+    pub fn or_default(self) -> ValueMut<'a, K, V, S, H> {
+        self.or_insert_with(V::default)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// OccupiedEntry
+// ---------------------------------------------------------------------------
+
+impl<'a, K, V, S, H> OccupiedEntry<'a, K, V, S, H>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    pub(crate) const fn new(guard: ValueMut<'a, K, V, S, H>) -> Self {
+        Self { guard }
+    }
+
+    /// Returns a reference to the stored key.
+    #[must_use]
+    pub fn key(&self) -> &K {
+        self.guard.key()
+    }
+
+    /// Returns a reference to the value.
+    #[must_use]
+    pub fn get(&self) -> &V {
+        &self.guard
+    }
+
+    /// Returns a mutable reference to the value, marking the entry for
+    /// write-back.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.guard
+    }
+
+    /// Replaces the value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Converts the entry into a guard bound to the map's lifetime.
+    #[must_use]
+    pub fn into_mut(self) -> ValueMut<'a, K, V, S, H> {
+        self.guard
+    }
+}
+
+// ---------------------------------------------------------------------------
+// VacantEntry
+// ---------------------------------------------------------------------------
+
+impl<'a, K, V, S, H> VacantEntry<'a, K, V, S, H>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    pub(crate) const fn new(
+        parts: MapParts<'a, K, V, S, H>,
+        path: Option<Path<K, V>>,
+        hash: u64,
+        key: K,
+    ) -> Self {
+        Self {
+            parts,
+            path,
+            hash,
+            key,
+        }
+    }
+
+    /// Returns a reference to the key that would be inserted.
+    #[must_use]
+    pub const fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Takes ownership of the key without inserting.
+    #[must_use]
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Inserts `value`. Returns a guard to it; the insertion lands when the
+    /// guard is dropped.
+    pub fn insert(self, value: V) -> ValueMut<'a, K, V, S, H> {
+        ValueMut::vacant(self.parts, self.path, self.hash, self.key, value)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Debug
+// ---------------------------------------------------------------------------
+
+impl<K, V, S, H> fmt::Debug for Entry<'_, K, V, S, H>
+where
+    K: Hash + Eq + Clone + fmt::Debug,
+    V: Hash + Clone + fmt::Debug,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Occupied(e) => f.debug_tuple("Entry").field(e).finish(),
+            Self::Vacant(e) => f.debug_tuple("Entry").field(e).finish(),
+        }
+    }
+}
+
+impl<K, V, S, H> fmt::Debug for OccupiedEntry<'_, K, V, S, H>
+where
+    K: Hash + Eq + Clone + fmt::Debug,
+    V: Hash + Clone + fmt::Debug,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("key", self.key())
+            .field("value", self.get())
+            .finish()
+    }
+}
+
+impl<K, V, S, H> fmt::Debug for VacantEntry<'_, K, V, S, H>
+where
+    K: Hash + Eq + Clone + fmt::Debug,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntry").field(self.key()).finish()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for OccupiedError<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedError")
+            .field("key", &self.key)
+            .field("existing", self.existing)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Display for OccupiedError<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to insert {:?}, key {:?} already exists with value {:?}",
+            self.value, self.key, self.existing,
+        )
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> std::error::Error for OccupiedError<'_, K, V> {}
@@ -0,0 +1,241 @@
+//! Many persistent map versions sharing one arena.
+//!
+//! A [`ChampForest`] owns a single [`ChampArenaSync`] and hands out
+//! [`RootHandle`]s, each naming one version of a map. Writes path-copy from
+//! a handle's root and return a new handle; the old handle still reads its
+//! own contents, since nodes are never modified once allocated. Versions
+//! derived from one another share every subtree the writes did not touch,
+//! so keeping many related versions costs one path copy per write — e.g.
+//! the scopes of an interpreter's environment chain.
+//!
+//! The forest owns its arena outright, like a map does. Nodes of versions
+//! that are no longer needed stay allocated until the forest is dropped.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+use safe_bump::Idx;
+
+use crate::adhash::{self, DefaultHashBuilder};
+use crate::arena_sync::ChampArenaSync;
+use crate::iter::Iter;
+use crate::node::{Entry, Node};
+use crate::ops::get::get_recursive;
+use crate::ops::insert::{self, insert_recursive};
+use crate::ops::remove::{RemoveOutcome, remove_recursive};
+use crate::store::ChampStore;
+
+/// Arena shared by many map versions, each named by a [`RootHandle`].
+///
+/// Handles are only meaningful for the forest that issued them.
+pub struct ChampForest<K, V, S = DefaultHashBuilder> {
+    store: ChampArenaSync<K, V>,
+    hasher: S,
+}
+
+/// One version of a map in a [`ChampForest`].
+///
+/// A root index plus the version's length and `AdHash` — cheap to copy and
+/// valid for the forest's whole lifetime.
+pub struct RootHandle<K, V> {
+    root: Option<Idx<Node<K, V>>>,
+    size: usize,
+    adhash: u64,
+}
+
+// RootHandle contains only an index and primitives — no actual K/V data.
+
+impl<K, V> Clone for RootHandle<K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for RootHandle<K, V> {}
+
+impl<K, V> fmt::Debug for RootHandle<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RootHandle")
+            .field("len", &self.size)
+            .field("adhash", &format_args!("{:#018x}", self.adhash))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<K, V> RootHandle<K, V> {
+    /// Returns the handle of the empty map, valid in every forest.
+    #[must_use]
+    pub const fn empty() -> Self {
+        Self {
+            root: None,
+            size: 0,
+            adhash: adhash::EMPTY,
+        }
+    }
+
+    /// Returns the number of key-value pairs in this version.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if this version contains no entries.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the `AdHash` of this version.
+    ///
+    /// Comparable across handles of the same forest: equal contents give
+    /// equal `AdHash` regardless of the writes that produced them.
+    #[must_use]
+    pub const fn adhash(&self) -> u64 {
+        self.adhash
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Construction & accessors — no trait bounds
+// ---------------------------------------------------------------------------
+
+impl<K, V> ChampForest<K, V> {
+    /// Creates an empty forest with the [`DefaultHashBuilder`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::new())
+    }
+}
+
+impl<K, V, S> ChampForest<K, V, S> {
+    /// Creates an empty forest that hashes keys and values with `hasher`.
+    #[must_use]
+    pub const fn with_hasher(hasher: S) -> Self {
+        Self {
+            store: ChampArenaSync::new(),
+            hasher,
+        }
+    }
+
+    /// Returns a reference to the forest's hasher.
+    #[must_use]
+    pub const fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    /// Returns the total number of allocated items in each arena, across
+    /// all versions: `(nodes, entries, children)`.
+    #[must_use]
+    pub fn arena_len(&self) -> (usize, usize, usize) {
+        self.store.arena_len()
+    }
+
+    /// Returns an iterator over the `(&K, &V)` pairs of `handle`'s version.
+    #[must_use]
+    pub fn iter(&self, handle: RootHandle<K, V>) -> Iter<'_, K, V, ChampArenaSync<K, V>> {
+        Iter::new(&self.store, handle.root, handle.size)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Read operations — K: Hash + Eq
+// ---------------------------------------------------------------------------
+
+impl<K: Hash + Eq, V, S: BuildHasher> ChampForest<K, V, S> {
+    /// Returns the value of `key` in `handle`'s version.
+    #[must_use]
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, handle: RootHandle<K, V>, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        let root = handle.root?;
+        get_recursive(&self.store, root, self.hasher.hash_one(key), key, 0).map(|e| &e.value)
+    }
+
+    /// Returns `true` if `handle`'s version contains `key`.
+    #[must_use]
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, handle: RootHandle<K, V>, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get(handle, key).is_some()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Write operations — K: Hash + Eq + Clone, V: Hash + Clone
+// ---------------------------------------------------------------------------
+
+impl<K: Hash + Eq + Clone, V: Hash + Clone, S: BuildHasher> ChampForest<K, V, S> {
+    /// Returns a new version: `handle`'s contents with `key` set to `value`.
+    ///
+    /// `handle` stays valid and unchanged.
+    #[must_use]
+    pub fn insert(&mut self, handle: RootHandle<K, V>, key: K, value: V) -> RootHandle<K, V> {
+        let hash = self.hasher.hash_one(&key);
+        let entry = Entry { hash, key, value };
+
+        if let Some(root) = handle.root {
+            let outcome = insert_recursive(&mut self.store, &self.hasher, root, entry, 0);
+            RootHandle {
+                root: Some(outcome.node),
+                size: handle.size + usize::from(outcome.old_value.is_none()),
+                adhash: adhash::combine(handle.adhash, outcome.adhash_delta),
+            }
+        } else {
+            let node = insert::singleton(&mut self.store, &self.hasher, entry);
+            RootHandle {
+                root: Some(node),
+                size: 1,
+                adhash: self.store.get_node(node).adhash(),
+            }
+        }
+    }
+
+    /// Returns a new version: `handle`'s contents without `key`.
+    ///
+    /// If `key` is absent, returns `handle` itself and allocates nothing.
+    #[must_use]
+    pub fn remove<Q: Hash + Eq + ?Sized>(
+        &mut self,
+        handle: RootHandle<K, V>,
+        key: &Q,
+    ) -> RootHandle<K, V>
+    where
+        K: Borrow<Q>,
+    {
+        let Some(root) = handle.root else {
+            return handle;
+        };
+        let hash = self.hasher.hash_one(key);
+        match remove_recursive(&mut self.store, &self.hasher, root, hash, key, 0) {
+            RemoveOutcome::NotFound => handle,
+            RemoveOutcome::Removed {
+                node, adhash_delta, ..
+            } => RootHandle {
+                root: node,
+                size: handle.size - 1,
+                adhash: adhash::difference(handle.adhash, adhash_delta),
+            },
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Trait impls
+// ---------------------------------------------------------------------------
+
+impl<K, V, S: Default> Default for ChampForest<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> fmt::Debug for ChampForest<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChampForest")
+            .field("arena_len", &self.store.arena_len())
+            .finish_non_exhaustive()
+    }
+}
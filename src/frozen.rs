@@ -0,0 +1,129 @@
+//! Immutable, cheaply shareable maps for concurrent readers.
+//!
+//! A [`FrozenChampMap`] is a [`ChampMapSync`] behind an [`Arc`]: cloning it
+//! bumps a reference count, and every clone reads the same arena from any
+//! thread without locking. The `Arc` wraps the whole map — nodes are still
+//! owned by its arena, never reference-counted one by one.
+//!
+//! Writers do not touch a frozen map. They take a [`ChampWriter`] (or
+//! [`thaw`](FrozenChampMap::thaw) it into a private [`ChampMapSync`]),
+//! apply their changes, and commit the result as a new version; readers
+//! holding the old version keep seeing it. Neither copies the map: the
+//! writer shares the frozen arena, as [`ChampMapSync::fork`] does, and
+//! path-copies into it.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use crate::ChampMapSync;
+use crate::adhash::DefaultHashBuilder;
+
+/// Shared, read-only [`ChampMapSync`], created by
+/// [`ChampMapSync::freeze`].
+///
+/// Dereferences to the map, so the whole read API — `get`, `iter`, `len`,
+/// `adhash`, diffs and set tests — is available on every clone.
+pub struct FrozenChampMap<K, V, S = DefaultHashBuilder> {
+    map: Arc<ChampMapSync<K, V, S>>,
+}
+
+impl<K, V, S> FrozenChampMap<K, V, S> {
+    /// Returns `true` if both handles share the same frozen map.
+    #[must_use]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.map, &other.map)
+    }
+}
+
+impl<K: Clone, V: Clone, S: Clone> FrozenChampMap<K, V, S> {
+    /// Returns a writable map with this version's contents.
+    ///
+    /// Takes the map out of the `Arc` if this is the last handle; otherwise
+    /// [`fork`](ChampMapSync::fork)s it. O(1) either way, and other handles
+    /// are unaffected.
+    #[must_use]
+    pub fn thaw(self) -> ChampMapSync<K, V, S> {
+        Arc::try_unwrap(self.map).unwrap_or_else(|map| map.fork())
+    }
+
+    /// Starts a new version based on this one. O(1).
+    ///
+    /// The writer is a [`fork`](ChampMapSync::fork) of the frozen map: it
+    /// shares its arena and nodes, and each write path-copies in O(depth)
+    /// into that arena while readers keep reading it. The writes stay
+    /// private until [`commit`](ChampWriter::commit).
+    #[must_use]
+    pub fn as_writer(&self) -> ChampWriter<K, V, S> {
+        ChampWriter {
+            map: self.map.fork(),
+        }
+    }
+}
+
+impl<K, V, S> Deref for FrozenChampMap<K, V, S> {
+    type Target = ChampMapSync<K, V, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+/// Bumps the reference count; the map itself is not copied.
+impl<K, V, S> Clone for FrozenChampMap<K, V, S> {
+    fn clone(&self) -> Self {
+        Self {
+            map: Arc::clone(&self.map),
+        }
+    }
+}
+
+impl<K, V, S> From<ChampMapSync<K, V, S>> for FrozenChampMap<K, V, S> {
+    fn from(map: ChampMapSync<K, V, S>) -> Self {
+        Self { map: Arc::new(map) }
+    }
+}
+
+impl<K, V, S> fmt::Debug for FrozenChampMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FrozenChampMap").field(&*self.map).finish()
+    }
+}
+
+/// Single-threaded builder of a new [`FrozenChampMap`] version, created by
+/// [`FrozenChampMap::as_writer`].
+///
+/// Dereferences mutably to a private [`ChampMapSync`], so the whole write
+/// API is available; [`commit`](Self::commit) publishes the result.
+pub struct ChampWriter<K, V, S = DefaultHashBuilder> {
+    map: ChampMapSync<K, V, S>,
+}
+
+impl<K, V, S> ChampWriter<K, V, S> {
+    /// Freezes the written map as a new version. Handles to the version the
+    /// writer started from are unaffected.
+    #[must_use]
+    pub fn commit(self) -> FrozenChampMap<K, V, S> {
+        self.map.freeze()
+    }
+}
+
+impl<K, V, S> Deref for ChampWriter<K, V, S> {
+    type Target = ChampMapSync<K, V, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<K, V, S> DerefMut for ChampWriter<K, V, S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.map
+    }
+}
+
+impl<K, V, S> fmt::Debug for ChampWriter<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ChampWriter").field(&self.map).finish()
+    }
+}
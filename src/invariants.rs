@@ -0,0 +1,92 @@
+//! Structural invariant violations reported by
+//! [`ChampMap::debug_invariants`](crate::ChampMap::debug_invariants).
+
+use std::fmt;
+
+/// A broken trie invariant. Node positions are raw indices into the node
+/// arena.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvariantError {
+    /// An inner node has a position set in both `data_map` and `node_map`.
+    OverlappingBitmaps {
+        /// Offending node.
+        node: usize,
+        /// The node's `data_map`.
+        data_map: u32,
+        /// The node's `node_map`.
+        node_map: u32,
+    },
+    /// A node's stored `AdHash` differs from the sum over its subtree.
+    NodeAdHash {
+        /// Offending node.
+        node: usize,
+        /// `AdHash` stored in the node.
+        stored: u64,
+        /// `AdHash` recomputed from the subtree's entries.
+        computed: u64,
+    },
+    /// A collision node holds fewer than two entries.
+    UndersizedCollision {
+        /// Offending node.
+        node: usize,
+        /// Number of entries held.
+        len: usize,
+    },
+    /// A non-root inner node holds fewer than two entries and no children,
+    /// so it should have been inlined into its parent.
+    NotInlined {
+        /// Offending node.
+        node: usize,
+    },
+    /// The map's `AdHash` differs from the root node's.
+    MapAdHash {
+        /// `AdHash` maintained by the map.
+        stored: u64,
+        /// `AdHash` recomputed from the trie.
+        computed: u64,
+    },
+    /// The map's length differs from the number of reachable entries.
+    Len {
+        /// Length maintained by the map.
+        stored: usize,
+        /// Entries counted in the trie.
+        counted: usize,
+    },
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::OverlappingBitmaps {
+                node,
+                data_map,
+                node_map,
+            } => write!(
+                f,
+                "node {node}: data_map {data_map:#010x} overlaps node_map {node_map:#010x}"
+            ),
+            Self::NodeAdHash {
+                node,
+                stored,
+                computed,
+            } => write!(
+                f,
+                "node {node}: stored adhash {stored:#018x}, computed {computed:#018x}"
+            ),
+            Self::UndersizedCollision { node, len } => {
+                write!(f, "node {node}: collision node with {len} entries")
+            }
+            Self::NotInlined { node } => {
+                write!(f, "node {node}: single-entry subtree not inlined")
+            }
+            Self::MapAdHash { stored, computed } => {
+                write!(f, "map adhash {stored:#018x}, computed {computed:#018x}")
+            }
+            Self::Len { stored, counted } => {
+                write!(f, "map len {stored}, counted {counted} entries")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvariantError {}
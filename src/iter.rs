@@ -1,84 +1,443 @@
 //! Iterator types for CHAMP maps.
 
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
 use safe_bump::Idx;
 
+use crate::arena::ChampArena;
 use crate::node::{self, Entry, Node};
 use crate::store::ChampStore;
 
 /// Iterator over references to key-value pairs in a [`ChampMap`](crate::ChampMap).
-pub struct Iter<'a, K, V> {
-    entries: Vec<(&'a K, &'a V)>,
-    pos: usize,
+///
+/// Walks the trie lazily in DFS order (a node's inline entries, then its
+/// children), holding one frame per level. Creating the iterator and
+/// yielding the first entry is O(depth).
+///
+/// Iterating from the back walks a second stack in exactly reverse order;
+/// the two ends stop when they meet, tracked by the remaining count.
+pub struct Iter<'a, K, V, S = ChampArena<K, V>> {
+    store: &'a S,
+    stack: Vec<Frame<'a, K, V>>,
+    back: Vec<Frame<'a, K, V>>,
+    remaining: usize,
+}
+
+/// DFS cursor over a single node. Positions count the entries and children
+/// already visited from the cursor's end.
+struct Frame<'a, K, V> {
+    node: &'a Node<K, V>,
+    data_pos: usize,
+    child_pos: usize,
 }
 
-impl<'a, K, V> Iter<'a, K, V> {
-    /// Creates an iterator by collecting all live entries via DFS.
-    pub fn new<S: ChampStore<K, V>>(store: &'a S, root: Option<Idx<Node<K, V>>>) -> Self {
-        let mut entries = Vec::new();
+impl<'a, K, V, S: ChampStore<K, V>> Iter<'a, K, V, S> {
+    /// Creates an iterator over the subtree rooted at `root`, which must
+    /// contain exactly `len` entries.
+    pub fn new(store: &'a S, root: Option<Idx<Node<K, V>>>, len: usize) -> Self {
+        let mut stack = Vec::new();
+        let mut back = Vec::new();
         if let Some(idx) = root {
-            collect(store, idx, &mut entries);
+            stack.push(Frame::new(store.get_node(idx)));
+            back.push(Frame::new(store.get_node(idx)));
+        }
+        Self {
+            store,
+            stack,
+            back,
+            remaining: len,
+        }
+    }
+}
+
+impl<'a, K, V> Frame<'a, K, V> {
+    const fn new(node: &'a Node<K, V>) -> Self {
+        Self {
+            node,
+            data_pos: 0,
+            child_pos: 0,
         }
-        Self { entries, pos: 0 }
     }
 }
 
-impl<'a, K, V> Iterator for Iter<'a, K, V> {
+impl<'a, K, V, S: ChampStore<K, V>> Iterator for Iter<'a, K, V, S> {
     type Item = (&'a K, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pos < self.entries.len() {
-            let item = self.entries[self.pos];
-            self.pos += 1;
-            Some(item)
-        } else {
-            None
+        if self.remaining == 0 {
+            return None;
+        }
+        loop {
+            let frame = self.stack.last_mut()?;
+            if frame.data_pos < frame.node.data_len() {
+                let e: &'a Entry<K, V> = self
+                    .store
+                    .get_entry(node::offset(data_start(frame.node), frame.data_pos));
+                frame.data_pos += 1;
+                self.remaining -= 1;
+                return Some((&e.key, &e.value));
+            }
+            if let Node::Inner { children_start, .. } = *frame.node
+                && frame.child_pos < frame.node.children_len()
+            {
+                let child = *self
+                    .store
+                    .get_child(node::offset(children_start, frame.child_pos));
+                frame.child_pos += 1;
+                self.stack.push(Frame::new(self.store.get_node(child)));
+                continue;
+            }
+            self.stack.pop();
         }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let remaining = self.entries.len() - self.pos;
-        (remaining, Some(remaining))
+        (self.remaining, Some(self.remaining))
     }
 }
 
-impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
-
-/// DFS collect all `(&K, &V)` from the subtree rooted at `node_idx`.
-fn collect<'a, K, V, S: ChampStore<K, V>>(
-    store: &'a S,
-    node_idx: Idx<Node<K, V>>,
-    out: &mut Vec<(&'a K, &'a V)>,
-) {
-    match *store.get_node(node_idx) {
-        Node::Inner {
-            data_map,
-            node_map,
-            data_start,
-            children_start,
-            ..
-        } => {
-            let data_len = data_map.count_ones() as usize;
-            let children_len = node_map.count_ones() as usize;
-
-            for i in 0..data_len {
-                let e: &'a Entry<K, V> = store.get_entry(node::offset(data_start, i));
-                out.push((&e.key, &e.value));
+impl<'a, K, V, S: ChampStore<K, V>> DoubleEndedIterator for Iter<'a, K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        loop {
+            let frame = self.back.last_mut()?;
+            let children_len = frame.node.children_len();
+            if let Node::Inner { children_start, .. } = *frame.node
+                && frame.child_pos < children_len
+            {
+                let pos = children_len - 1 - frame.child_pos;
+                let child = *self.store.get_child(node::offset(children_start, pos));
+                frame.child_pos += 1;
+                self.back.push(Frame::new(self.store.get_node(child)));
+                continue;
             }
-
-            for i in 0..children_len {
-                let child = *store.get_child(node::offset(children_start, i));
-                collect(store, child, out);
+            let data_len = frame.node.data_len();
+            if frame.data_pos < data_len {
+                let pos = data_len - 1 - frame.data_pos;
+                let e: &'a Entry<K, V> = self
+                    .store
+                    .get_entry(node::offset(data_start(frame.node), pos));
+                frame.data_pos += 1;
+                self.remaining -= 1;
+                return Some((&e.key, &e.value));
             }
+            self.back.pop();
         }
-        Node::Collision {
-            entries_start,
-            entries_len,
-            ..
-        } => {
-            for i in 0..usize::from(entries_len) {
-                let e: &'a Entry<K, V> = store.get_entry(node::offset(entries_start, i));
-                out.push((&e.key, &e.value));
-            }
+    }
+}
+
+impl<K, V, S: ChampStore<K, V>> ExactSizeIterator for Iter<'_, K, V, S> {}
+
+/// Iterator over references to the keys of a map, in DFS order.
+pub struct Keys<'a, K, V, S = ChampArena<K, V>> {
+    inner: Iter<'a, K, V, S>,
+}
+
+impl<'a, K, V, S> Keys<'a, K, V, S> {
+    /// Wraps an entry iterator.
+    #[must_use]
+    pub const fn new(inner: Iter<'a, K, V, S>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, K, V, S: ChampStore<K, V>> Iterator for Keys<'a, K, V, S> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<&'a K> {
+        self.inner.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V, S: ChampStore<K, V>> DoubleEndedIterator for Keys<'_, K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<K, V, S: ChampStore<K, V>> ExactSizeIterator for Keys<'_, K, V, S> {}
+
+/// Iterator over references to the values of a map, in DFS order.
+pub struct Values<'a, K, V, S = ChampArena<K, V>> {
+    inner: Iter<'a, K, V, S>,
+}
+
+impl<'a, K, V, S> Values<'a, K, V, S> {
+    /// Wraps an entry iterator.
+    #[must_use]
+    pub const fn new(inner: Iter<'a, K, V, S>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, K, V, S: ChampStore<K, V>> Iterator for Values<'a, K, V, S> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<&'a V> {
+        self.inner.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V, S: ChampStore<K, V>> DoubleEndedIterator for Values<'_, K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, v)| v)
+    }
+}
+
+impl<K, V, S: ChampStore<K, V>> ExactSizeIterator for Values<'_, K, V, S> {}
+
+/// Iterator over references to key-value pairs in ascending key order.
+///
+/// Collects and sorts all entries up front: O(n log n) to create, then O(1)
+/// per entry.
+pub struct SortedIter<'a, K, V> {
+    inner: std::vec::IntoIter<(&'a K, &'a V)>,
+}
+
+impl<'a, K: Ord, V> SortedIter<'a, K, V> {
+    /// Collects the entries of `iter` and sorts them by key.
+    #[must_use]
+    pub fn new<S: ChampStore<K, V>>(iter: Iter<'a, K, V, S>) -> Self {
+        let mut entries: Vec<(&'a K, &'a V)> = iter.collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        Self {
+            inner: entries.into_iter(),
         }
     }
 }
+
+impl<'a, K, V> SortedIter<'a, K, V> {
+    /// Collects the entries of `iter` whose keys fall in `range`, sorted by
+    /// key. The bounds are located by binary search over the sorted entries.
+    ///
+    /// A range whose start lies past its end yields nothing.
+    #[must_use]
+    pub fn range<S, Q, R>(iter: Iter<'a, K, V, S>, range: &R) -> Self
+    where
+        K: Ord + Borrow<Q>,
+        S: ChampStore<K, V>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let mut entries = Self::new(iter).inner.collect::<Vec<_>>();
+        let key = |e: &(&'a K, &'a V)| -> &Q { e.0.borrow() };
+        let lo = match range.start_bound() {
+            Bound::Included(s) => entries.partition_point(|e| key(e) < s),
+            Bound::Excluded(s) => entries.partition_point(|e| key(e) <= s),
+            Bound::Unbounded => 0,
+        };
+        let hi = match range.end_bound() {
+            Bound::Included(e) => entries.partition_point(|x| key(x) <= e),
+            Bound::Excluded(e) => entries.partition_point(|x| key(x) < e),
+            Bound::Unbounded => entries.len(),
+        };
+        entries.truncate(hi);
+        entries.drain(..lo.min(hi));
+        Self {
+            inner: entries.into_iter(),
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for SortedIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for SortedIter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<K, V> ExactSizeIterator for SortedIter<'_, K, V> {}
+
+/// Owning iterator over the key-value pairs of a map.
+///
+/// Moves entries out of the consumed entries arena, skipping dead COW
+/// copies: only the entries reachable from the root are yielded, in
+/// arena order.
+pub struct IntoIter<K, V, S: ChampStore<K, V> = ChampArena<K, V>> {
+    entries: S::IntoEntries,
+    live: Vec<bool>,
+    pos: usize,
+    remaining: usize,
+}
+
+impl<K, V, S: ChampStore<K, V>> IntoIter<K, V, S> {
+    /// Consumes `store`, yielding the `len` entries reachable from `root`.
+    pub fn new(store: S, root: Option<Idx<Node<K, V>>>, len: usize) -> Self {
+        let mut live = vec![false; store.arena_len().1];
+        if let Some(idx) = root {
+            mark_live(&store, idx, &mut live);
+        }
+        Self {
+            entries: store.into_entries(),
+            live,
+            pos: 0,
+            remaining: len,
+        }
+    }
+}
+
+impl<K, V, S: ChampStore<K, V>> Iterator for IntoIter<K, V, S> {
+    type Item = (K, V);
+
+    /// Steps over dead entries with `nth`, so a store that has to copy
+    /// entries out copies only the live ones.
+    fn next(&mut self) -> Option<(K, V)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let dead = self.live[self.pos..].iter().position(|&live| live)?;
+        let e = self.entries.nth(dead)?;
+        self.pos += dead + 1;
+        self.remaining -= 1;
+        Some((e.key, e.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V, S: ChampStore<K, V>> ExactSizeIterator for IntoIter<K, V, S> {}
+
+/// Owning iterator over the keys of a map, returned by
+/// [`ChampMap::into_keys`](crate::ChampMap::into_keys). Values are dropped
+/// as their entries are passed.
+pub struct IntoKeys<K, V, S: ChampStore<K, V> = ChampArena<K, V>> {
+    inner: IntoIter<K, V, S>,
+}
+
+impl<K, V, S: ChampStore<K, V>> IntoKeys<K, V, S> {
+    /// Wraps an owning entry iterator.
+    #[must_use]
+    pub const fn new(inner: IntoIter<K, V, S>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<K, V, S: ChampStore<K, V>> Iterator for IntoKeys<K, V, S> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        self.inner.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V, S: ChampStore<K, V>> ExactSizeIterator for IntoKeys<K, V, S> {}
+
+/// Owning iterator over the values of a map, returned by
+/// [`ChampMap::into_values`](crate::ChampMap::into_values). Keys are
+/// dropped as their entries are passed.
+pub struct IntoValues<K, V, S: ChampStore<K, V> = ChampArena<K, V>> {
+    inner: IntoIter<K, V, S>,
+}
+
+impl<K, V, S: ChampStore<K, V>> IntoValues<K, V, S> {
+    /// Wraps an owning entry iterator.
+    #[must_use]
+    pub const fn new(inner: IntoIter<K, V, S>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<K, V, S: ChampStore<K, V>> Iterator for IntoValues<K, V, S> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<V> {
+        self.inner.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V, S: ChampStore<K, V>> ExactSizeIterator for IntoValues<K, V, S> {}
+
+/// Draining iterator over the key-value pairs of a map, returned by
+/// [`ChampMap::drain`](crate::ChampMap::drain).
+///
+/// The map is emptied as soon as the drain is created; the arenas it held
+/// are owned by the drain and dropped with it, together with any entries
+/// not yet yielded.
+pub struct Drain<'a, K, V, S: ChampStore<K, V> = ChampArena<K, V>> {
+    inner: IntoIter<K, V, S>,
+    marker: PhantomData<&'a mut S>,
+}
+
+impl<K, V, S: ChampStore<K, V>> Drain<'_, K, V, S> {
+    /// Wraps an owning iterator over the arenas taken from a map.
+    #[must_use]
+    pub const fn new(inner: IntoIter<K, V, S>) -> Self {
+        Self {
+            inner,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V, S: ChampStore<K, V>> Iterator for Drain<'_, K, V, S> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V, S: ChampStore<K, V>> ExactSizeIterator for Drain<'_, K, V, S> {}
+
+/// Marks the raw index of every entry reachable from `node_idx`.
+fn mark_live<K, V, S: ChampStore<K, V>>(store: &S, node_idx: Idx<Node<K, V>>, live: &mut [bool]) {
+    let node = store.get_node(node_idx);
+    let start = data_start(node).into_raw();
+    for flag in &mut live[start..start + node.data_len()] {
+        *flag = true;
+    }
+    if let Node::Inner { children_start, .. } = *node {
+        for i in 0..node.children_len() {
+            mark_live(
+                store,
+                *store.get_child(node::offset(children_start, i)),
+                live,
+            );
+        }
+    }
+}
+
+/// Returns the index of the first inline entry of `node`.
+const fn data_start<K, V>(node: &Node<K, V>) -> Idx<Entry<K, V>> {
+    match *node {
+        Node::Inner { data_start, .. } => data_start,
+        Node::Collision { entries_start, .. } => entries_start,
+    }
+}
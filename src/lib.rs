@@ -26,12 +26,25 @@ use std::fmt;
 use safe_bump::Idx;
 
 pub mod adhash;
+pub mod cursor;
+pub mod diff;
+pub mod entry;
+pub mod forest;
+pub mod frozen;
+pub mod invariants;
 pub mod iter;
 pub mod node;
+#[cfg(feature = "rayon")]
+pub mod par;
+pub mod snapshot;
 pub mod store;
+pub mod transaction;
+pub mod transient;
+pub mod value_mut;
 
 mod arena;
 mod arena_sync;
+mod codec;
 mod map;
 mod map_sync;
 mod ops;
@@ -39,7 +52,10 @@ mod ops;
 #[cfg(test)]
 mod tests;
 
-pub use map::ChampMap;
+pub use adhash::DefaultHashBuilder;
+pub use forest::ChampForest;
+pub use frozen::{ChampWriter, FrozenChampMap};
+pub use map::{ChampMap, ChampMapGeneric};
 pub use map_sync::ChampMapSync;
 
 /// Saved map state for rollback.
@@ -75,3 +91,25 @@ impl<K, V> fmt::Debug for ChampCheckpoint<K, V> {
             .finish_non_exhaustive()
     }
 }
+
+/// Arena sizes before and after a compaction.
+///
+/// Returned by [`ChampMap::compact`] and [`ChampMapSync::compact`]. Each
+/// tuple is `(nodes, entries, children)`, as reported by `arena_len`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompactStats {
+    /// Arena sizes before compaction, dead COW copies included.
+    pub before: (usize, usize, usize),
+    /// Arena sizes after compaction — the live set.
+    pub after: (usize, usize, usize),
+}
+
+impl CompactStats {
+    /// Returns the total number of dead items reclaimed across all arenas.
+    #[must_use]
+    pub const fn reclaimed(&self) -> usize {
+        let (n0, e0, c0) = self.before;
+        let (n1, e1, c1) = self.after;
+        (n0 + e0 + c0) - (n1 + e1 + c1)
+    }
+}
@@ -1,48 +1,180 @@
-//! Single-threaded CHAMP map.
+//! CHAMP map generic over its storage backend, and its single-threaded
+//! alias.
 
+use std::borrow::{Borrow, Cow};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
-use std::hash::Hash;
-use std::ops;
+use std::hash::{BuildHasher, Hash, Hasher, RandomState};
+use std::io;
+use std::mem;
+use std::ops::{self, RangeBounds};
 
+#[cfg(feature = "rand")]
+use rand::Rng;
 use safe_bump::Idx;
 
-use crate::ChampCheckpoint;
-use crate::adhash;
+use crate::adhash::{self, DefaultHashBuilder};
 use crate::arena::ChampArena;
-use crate::iter::Iter;
-use crate::node::{self, Entry, Node};
-use crate::ops::get::get_recursive;
-use crate::ops::insert::insert_recursive;
+use crate::codec;
+use crate::cursor::{self, Cursor, KeyPath, PrefixTarget, StructureStats};
+use crate::diff::ChangeSet;
+use crate::entry::{self, OccupiedEntry, OccupiedError, VacantEntry};
+use crate::invariants::InvariantError;
+use crate::iter::{Drain, IntoIter, IntoKeys, IntoValues, Iter, Keys, SortedIter, Values};
+use crate::node::{self, Entry};
+use crate::ops::diff;
+use crate::ops::dot;
+use crate::ops::get::{get_recursive, try_get_recursive};
+use crate::ops::insert::{self, insert_recursive};
+use crate::ops::invariants;
+use crate::ops::merge::{self, Keep, Pruned, SlotRef};
+use crate::ops::order;
+use crate::ops::path::{self, Path};
 use crate::ops::remove::{RemoveOutcome, remove_recursive};
-use crate::store::ChampStore;
+use crate::ops::transform;
+use crate::snapshot::{Snapshot, SnapshotView};
+use crate::store::{self, ChampStore, StoreError};
+use crate::transaction::Transaction;
+use crate::transient::Transient;
+use crate::value_mut::{IterMut, MapParts, ValueMut};
+use crate::{ChampCheckpoint, CompactStats};
 
-/// Persistent hash map based on a CHAMP trie, single-threaded.
+/// Persistent hash map based on a CHAMP trie, generic over its storage
+/// backend `St`.
 ///
 /// Same set of key-value pairs always produces the same trie structure
 /// (canonical form), enabling O(1) structural equality via [`adhash`](Self::adhash).
-pub struct ChampMap<K, V> {
-    store: ChampArena<K, V>,
-    root: Option<safe_bump::Idx<crate::node::Node<K, V>>>,
-    size: usize,
-    adhash: u64,
+///
+/// Keys and values are hashed with `S`. Operations across two maps —
+/// equality, set operations, subset tests — align their tries by hash when
+/// both maps hash alike, as with an unseeded or fixed-seed hasher. Between
+/// maps whose hashers differ (e.g. two [`with_random_seed`] maps) they
+/// still give the right answers, by entry lookups or by rebuilding the
+/// other map, at O(n) cost. See [`ChampMap::with_hasher`] for reproducible
+/// `AdHash` values.
+///
+/// [`with_random_seed`]: ChampMapGeneric::with_random_seed
+///
+/// Most code names one of the aliases, [`ChampMap`] or
+/// [`ChampMapSync`](crate::ChampMapSync); name this type directly to run
+/// the map over a custom [`ChampStore`], e.g. a pooled or mmap-backed
+/// arena.
+pub struct ChampMapGeneric<K, V, St: ChampStore<K, V>, S = DefaultHashBuilder> {
+    pub(crate) store: St,
+    pub(crate) root: Option<Idx<node::Node<K, V>>>,
+    pub(crate) size: usize,
+    pub(crate) adhash: u64,
+    pub(crate) hasher: S,
 }
 
+/// Persistent hash map based on a CHAMP trie, single-threaded: a
+/// [`ChampMapGeneric`] over [`Arena`](safe_bump::Arena) storage.
+pub type ChampMap<K, V, S = DefaultHashBuilder> = ChampMapGeneric<K, V, ChampArena<K, V>, S>;
+
 // ---------------------------------------------------------------------------
 // Construction & accessors — no trait bounds
 // ---------------------------------------------------------------------------
 
 impl<K, V> ChampMap<K, V> {
-    /// Creates an empty map.
+    /// Creates an empty map with the [`DefaultHashBuilder`].
     #[must_use]
     pub const fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::new())
+    }
+
+    /// Creates an empty map with arena room for about `n` entries; see
+    /// [`with_capacity_and_hasher`](Self::with_capacity_and_hasher).
+    #[must_use]
+    pub fn with_capacity(n: usize) -> Self {
+        Self::with_capacity_and_hasher(n, DefaultHashBuilder::new())
+    }
+}
+
+impl<K, V, S> ChampMap<K, V, S> {
+    /// Creates an empty map that hashes keys and values with `hasher`.
+    ///
+    /// The [`adhash`](Self::adhash) is a function of the entries and the
+    /// hasher alone: maps holding the same entries, built with hashers that
+    /// hash alike, have the same `AdHash` regardless of insertion order,
+    /// process or machine. For content addressing, use a fixed-seed hasher
+    /// whose algorithm is pinned (unlike [`DefaultHashBuilder`]), and key and
+    /// value types whose `Hash` output is stable — integer `Hash` impls feed
+    /// native-endian bytes to the hasher unless it normalizes them.
+    #[must_use]
+    pub const fn with_hasher(hasher: S) -> Self {
+        Self::with_store_and_hasher(ChampArena::new(), hasher)
+    }
+}
+
+impl<K, V, St: ChampStore<K, V> + Default> ChampMapGeneric<K, V, St, RandomState> {
+    /// Creates an empty map that hashes with a freshly seeded
+    /// [`RandomState`], for maps exposed to untrusted keys.
+    ///
+    /// With the unseeded default hasher, anyone can precompute keys whose
+    /// 64-bit hashes collide and pile them into one collision node, where
+    /// every lookup is a linear scan (hash flooding). A per-map random
+    /// seed makes that infeasible.
+    ///
+    /// The tradeoff is the content-addressing mode: the
+    /// [`adhash`](Self::adhash) is only stable for this map, not across
+    /// maps, processes or runs. Equality, `Hash`, set operations and subset
+    /// tests between two randomly seeded maps stay correct but lose their
+    /// shortcuts: equality and subset tests look up every entry, set
+    /// operations first rebuild the other map with this map's hasher, and
+    /// hashing is O(n).
+    #[must_use]
+    pub fn with_random_seed() -> Self {
+        Self::with_store_and_hasher(St::default(), RandomState::new())
+    }
+}
+
+impl<K, V, St: ChampStore<K, V>, S> ChampMapGeneric<K, V, St, S> {
+    /// Creates an empty map that stores its trie in `store` and hashes with
+    /// `hasher`.
+    ///
+    /// Items already allocated in `store` are not part of the map; they
+    /// stay allocated until [`compact`](Self::compact) or
+    /// [`clear`](Self::clear).
+    #[must_use]
+    pub const fn with_store_and_hasher(store: St, hasher: S) -> Self {
         Self {
-            store: ChampArena::new(),
+            store,
             root: None,
             size: 0,
-            adhash: 0,
+            adhash: adhash::EMPTY,
+            hasher,
         }
     }
 
+    /// Creates an empty map that hashes with `hasher`, with arena room for
+    /// `n` entries, `n` nodes and `n` child slots.
+    ///
+    /// That bounds the live trie of `n` entries: every non-root subtree
+    /// holds at least two entries, so there are fewer nodes and child slots
+    /// than entries. Dead COW copies left by later writes are not covered;
+    /// bulk loads that build in place ([`FromIterator`],
+    /// [`insert_many`](Self::insert_many)) allocate little beyond the live
+    /// set.
+    ///
+    /// The room is taken with [`reserve`](Self::reserve), which does
+    /// nothing for [`ChampMapSync`](crate::ChampMapSync).
+    #[must_use]
+    pub fn with_capacity_and_hasher(n: usize, hasher: S) -> Self
+    where
+        St: Default,
+    {
+        let mut store = St::default();
+        store.reserve(n, n, n);
+        Self::with_store_and_hasher(store, hasher)
+    }
+
+    /// Returns a reference to the map's hasher.
+    #[must_use]
+    pub const fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
     /// Returns the number of key-value pairs.
     #[must_use]
     pub const fn len(&self) -> usize {
@@ -75,6 +207,43 @@ impl<K, V> ChampMap<K, V> {
         }
     }
 
+    /// Captures the current version of the map as a [`Snapshot`].
+    ///
+    /// O(1): records the root, length and `AdHash`. Later writes path-copy
+    /// around the snapshot's nodes instead of modifying them, so it keeps
+    /// observing the contents at this point. Read it back with
+    /// [`view`](Self::view). Like a checkpoint, it is invalidated by rolling
+    /// back past it, [`compact`](Self::compact), [`clear`](Self::clear) and
+    /// [`drain`](Self::drain).
+    #[must_use]
+    pub fn snapshot(&self) -> Snapshot<K, V> {
+        Snapshot {
+            root: self.root,
+            size: self.size,
+            adhash: self.adhash,
+            generation: self.store.checkpoint().generation,
+        }
+    }
+
+    /// Reads `snapshot` through this map's arena.
+    ///
+    /// `snapshot` must have been taken from this map; see
+    /// [`snapshot`](Self::snapshot).
+    ///
+    /// # Panics
+    ///
+    /// Panics with "view of a snapshot invalidated by a rollback or a
+    /// cleared arena" if the map was rolled back past `snapshot`, or its
+    /// arenas were cleared or replaced since.
+    #[must_use]
+    pub fn view(&self, snapshot: &Snapshot<K, V>) -> SnapshotView<'_, K, V, St, S> {
+        assert!(
+            self.store.is_valid(snapshot.generation),
+            "view of a snapshot invalidated by a rollback or a cleared arena"
+        );
+        SnapshotView::new(&self.store, &self.hasher, *snapshot)
+    }
+
     /// Returns the total number of allocated items in each arena:
     /// `(nodes, entries, children)`.
     ///
@@ -84,41 +253,463 @@ impl<K, V> ChampMap<K, V> {
         self.store.arena_len()
     }
 
+    /// Returns the allocated capacity of each arena:
+    /// `(nodes, entries, children)`.
+    ///
+    /// Always at least [`arena_len`](Self::arena_len); the difference is
+    /// memory reserved for future allocations. The shared arena behind
+    /// [`ChampMapSync`](crate::ChampMapSync) does not report its capacity
+    /// and returns `arena_len`.
+    #[must_use]
+    pub fn arena_capacity(&self) -> (usize, usize, usize) {
+        self.store.arena_capacity()
+    }
+
+    /// Reserves arena room for at least `additional` more nodes, entries
+    /// and child slots.
+    ///
+    /// Enough for `additional` entries built in place, as with
+    /// [`with_capacity_and_hasher`](Self::with_capacity_and_hasher);
+    /// path-copying inserts allocate more, but start from a larger arena
+    /// and so grow it less often.
+    ///
+    /// A no-op for [`ChampMapSync`](crate::ChampMapSync), whose shared
+    /// arena grows in chunks on demand.
+    pub fn reserve(&mut self, additional: usize) {
+        self.store.reserve(additional, additional, additional);
+    }
+
+    /// Returns the bytes taken by every allocated arena item, dead COW
+    /// copies included: [`arena_len`](Self::arena_len) weighted by the size
+    /// of a node, an entry and a child index.
+    ///
+    /// Unused capacity and heap memory owned by keys and values (e.g. a
+    /// `String`'s buffer) are not counted.
+    #[must_use]
+    pub fn memory_bytes(&self) -> usize {
+        store::arena_bytes::<K, V>(self.store.arena_len())
+    }
+
+    /// Returns the bytes taken by the arena items reachable from the root,
+    /// counted as in [`memory_bytes`](Self::memory_bytes). This is what
+    /// [`compact`](Self::compact) would keep. O(n).
+    #[must_use]
+    pub fn live_memory_bytes(&self) -> usize {
+        let stats = self.structure_stats();
+        let children = stats.fan_out.iter().enumerate().map(|(n, c)| n * c).sum();
+        store::arena_bytes::<K, V>((
+            stats.inner_nodes + stats.collision_nodes,
+            stats.entries,
+            children,
+        ))
+    }
+
+    /// Releases unused arena capacity.
+    ///
+    /// Unlike [`compact`](Self::compact), dead COW copies are kept, so
+    /// checkpoints remain valid; combine the two to minimise memory.
+    ///
+    /// This is a no-op for [`ChampMapSync`](crate::ChampMapSync), whose shared
+    /// arena cannot give capacity back.
+    pub fn shrink_to_fit(&mut self) {
+        self.store.shrink_to_fit();
+    }
+
     /// Restores the map to a previously saved checkpoint.
     ///
-    /// All changes made after the checkpoint are discarded.
+    /// All changes made after the checkpoint are discarded. Rolling back to
+    /// a checkpoint invalidates every checkpoint taken after it; nested
+    /// checkpoints can still be rolled back innermost first.
+    ///
+    /// # Panics
+    ///
+    /// Panics with "rollback to a checkpoint invalidated by an earlier
+    /// rollback" if `cp` was taken after the target of an earlier rollback,
+    /// or before a call that replaced or emptied the arenas:
+    /// [`clear`](Self::clear), [`compact`](Self::compact),
+    /// [`canonicalize`](Self::canonicalize), [`split_off`](Self::split_off)
+    /// or [`drain`](Self::drain).
     pub fn rollback(&mut self, cp: ChampCheckpoint<K, V>) {
         self.store.rollback(cp.store);
         self.root = cp.root;
         self.size = cp.size;
         self.adhash = cp.adhash;
     }
+
+    /// Starts a [`Transaction`]: edits made through the returned guard are
+    /// rolled back when it is dropped, unless it is
+    /// [`commit`](Transaction::commit)ted first.
+    ///
+    /// O(1): takes a [`checkpoint`](Self::checkpoint), rolled back to on
+    /// drop, which also releases the arena items of the discarded edits.
+    pub fn transaction(&mut self) -> Transaction<'_, K, V, St, S> {
+        Transaction::new(self)
+    }
+
+    /// Runs `f` on the map, rolling back everything it did if it returns
+    /// `Err`; on `Ok` its changes are kept.
+    ///
+    /// `f` runs inside a [`transaction`](Self::transaction), committed only
+    /// on `Ok`, so a panic in `f` rolls back as well. `f` must not
+    /// [`compact`](Self::compact), [`clear`](Self::clear) or roll back past
+    /// the call, which would invalidate the transaction's checkpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns `f`'s error, after rolling back.
+    ///
+    /// # Panics
+    ///
+    /// Panics as [`rollback`](Self::rollback) does if `f` invalidated the
+    /// checkpoint and then returned `Err`.
+    pub fn with_rollback<R, E, F: FnOnce(&mut Self) -> Result<R, E>>(
+        &mut self,
+        f: F,
+    ) -> Result<R, E> {
+        let mut tx = self.transaction();
+        let result = f(&mut tx);
+        if result.is_ok() {
+            tx.commit();
+        }
+        result
+    }
+
+    /// Removes all entries and resets the arenas.
+    ///
+    /// The map becomes empty with an `AdHash` of
+    /// [`adhash::EMPTY`](crate::adhash::EMPTY). Arena capacity is retained,
+    /// so refilling the map reuses the same memory; dropping the old items
+    /// is O(n) only for types with drop glue. All checkpoints taken before
+    /// the call are invalidated and must not be rolled back to.
+    pub fn clear(&mut self) {
+        self.store.clear();
+        self.root = None;
+        self.size = 0;
+        self.adhash = adhash::EMPTY;
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Read operations — K: Hash + Eq
 // ---------------------------------------------------------------------------
 
-impl<K: Hash + Eq, V> ChampMap<K, V> {
+impl<K: Hash + Eq, V, St: ChampStore<K, V>, S: BuildHasher> ChampMapGeneric<K, V, St, S> {
     /// Returns a reference to the value associated with `key`.
+    ///
+    /// `key` may be any borrowed form of the map's key type, such as `&str`
+    /// for `String` keys; its `Hash` and `Eq` must match the owned form.
+    #[must_use]
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        let root = self.root?;
+        get_recursive(&self.store, root, self.hasher.hash_one(key), key, 0).map(|e| &e.value)
+    }
+
+    /// Like [`get`](Self::get), but reads the store through its fallible
+    /// `try_get_*` accessors, so a backend that cannot read an item reports
+    /// it instead of panicking. The built-in arenas never fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`StoreError`] met on the way to `key`.
+    pub fn try_get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Result<Option<&V>, StoreError>
+    where
+        K: Borrow<Q>,
+    {
+        let Some(root) = self.root else {
+            return Ok(None);
+        };
+        let entry = try_get_recursive(&self.store, root, self.hasher.hash_one(key), key, 0)?;
+        Ok(entry.map(|e| &e.value))
+    }
+
+    /// Returns the stored key and its value for `key`.
+    ///
+    /// The returned key is the instance held by the map, which may differ
+    /// from the probe in fields that `Eq` ignores.
     #[must_use]
-    pub fn get(&self, key: &K) -> Option<&V> {
+    pub fn get_key_value<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+    {
         let root = self.root?;
-        get_recursive(&self.store, root, adhash::hash_one(key), key, 0)
+        get_recursive(&self.store, root, self.hasher.hash_one(key), key, 0)
+            .map(|e| (&e.key, &e.value))
     }
 
     /// Returns `true` if the map contains the given key.
     #[must_use]
-    pub fn contains_key(&self, key: &K) -> bool {
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
         self.get(key).is_some()
     }
+
+    /// Returns `true` if the map contains every key in `keys`.
+    ///
+    /// Keys may be passed owned or by reference. Stops at the first missing
+    /// key, so a rejected batch costs only the lookups up to it; an empty
+    /// batch is accepted.
+    #[must_use]
+    pub fn contains_all_keys<Q: Borrow<K>>(&self, keys: impl IntoIterator<Item = Q>) -> bool {
+        keys.into_iter().all(|k| self.contains_key(k.borrow()))
+    }
+
+    /// Returns the route to `key` through the trie, or `None` if it is
+    /// absent.
+    ///
+    /// Lists the hash fragment taken at each inner node and whether the key
+    /// ends up inline or in a collision node. A diagnostic for hash
+    /// distribution and collision issues: keys sharing a long fragment
+    /// prefix sit deep in the trie.
+    #[must_use]
+    pub fn key_path<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<KeyPath>
+    where
+        K: Borrow<Q>,
+    {
+        cursor::key_path(&self.store, self.root?, self.hasher.hash_one(key), key)
+    }
+
+    /// Returns the entry whose key hash is the smallest, in O(depth).
+    ///
+    /// The root level of the trie takes the most significant bits of the
+    /// hash, so the lowest occupied slot at each level leads to the minimum
+    /// without a scan. With a collision node at the bottom, its first entry
+    /// is returned.
+    #[must_use]
+    pub fn entry_with_min_hash(&self) -> Option<(&K, &V)> {
+        let e = order::min_entry(&self.store, self.root?);
+        Some((&e.key, &e.value))
+    }
+
+    /// Returns the entry whose key hash is the largest, in O(depth); see
+    /// [`entry_with_min_hash`](Self::entry_with_min_hash).
+    #[must_use]
+    pub fn entry_with_max_hash(&self) -> Option<(&K, &V)> {
+        let e = order::max_entry(&self.store, self.root?);
+        Some((&e.key, &e.value))
+    }
+
+    /// Returns the entry whose key hash is the smallest one `>= target`,
+    /// wrapping around to [`entry_with_min_hash`](Self::entry_with_min_hash)
+    /// if every hash is below it.
+    ///
+    /// The lookup of a consistent-hash ring: with nodes as keys, the node
+    /// owning an item is `successor_by_hash(hasher.hash_one(item))`. A
+    /// descent along `target`'s fragments that steps to the next occupied
+    /// slot where needed: O(depth).
+    #[must_use]
+    pub fn successor_by_hash(&self, target: u64) -> Option<(&K, &V)> {
+        let root = self.root?;
+        let e = order::successor(&self.store, root, target, 0)
+            .unwrap_or_else(|| order::min_entry(&self.store, root));
+        Some((&e.key, &e.value))
+    }
+
+    /// Returns `true` if every key of `self` is in `other` with an equal
+    /// value.
+    ///
+    /// Walks both tries in lockstep; aligned subtrees with equal `AdHash`
+    /// are accepted without being visited, and a longer `self` is rejected
+    /// in O(1). If the maps hash differently, each entry of `self` is
+    /// looked up in `other` instead.
+    #[must_use]
+    pub fn is_subset(&self, other: &Self) -> bool
+    where
+        V: PartialEq,
+    {
+        if self.size > other.size {
+            return false;
+        }
+        if !adhash::hash_alike(&self.hasher, &other.hasher) {
+            return self.entries_in(other);
+        }
+        match (self.root, other.root) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(a), Some(b)) => merge::subset_recursive(&self.store, a, &other.store, b, 0),
+        }
+    }
+
+    /// Returns `true` if every key of `other` is in `self` with an equal
+    /// value. See [`is_subset`](Self::is_subset).
+    #[must_use]
+    pub fn is_superset(&self, other: &Self) -> bool
+    where
+        V: PartialEq,
+    {
+        other.is_subset(self)
+    }
+
+    /// Returns `true` if every entry of `self` is in `other`, looking each
+    /// one up: correct whatever the two hashers.
+    fn entries_in(&self, other: &Self) -> bool
+    where
+        V: PartialEq,
+    {
+        self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+
+    /// Returns an iterator over the keys present in both maps, with the
+    /// value from each: `(key, self_value, other_value)`.
+    ///
+    /// Walks the smaller map and looks each key up in the larger:
+    /// O(min(n, m) · log₃₂ max(n, m)). Order is the smaller map's
+    /// iteration order. Unlike the set operations, equal subtrees cannot be
+    /// skipped — their keys are common and must be yielded.
+    pub fn join<'a, W, St2: ChampStore<K, W>>(
+        &'a self,
+        other: &'a ChampMapGeneric<K, W, St2, S>,
+    ) -> impl Iterator<Item = (&'a K, &'a V, &'a W)> + 'a {
+        let by_self = self.size <= other.len();
+        let from_self = by_self.then(|| {
+            self.iter()
+                .filter_map(move |(k, v)| other.get(k).map(|w| (k, v, w)))
+        });
+        let from_other = (!by_self).then(|| {
+            other
+                .iter()
+                .filter_map(move |(k, w)| self.get(k).map(|v| (k, v, w)))
+        });
+        from_self
+            .into_iter()
+            .flatten()
+            .chain(from_other.into_iter().flatten())
+    }
+
+    /// Returns the keys added, removed or updated since `cp` was taken.
+    ///
+    /// Compares the current trie against the one rooted at the checkpoint,
+    /// which is still present in the arena as long as `cp` is valid: taken
+    /// from this map, and not discarded by rolling back past it,
+    /// [`compact`](Self::compact) or [`clear`](Self::clear). Subtrees shared
+    /// with the checkpoint, or with an equal `AdHash`, are skipped.
+    #[must_use]
+    pub fn diff_since(&self, cp: &ChampCheckpoint<K, V>) -> ChangeSet<K>
+    where
+        K: Clone,
+        V: PartialEq,
+    {
+        let mut changes = Vec::new();
+        diff::diff_slots(
+            &self.store,
+            cp.root.map(SlotRef::Child),
+            self.root.map(SlotRef::Child),
+            &mut changes,
+        );
+        ChangeSet::new(changes)
+    }
+
+    /// Returns the value `key` had when `cp` was taken, without rolling
+    /// back.
+    ///
+    /// Looks the key up from the checkpoint's root in the current arena,
+    /// which still holds that trie as long as `cp` is valid; see
+    /// [`diff_since`](Self::diff_since). Same cost as [`get`](Self::get).
+    #[must_use]
+    pub fn read_at<Q: Hash + Eq + ?Sized>(&self, cp: &ChampCheckpoint<K, V>, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        let root = cp.root?;
+        get_recursive(&self.store, root, self.hasher.hash_one(key), key, 0).map(|e| &e.value)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Diagnostics — K: Hash + Eq, V: Hash
+// ---------------------------------------------------------------------------
+
+impl<K: Hash + Eq, V: Hash, St: ChampStore<K, V>, S: BuildHasher> ChampMapGeneric<K, V, St, S> {
+    /// Recomputes the `AdHash` from scratch by hashing every live key and
+    /// value, ignoring the incrementally maintained value and every stored
+    /// node hash. O(n).
+    #[must_use]
+    pub fn recompute_adhash(&self) -> u64 {
+        self.iter().fold(adhash::EMPTY, |acc, (k, v)| {
+            adhash::combine(
+                acc,
+                adhash::entry_adhash(self.hasher.hash_one(k), self.hasher.hash_one(v)),
+            )
+        })
+    }
+
+    /// Returns `true` if the maintained [`adhash`](Self::adhash) matches
+    /// [`recompute_adhash`](Self::recompute_adhash). Intended for tests and
+    /// debug assertions.
+    #[must_use]
+    pub fn verify_adhash(&self) -> bool {
+        self.adhash == self.recompute_adhash()
+    }
+
+    /// Checks every structural invariant of the trie reachable from the
+    /// root: disjoint bitmaps, per-node `AdHash` equal to the sum over the
+    /// subtree, collision nodes with at least two entries, no un-inlined
+    /// single-entry subtrees below the root, and map length and `AdHash`
+    /// matching the trie. O(n). Intended for tests.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first violated [`InvariantError`] found in depth-first
+    /// order.
+    pub fn debug_invariants(&self) -> Result<(), InvariantError> {
+        let (counted, computed) = match self.root {
+            Some(root) => invariants::check_node(&self.store, &self.hasher, root, true)?,
+            None => (0, adhash::EMPTY),
+        };
+        if counted != self.size {
+            return Err(InvariantError::Len {
+                stored: self.size,
+                counted,
+            });
+        }
+        if computed != self.adhash {
+            return Err(InvariantError::MapAdHash {
+                stored: self.adhash,
+                computed,
+            });
+        }
+        Ok(())
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Write operations — K: Hash + Eq + Clone, V: Hash + Clone
 // ---------------------------------------------------------------------------
 
-impl<K: Hash + Eq + Clone, V: Hash + Clone> ChampMap<K, V> {
+impl<K, V, St, S> ChampMapGeneric<K, V, St, S>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    St: ChampStore<K, V>,
+    S: BuildHasher,
+{
+    /// Builds a map from pairs whose keys are known to be distinct.
+    ///
+    /// Like [`FromIterator`], but the builder never compares keys, which
+    /// saves the equality probe on every slot or collision a new key meets
+    /// — a win for bulk loads of already-deduplicated data.
+    ///
+    /// Duplicate keys cause no memory unsafety, but the map is then
+    /// logically incorrect: both entries are kept, `len` over-counts, and
+    /// which value a lookup sees is unspecified (last-wins is not
+    /// guaranteed).
+    #[must_use]
+    pub fn from_unique_unchecked<I: IntoIterator<Item = (K, V)>>(pairs: I) -> Self
+    where
+        St: Default,
+        S: Default,
+    {
+        let mut transient = Transient::default();
+        for (k, v) in pairs {
+            transient.insert_unique(k, v);
+        }
+        Self::from(transient)
+    }
+
     /// Inserts a key-value pair into the map.
     ///
     /// Returns `None` if the key was new, or `Some(old_value)` if an existing
@@ -128,70 +719,1155 @@ impl<K: Hash + Eq + Clone, V: Hash + Clone> ChampMap<K, V> {
     ///
     /// Panics if internal arena allocation returns an unexpected `None`.
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        let hash = adhash::hash_one(&key);
+        let hash = self.hasher.hash_one(&key);
         let entry = Entry { hash, key, value };
 
         if let Some(root) = self.root {
-            let outcome = insert_recursive(&mut self.store, root, entry, 0);
+            let outcome = insert_recursive(&mut self.store, &self.hasher, root, entry, 0);
             self.root = Some(outcome.node);
-            self.adhash = self.adhash.wrapping_add(outcome.adhash_delta);
+            self.adhash = adhash::combine(self.adhash, outcome.adhash_delta);
             if outcome.old_value.is_none() {
                 self.size += 1;
             }
             outcome.old_value
         } else {
-            let value_hash = adhash::hash_one(&entry.value);
-            let contribution = adhash::entry_adhash(hash, value_hash);
-            let frag = node::fragment(hash, 0);
-            let bit = node::mask(frag);
-            let data_start = self
-                .store
-                .alloc_entries(std::iter::once(entry))
-                .expect("single entry");
-            let new_node = self.store.alloc_node(Node::Inner {
-                data_map: bit,
-                node_map: 0,
-                data_start,
-                children_start: Idx::from_raw(0),
-                adhash: contribution,
-            });
+            let new_node = insert::singleton(&mut self.store, &self.hasher, entry);
             self.root = Some(new_node);
             self.size = 1;
-            self.adhash = contribution;
+            self.adhash = self.store.get_node(new_node).adhash();
             None
         }
     }
 
+    /// Returns a write guard for the value associated with `key`.
+    ///
+    /// If the value is written through the guard, the route to the entry is
+    /// path-copied when the guard is dropped, keeping
+    /// [`adhash`](Self::adhash) in sync with the new value. Checkpoints
+    /// taken before the call remain valid for rollback.
+    pub fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<ValueMut<'_, K, V, St, S>>
+    where
+        K: Borrow<Q>,
+    {
+        let root = self.root?;
+        let hash = self.hasher.hash_one(key);
+        let path = path::descend(&self.store, root, hash);
+        let pos = path::find_entry(&self.store, &path, hash, key)?;
+        Some(ValueMut::occupied(self.parts(), path, pos))
+    }
+
+    /// Applies `f` to the value for `key`, if present. Returns `true` if the
+    /// key was found.
+    ///
+    /// The value is modified on a private copy and written back with a single
+    /// path-copy of the spine; [`adhash`](Self::adhash) is corrected by the
+    /// entry's contribution delta. A missing key leaves the map untouched.
+    pub fn update<Q: Hash + Eq + ?Sized, F: FnOnce(&mut V)>(&mut self, key: &Q, f: F) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get_mut(key).is_some_and(|mut value| {
+            f(&mut value);
+            true
+        })
+    }
+
+    /// Returns a lending iterator over `(&K, &mut V)` pairs.
+    ///
+    /// Values are edited on private copies and written back when the
+    /// [`IterMut`] is dropped, with one path copy of the whole trie — O(n)
+    /// clones — and every `AdHash` recomputed. Checkpoints taken before the
+    /// call remain valid for rollback.
+    #[allow(clippy::iter_not_returning_iterator)]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V, St, S> {
+        IterMut::new(self.parts())
+    }
+
+    /// Returns the [`Entry`](entry::Entry) for `key`, for in-place
+    /// insert-or-update.
+    ///
+    /// Descends the trie once; the route is path-copied only when the entry
+    /// is actually written.
+    pub fn entry(&mut self, key: K) -> entry::Entry<'_, K, V, St, S> {
+        let hash = self.hasher.hash_one(&key);
+        let Some(root) = self.root else {
+            return entry::Entry::Vacant(VacantEntry::new(self.parts(), None, hash, key));
+        };
+        let path = path::descend(&self.store, root, hash);
+        match path::find_entry(&self.store, &path, hash, &key) {
+            Some(pos) => entry::Entry::Occupied(OccupiedEntry::new(ValueMut::occupied(
+                self.parts(),
+                path,
+                pos,
+            ))),
+            None => entry::Entry::Vacant(VacantEntry::new(self.parts(), Some(path), hash, key)),
+        }
+    }
+
+    /// Returns the value for `key`, inserting `f()` first if it is absent.
+    ///
+    /// Descends the trie once. On a miss, `f` is called exactly once and the
+    /// new entry is inserted with a single path-copy from the descent's end,
+    /// keeping [`adhash`](Self::adhash) in sync.
+    ///
+    /// # Panics
+    ///
+    /// Panics if internal arena allocation returns an unexpected `None`.
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, f: F) -> &V {
+        let hash = self.hasher.hash_one(&key);
+        let path = self.root.map(|root| path::descend(&self.store, root, hash));
+        if let Some(path) = &path
+            && let Some(pos) = path::find_entry(&self.store, path, hash, &key)
+        {
+            return &self
+                .store
+                .get_entry(path::entry_at(&self.store, path.node, pos))
+                .value;
+        }
+        let value = f();
+        let idx = self.insert_vacant(path, Entry { hash, key, value });
+        &self.store.get_entry(idx).value
+    }
+
+    /// Inserts `key` with `value` only if the key is absent, returning a
+    /// reference to the stored value.
+    ///
+    /// If the key is present, nothing is written — [`adhash`](Self::adhash)
+    /// is unchanged — and the rejected key and value are returned in the
+    /// error, along with a reference to the existing value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OccupiedError`] if the map already contains `key`.
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<&V, OccupiedError<'_, K, V>> {
+        let hash = self.hasher.hash_one(&key);
+        let path = self.root.map(|root| path::descend(&self.store, root, hash));
+        if let Some(path) = &path
+            && let Some(pos) = path::find_entry(&self.store, path, hash, &key)
+        {
+            let existing = &self
+                .store
+                .get_entry(path::entry_at(&self.store, path.node, pos))
+                .value;
+            return Err(OccupiedError {
+                key,
+                value,
+                existing,
+            });
+        }
+        let idx = self.insert_vacant(path, Entry { hash, key, value });
+        Ok(&self.store.get_entry(idx).value)
+    }
+
+    /// Inserts an entry whose key is known to be absent, at the end of
+    /// `path` (or as the root if the map is empty). Returns the index of the
+    /// stored entry.
+    fn insert_vacant(&mut self, path: Option<Path<K, V>>, entry: Entry<K, V>) -> Idx<Entry<K, V>> {
+        let hash = entry.hash;
+        let (leaf, shift) = if let Some(path) = path {
+            let outcome =
+                insert_recursive(&mut self.store, &self.hasher, path.node, entry, path.shift);
+            let delta = outcome.adhash_delta;
+            let root = path::rebuild_spine(&mut self.store, &path.frames, outcome.node, delta);
+            self.root = Some(root);
+            self.adhash = adhash::combine(self.adhash, delta);
+            (outcome.node, path.shift)
+        } else {
+            let root = insert::singleton(&mut self.store, &self.hasher, entry);
+            self.root = Some(root);
+            self.adhash = self.store.get_node(root).adhash();
+            (root, 0)
+        };
+        self.size += 1;
+        path::inserted_entry(&self.store, leaf, hash, shift)
+    }
+
+    /// Borrows the fields touched by a deferred write.
+    const fn parts(&mut self) -> MapParts<'_, K, V, St, S> {
+        MapParts {
+            store: &mut self.store,
+            root: &mut self.root,
+            size: &mut self.size,
+            adhash: &mut self.adhash,
+            hasher: &self.hasher,
+        }
+    }
+
     /// Removes a key from the map. Returns the removed value, or `None` if
     /// the key was not present.
-    pub fn remove(&mut self, key: &K) -> Option<V> {
+    ///
+    /// `key` may be any borrowed form of the map's key type.
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
         let root = self.root?;
-        let hash = adhash::hash_one(key);
-        match remove_recursive(&mut self.store, root, hash, key, 0) {
+        let hash = self.hasher.hash_one(key);
+        match remove_recursive(&mut self.store, &self.hasher, root, hash, key, 0) {
             RemoveOutcome::NotFound => None,
             RemoveOutcome::Removed {
                 node,
                 adhash_delta,
                 removed_value,
             } => {
+                debug_assert!(self.size > 0, "removal reported from an empty map");
                 self.root = node;
                 self.size -= 1;
-                self.adhash = self.adhash.wrapping_sub(adhash_delta);
+                self.adhash = adhash::difference(self.adhash, adhash_delta);
                 Some(removed_value)
             }
         }
     }
+
+    /// Inserts every pair, returning how many keys were new (as opposed to
+    /// overwritten). For repeated keys the last pair wins, as with
+    /// [`insert`](Self::insert).
+    ///
+    /// The pairs are first built in place in a [`Transient`], then merged
+    /// into the map with the [`union`](Self::union) machinery, so only the
+    /// merged paths are copied — far fewer dead COW copies than calling
+    /// `insert` in a loop.
+    pub fn insert_many<I: IntoIterator<Item = (K, V)>>(&mut self, pairs: I) -> usize
+    where
+        S: Clone,
+    {
+        let mut transient = Transient::with_hasher(self.hasher.clone());
+        transient.extend(pairs);
+        let Some(root) = self.root else {
+            let (root, size, adhash, _) = transient.freeze_into(&mut self.store);
+            self.root = root;
+            self.size = size;
+            self.adhash = adhash;
+            return size;
+        };
+        let mut batch = ChampArena::new();
+        let (Some(b), ..) = transient.freeze_into(&mut batch) else {
+            return 0;
+        };
+        let mut added = 0;
+        let newer = |_: &V, b: &V| b.clone();
+        let root = merge::union_recursive(
+            &mut self.store,
+            &self.hasher,
+            root,
+            &batch,
+            b,
+            0,
+            &newer,
+            true,
+            &mut added,
+        );
+        self.root = Some(root);
+        self.size += added;
+        self.adhash = self.store.get_node(root).adhash();
+        added
+    }
+
+    /// Rebuilds the trie bottom-up from its entries.
+    ///
+    /// Every collision node ends up with at least two entries, every
+    /// single-entry subtree is inlined and every `AdHash` is recomputed from
+    /// scratch. Writes already maintain this form, so on a healthy map the
+    /// contents, `adhash` and trie shape come out unchanged — a
+    /// belt-and-braces normalization, e.g. before persisting a content
+    /// address. The trie is written into fresh arenas, as by
+    /// [`compact`](Self::compact): dead COW copies are dropped and all
+    /// checkpoints taken before the call are invalidated.
+    pub fn canonicalize(&mut self)
+    where
+        St: Default,
+        S: Clone,
+    {
+        let mut transient = Transient::with_hasher(self.hasher.clone());
+        transient.extend(self.iter().map(|(k, v)| (k.clone(), v.clone())));
+        let mut store = St::default();
+        let (root, size, adhash, _) = transient.freeze_into(&mut store);
+        debug_assert_eq!(size, self.size);
+        store.supersede(&self.store);
+        self.store = store;
+        self.root = root;
+        self.adhash = adhash;
+    }
+
+    /// Returns a new map with `key` set to `value`, leaving `self`
+    /// unchanged.
+    ///
+    /// The arena is owned by one map, so the new map starts from a copy of
+    /// `self` — O(arena size) — before the insert path-copies as usual. For
+    /// many cheap persistent versions, use a
+    /// [`ChampForest`](crate::ChampForest).
+    #[must_use]
+    pub fn inserted(&self, key: K, value: V) -> Self
+    where
+        St: Clone,
+        S: Clone,
+    {
+        let mut out = self.clone();
+        out.insert(key, value);
+        out
+    }
+
+    /// Returns a new map without `key`, leaving `self` unchanged. Copies
+    /// the arena like [`inserted`](Self::inserted).
+    #[must_use]
+    pub fn removed<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+        St: Clone,
+        S: Clone,
+    {
+        let mut out = self.clone();
+        out.remove(key);
+        out
+    }
+
+    /// Removes every key yielded by `keys`, returning how many were present.
+    ///
+    /// Keys are hashed and sorted so that keys sharing a route through the
+    /// trie are handled together: each touched node is rebuilt once, instead
+    /// of the spine being path-copied once per key. Absent and repeated keys
+    /// are ignored.
+    pub fn remove_all<I: IntoIterator<Item = K>>(&mut self, keys: I) -> usize {
+        let Some(root) = self.root else {
+            return 0;
+        };
+        let mut keys: Vec<(u64, K)> = keys
+            .into_iter()
+            .map(|k| (self.hasher.hash_one(&k), k))
+            .collect();
+        keys.sort_unstable_by_key(|(hash, _)| *hash);
+
+        let mut removed = 0;
+        self.root =
+            match merge::remove_keys(&mut self.store, &self.hasher, root, &keys, 0, &mut removed) {
+                Pruned::Empty => None,
+                Pruned::Single(e) => Some(insert::singleton(&mut self.store, &self.hasher, e)),
+                Pruned::Node(n) => Some(n),
+            };
+        self.size -= removed;
+        self.adhash = self
+            .root
+            .map_or(adhash::EMPTY, |r| self.store.get_node(r).adhash());
+        removed
+    }
+
+    /// Overwrites the values of existing keys, returning how many entries
+    /// were updated.
+    ///
+    /// Keys absent from the map are skipped, never inserted; a key given
+    /// more than once gets its last value. Updates are sorted by hash and
+    /// applied in one bottom-up pass, so each touched node is path-copied
+    /// once rather than once per key as with repeated
+    /// [`insert`](Self::insert). The key set and trie shape are unchanged.
+    pub fn modify_many<I: IntoIterator<Item = (K, V)>>(&mut self, updates: I) -> usize {
+        let Some(root) = self.root else {
+            return 0;
+        };
+        let mut updates: Vec<(u64, K, Option<V>)> = updates
+            .into_iter()
+            .map(|(k, v)| (self.hasher.hash_one(&k), k, Some(v)))
+            .collect();
+        // Stable, so repeated keys keep their order and the last one wins.
+        updates.sort_by_key(|(hash, _, _)| *hash);
+
+        let mut applied = 0;
+        let root = transform::overwrite_values(
+            &mut self.store,
+            &self.hasher,
+            root,
+            &mut updates,
+            0,
+            &mut applied,
+        );
+        self.root = Some(root);
+        self.adhash = self.store.get_node(root).adhash();
+        applied
+    }
+
+    /// Removes every entry for which `f(&key, &value)` returns `true`,
+    /// returning the removed pairs in [`iter`](Self::iter) order.
+    ///
+    /// Matches are collected in one pass, then removed together through
+    /// [`remove_all`](Self::remove_all), so the remaining trie is canonical
+    /// and each touched node is rebuilt once. The pairs are cloned out of
+    /// the arena, where checkpoints may still refer to them.
+    pub fn extract_if<F: FnMut(&K, &V) -> bool>(&mut self, mut f: F) -> Vec<(K, V)> {
+        let extracted: Vec<(K, V)> = self
+            .iter()
+            .filter(|(k, v)| f(k, v))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        let removed = self.remove_all(extracted.iter().map(|(k, _)| k.clone()));
+        debug_assert_eq!(removed, extracted.len());
+        extracted
+    }
+
+    /// Moves every entry whose key satisfies `f` into a new map, keeping the
+    /// rest in `self`.
+    ///
+    /// One pass partitions the entries into two [`Transient`] builders,
+    /// which are then frozen — `self` into fresh arenas, as by
+    /// [`compact`](Self::compact). Both maps come out canonical with exact
+    /// `len` and `AdHash`. All checkpoints taken before the call are
+    /// invalidated and must not be rolled back to.
+    #[must_use]
+    pub fn split_off<F: FnMut(&K) -> bool>(&mut self, mut f: F) -> Self
+    where
+        St: Default,
+        S: Clone,
+    {
+        let mut moved = Transient::with_hasher(self.hasher.clone());
+        let mut kept = Transient::with_hasher(self.hasher.clone());
+        for (k, v) in self.iter() {
+            let half = if f(k) { &mut moved } else { &mut kept };
+            half.insert(k.clone(), v.clone());
+        }
+        let mut store = St::default();
+        let (root, size, adhash, _) = kept.freeze_into(&mut store);
+        store.supersede(&self.store);
+        self.store = store;
+        self.root = root;
+        self.size = size;
+        self.adhash = adhash;
+        Self::from(moved)
+    }
+
+    /// Keeps only the entries for which `f(&key, &value)` returns `true`.
+    ///
+    /// Entries to drop are collected in one pass, then removed together
+    /// through [`remove_all`](Self::remove_all), so the remaining trie is
+    /// canonical and its `AdHash` exact. Subtrees that lose no entry keep
+    /// their nodes: arena growth follows the removals, and retaining every
+    /// entry allocates nothing.
+    pub fn retain<F: FnMut(&K, &V) -> bool>(&mut self, mut f: F) {
+        let dropped: Vec<K> = self
+            .iter()
+            .filter(|(k, v)| !f(k, v))
+            .map(|(k, _)| k.clone())
+            .collect();
+        self.remove_all(dropped);
+    }
+
+    /// Keeps only the entries whose key satisfies `f`; see
+    /// [`retain`](Self::retain).
+    pub fn retain_keys<F: FnMut(&K) -> bool>(&mut self, mut f: F) {
+        self.retain(|k, _| f(k));
+    }
+
+    /// Keeps only the entries whose value satisfies `f`; see
+    /// [`retain`](Self::retain).
+    pub fn retain_values<F: FnMut(&V) -> bool>(&mut self, mut f: F) {
+        self.retain(|_, v| f(v));
+    }
+
+    /// Removes and returns the entry with the smallest key.
+    ///
+    /// Finds the key with [`first_key_value`](Self::first_key_value), then
+    /// removes it through [`remove`](Self::remove): O(n).
+    ///
+    /// # Panics
+    ///
+    /// Panics if cloning the key yields one that no longer hashes or
+    /// compares equal to the stored key.
+    pub fn pop_first(&mut self) -> Option<(K, V)>
+    where
+        K: Ord,
+    {
+        let key = self.first_key_value()?.0.clone();
+        let value = self.remove(&key).expect("key found by scan");
+        Some((key, value))
+    }
+
+    /// Removes and returns the entry with the largest key.
+    ///
+    /// Finds the key with [`last_key_value`](Self::last_key_value), then
+    /// removes it through [`remove`](Self::remove): O(n).
+    ///
+    /// # Panics
+    ///
+    /// Panics if cloning the key yields one that no longer hashes or
+    /// compares equal to the stored key.
+    pub fn pop_last(&mut self) -> Option<(K, V)>
+    where
+        K: Ord,
+    {
+        let key = self.last_key_value()?.0.clone();
+        let value = self.remove(&key).expect("key found by scan");
+        Some((key, value))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Counting — K: Hash + Eq
+// ---------------------------------------------------------------------------
+
+impl<K: Hash + Eq, St: ChampStore<K, usize> + Default> ChampMapGeneric<K, usize, St> {
+    /// Returns a map from each distinct key in `keys` to the number of times
+    /// it occurs.
+    ///
+    /// Tallies in one pass through a [`Transient`], so no dead COW copies are
+    /// allocated. The result is canonical: any ordering of the same multiset
+    /// of keys gives an equal map with an equal [`adhash`](Self::adhash).
+    #[must_use]
+    pub fn count_occurrences<I: IntoIterator<Item = K>>(keys: I) -> Self {
+        let mut transient = Transient::new();
+        for key in keys {
+            transient.insert_or_merge(key, 1, |n, one| *n += one);
+        }
+        Self::from(transient)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Value transforms — K: Clone
+// ---------------------------------------------------------------------------
+
+impl<K: Clone, V, S: BuildHasher + Clone> ChampMap<K, V, S> {
+    /// Returns a new map with the same keys and every value mapped through `f`.
+    ///
+    /// Keys are not re-hashed: the key set and trie shape are unchanged, so
+    /// the node structure is copied as-is into a fresh arena and only the
+    /// entries (and `AdHash` values) are rebuilt. Dead COW copies are not
+    /// carried over.
+    #[must_use]
+    pub fn map_values<W: Hash + Clone, F: FnMut(&V) -> W>(&self, mut f: F) -> ChampMap<K, W, S> {
+        let mut store = ChampArena::new();
+        let root = self.root.map(|root| {
+            transform::map_subtree(&self.store, &self.hasher, &mut store, root, &mut f)
+        });
+        let adhash = root.map_or(adhash::EMPTY, |r| store.get_node(r).adhash());
+        ChampMap {
+            store,
+            root,
+            size: self.size,
+            adhash,
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+impl<K: Clone, V: Hash + Clone, St: ChampStore<K, V>, S: BuildHasher> ChampMapGeneric<K, V, St, S> {
+    /// Applies `f` to every value in place, keeping the key set unchanged.
+    ///
+    /// The whole trie is path-copied once, with every node's `AdHash`
+    /// recomputed from the new values, so [`adhash`](Self::adhash) matches
+    /// a map built from scratch with the transformed values. Checkpoints
+    /// taken before the call remain valid for rollback.
+    pub fn map_values_in_place<F: FnMut(&K, &mut V)>(&mut self, mut f: F) {
+        let Some(root) = self.root else {
+            return;
+        };
+        let root = transform::update_values(&mut self.store, &self.hasher, root, &mut f);
+        self.root = Some(root);
+        self.adhash = self.store.get_node(root).adhash();
+    }
+
+    /// Makes every value a clone of one canonical copy per distinct value,
+    /// returning the number of distinct values.
+    ///
+    /// Values are stored inline in entries, so equal values cannot share a
+    /// slot; what interning saves is whatever a clone shares. For
+    /// reference-counted values such as `Arc<Config>`, equal values end up
+    /// pointing at one allocation, and later COW copies only bump its
+    /// count. Contents and [`adhash`](Self::adhash) are unchanged.
+    ///
+    /// Rewrites the trie once, as
+    /// [`map_values_in_place`](Self::map_values_in_place) does; the old
+    /// values stay alive in the superseded copies until
+    /// [`compact`](Self::compact).
+    pub fn intern_values(&mut self) -> usize
+    where
+        V: Eq,
+    {
+        let mut canonical: HashSet<V> = HashSet::new();
+        self.map_values_in_place(|_, v| {
+            if let Some(c) = canonical.get(v) {
+                *v = c.clone();
+            } else {
+                canonical.insert(v.clone());
+            }
+        });
+        canonical.len()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Maintenance — K: Clone, V: Clone
+// ---------------------------------------------------------------------------
+
+impl<K: Clone, V: Clone, St: ChampStore<K, V> + Default, S> ChampMapGeneric<K, V, St, S> {
+    /// Reclaims dead COW copies by copying the live trie into fresh arenas.
+    ///
+    /// Walks the trie from the root and copies only reachable nodes, entries
+    /// and children, then replaces the store. Contents, `len` and `AdHash`
+    /// are unchanged, and [`arena_len`](Self::arena_len) drops to the live
+    /// set. All checkpoints taken before the call are invalidated and must
+    /// not be rolled back to.
+    pub fn compact(&mut self) -> CompactStats {
+        let before = self.store.arena_len();
+        let mut store = St::default();
+        let mut copied = 0;
+        self.root = self
+            .root
+            .map(|root| merge::copy_subtree(&mut store, &self.store, root, &mut copied));
+        debug_assert_eq!(copied, self.size);
+        store.supersede(&self.store);
+        self.store = store;
+        CompactStats {
+            before,
+            after: self.store.arena_len(),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
-// Iterator stubs
+// Set operations — K: Hash + Eq + Clone, V: Hash + Clone
 // ---------------------------------------------------------------------------
 
-impl<K, V> ChampMap<K, V> {
+impl<K, V, St, S> ChampMapGeneric<K, V, St, S>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    St: ChampStore<K, V> + Clone + Default,
+    S: BuildHasher + Clone,
+{
+    /// Returns `other` if it hashes like `self`, or else a copy of it
+    /// rebuilt with `self`'s hasher, so that the two tries align.
+    fn aligned<'a>(&self, other: &'a Self) -> Cow<'a, Self> {
+        if adhash::hash_alike(&self.hasher, &other.hasher) {
+            return Cow::Borrowed(other);
+        }
+        let mut transient = Transient::with_hasher(self.hasher.clone());
+        for (k, v) in other {
+            transient.insert_unique(k.clone(), v.clone());
+        }
+        Cow::Owned(Self::from(transient))
+    }
+
+    /// Returns a map holding the entries of both maps.
+    ///
+    /// Keys present in both get `resolve(self_value, other_value)`. The
+    /// result is built on a copy of `self`'s arena: aligned subtrees with
+    /// equal `AdHash` are shared as-is, and only differing paths are copied.
+    /// Because identical subtrees are skipped, `resolve(v, v)` must return
+    /// `v` (as first-wins, last-wins, `max` and `min` do); use
+    /// [`merge_with`](Self::merge_with) otherwise.
+    #[must_use]
+    pub fn union<F: Fn(&V, &V) -> V>(&self, other: &Self, resolve: F) -> Self {
+        self.union_by(other, &resolve, true)
+    }
+
+    /// Returns a map holding the entries of both maps, combining the values
+    /// of keys present in both with `combine(self_value, other_value)`.
+    ///
+    /// The entry point for merging counters and other accumulators:
+    /// unlike [`union`](Self::union), `combine` runs for every common key,
+    /// even inside identical subtrees, so it need not satisfy
+    /// `combine(v, v) == v` — e.g. `|a, b| a + b`.
+    #[must_use]
+    pub fn merge_with<F: Fn(&V, &V) -> V>(&self, other: &Self, combine: F) -> Self {
+        self.union_by(other, &combine, false)
+    }
+
+    /// Shared driver for [`union`](Self::union) and
+    /// [`merge_with`](Self::merge_with).
+    fn union_by<F: Fn(&V, &V) -> V>(&self, other: &Self, resolve: &F, skip_equal: bool) -> Self {
+        let other = &*self.aligned(other);
+        let mut out = self.clone();
+        let Some(b) = other.root else {
+            return out;
+        };
+        let mut added = 0;
+        let root = match self.root {
+            Some(a) => merge::union_recursive(
+                &mut out.store,
+                &out.hasher,
+                a,
+                &other.store,
+                b,
+                0,
+                resolve,
+                skip_equal,
+                &mut added,
+            ),
+            None => merge::copy_subtree(&mut out.store, &other.store, b, &mut added),
+        };
+        out.root = Some(root);
+        out.size += added;
+        out.adhash = out.store.get_node(root).adhash();
+        out
+    }
+
+    /// Returns a map holding the entries of `self` whose keys are also in
+    /// `other`, with `self`'s values.
+    ///
+    /// Aligned subtrees with equal `AdHash` are kept wholesale.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.filter_by(other, Keep::Common)
+    }
+
+    /// Returns a map holding the entries of `self` whose keys are not in
+    /// `other`.
+    ///
+    /// Aligned subtrees with equal `AdHash` are dropped wholesale.
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        self.filter_by(other, Keep::Unique)
+    }
+
+    /// Returns a map holding the entries whose keys are in exactly one of
+    /// the two maps, each with the value from the map that has it.
+    ///
+    /// Keys present in both maps are left out whatever their values.
+    /// Aligned subtrees with equal `AdHash` contribute nothing and are
+    /// dropped wholesale.
+    #[must_use]
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        let other = &*self.aligned(other);
+        let mut out = self.clone();
+        let Some(b) = other.root else {
+            return out;
+        };
+        let Some(a) = self.root else {
+            let mut copied = 0;
+            let root = merge::copy_subtree(&mut out.store, &other.store, b, &mut copied);
+            out.root = Some(root);
+            out.size = copied;
+            out.adhash = other.adhash;
+            return out;
+        };
+        let mut common = 0;
+        out.root = match merge::symmetric_recursive(
+            &mut out.store,
+            &out.hasher,
+            a,
+            &other.store,
+            b,
+            0,
+            &mut common,
+        ) {
+            Pruned::Empty => None,
+            Pruned::Single(e) => Some(insert::singleton(&mut out.store, &out.hasher, e)),
+            Pruned::Node(n) => Some(n),
+        };
+        out.size = self.size + other.size - 2 * common;
+        out.adhash = out
+            .root
+            .map_or(adhash::EMPTY, |r| out.store.get_node(r).adhash());
+        out
+    }
+
+    /// Shared driver for [`intersection`](Self::intersection) and
+    /// [`difference`](Self::difference).
+    fn filter_by(&self, other: &Self, keep: Keep) -> Self {
+        let other = &*self.aligned(other);
+        let (Some(a), Some(b)) = (self.root, other.root) else {
+            return match keep {
+                Keep::Common => Self::with_store_and_hasher(St::default(), self.hasher.clone()),
+                Keep::Unique => self.clone(),
+            };
+        };
+        let mut out = self.clone();
+        let mut removed = 0;
+        out.root = match merge::filter_recursive(
+            &mut out.store,
+            &out.hasher,
+            a,
+            &other.store,
+            b,
+            0,
+            keep,
+            &mut removed,
+        ) {
+            Pruned::Empty => None,
+            Pruned::Single(e) => Some(insert::singleton(&mut out.store, &out.hasher, e)),
+            Pruned::Node(n) => Some(n),
+        };
+        out.size -= removed;
+        out.adhash = out
+            .root
+            .map_or(adhash::EMPTY, |r| out.store.get_node(r).adhash());
+        out
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Serialization — K: Ord
+// ---------------------------------------------------------------------------
+
+impl<K: Ord, V, St: ChampStore<K, V>, S> ChampMapGeneric<K, V, St, S> {
+    /// Writes the live entries as a compact binary dump, in ascending key
+    /// order.
+    ///
+    /// `encode_key` and `encode_value` append an item's bytes to the buffer
+    /// they are given; each item is framed with its length behind a short
+    /// header. Only the logical entry set is written, so maps with equal
+    /// contents produce identical bytes whatever their history or hasher —
+    /// the dump can be hashed or compared for content addressing. Read it
+    /// back with [`read_entries`](Self::read_entries). O(n log n).
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from writing to `w`, or
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if an encoded key or
+    /// value exceeds `u32::MAX` bytes.
+    pub fn write_entries<W, FK, FV>(
+        &self,
+        w: &mut W,
+        encode_key: FK,
+        encode_value: FV,
+    ) -> io::Result<()>
+    where
+        W: io::Write,
+        FK: FnMut(&K, &mut Vec<u8>),
+        FV: FnMut(&V, &mut Vec<u8>),
+    {
+        codec::write_entries(w, &self.as_sorted_vec(), encode_key, encode_value)
+    }
+
+    /// Reads a dump written by [`write_entries`](Self::write_entries),
+    /// rebuilding the map canonically through a [`Transient`].
+    ///
+    /// `decode_key` and `decode_value` receive exactly the bytes their
+    /// encoders produced. With the same hasher, the result has the same
+    /// [`adhash`](Self::adhash) as the map that was written.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from reading `r` or from the decoders,
+    /// [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) on truncated input,
+    /// and [`InvalidData`](io::ErrorKind::InvalidData) if the header is
+    /// wrong or the keys are not strictly ascending.
+    pub fn read_entries<R, FK, FV>(r: &mut R, decode_key: FK, decode_value: FV) -> io::Result<Self>
+    where
+        K: Hash + Clone,
+        V: Hash + Clone,
+        St: Default,
+        S: BuildHasher + Default,
+        R: io::Read,
+        FK: FnMut(&[u8]) -> io::Result<K>,
+        FV: FnMut(&[u8]) -> io::Result<V>,
+    {
+        let mut transient = Transient::default();
+        codec::read_entries(r, &mut transient, decode_key, decode_value)?;
+        Ok(Self::from(transient))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Iterators
+// ---------------------------------------------------------------------------
+
+impl<K, V, St: ChampStore<K, V>, S> ChampMapGeneric<K, V, St, S> {
     /// Returns an iterator over `(&K, &V)` pairs.
     #[must_use]
-    pub fn iter(&self) -> Iter<'_, K, V> {
-        Iter::new(&self.store, self.root)
+    pub fn iter(&self) -> Iter<'_, K, V, St> {
+        Iter::new(&self.store, self.root, self.size)
+    }
+
+    /// Returns a read-only [`Cursor`] at the root node, or `None` if the map
+    /// is empty.
+    #[must_use]
+    pub fn cursor(&self) -> Option<Cursor<'_, K, V, St>> {
+        self.root.map(|root| Cursor::new(&self.store, root, 0))
+    }
+
+    /// Splits the entries into one group per root slot (32, or 16 with the
+    /// `branch-16` feature): group `i` holds the
+    /// entries whose hash has fragment `i` at the root — one inline entry,
+    /// or every entry of the child subtree in that slot.
+    ///
+    /// Groups are disjoint and together hold every entry, in DFS order
+    /// within each group, which makes them natural units of parallel work.
+    #[must_use]
+    pub fn partition_by_top_fragment(&self) -> [Vec<(&K, &V)>; node::BRANCHING] {
+        cursor::top_fragment_groups(self.cursor())
+    }
+
+    /// Returns an iterator over the entries whose key hashes start with
+    /// `fragments`, one hash fragment per trie level from the root.
+    ///
+    /// Descends the trie along the prefix, then walks only the subtree it
+    /// leads to, in DFS order: O(prefix length) to start, plus the size of
+    /// that subtree. A prefix no entry has — including one with a fragment
+    /// of [`node::BRANCHING`] or more — yields nothing, and the empty
+    /// prefix yields the whole map. The groups of
+    /// [`partition_by_top_fragment`](Self::partition_by_top_fragment) are
+    /// the single-fragment prefixes.
+    pub fn iter_under_prefix(&self, fragments: &[u32]) -> impl Iterator<Item = (&K, &V)> + '_ {
+        let target = self
+            .root
+            .and_then(|root| cursor::prefix_target(&self.store, root, fragments));
+        let (subtree, single) = match target {
+            Some(PrefixTarget::Subtree(idx)) => {
+                let len = merge::subtree_len(&self.store, idx);
+                (Some(Iter::new(&self.store, Some(idx), len)), None)
+            }
+            Some(PrefixTarget::Entry(e)) => (None, Some((&e.key, &e.value))),
+            None => (None, None),
+        };
+        subtree.into_iter().flatten().chain(single)
+    }
+
+    /// Returns shape statistics of the trie: node counts by kind, entry and
+    /// fan-out histograms, depth and collision-node sizes.
+    #[must_use]
+    pub fn structure_stats(&self) -> StructureStats {
+        StructureStats::collect(self.cursor())
+    }
+
+    /// Folds the trie bottom-up into one value, or returns `None` if the
+    /// map is empty.
+    ///
+    /// Each entry becomes `leaf(key, value)`; each node then passes
+    /// `combine` the results for its inline entries followed by those of its
+    /// child subtrees, in bitmap order, and the root's result is returned.
+    /// A collision node combines its entries alone. The map's own `AdHash`
+    /// is such a fold, with the entry contribution as `leaf` and
+    /// [`adhash::combine`] as `combine`; per-subtree sums, counts or depths
+    /// work the same way. O(n).
+    pub fn fold_subtrees<A, L, C>(&self, leaf: L, combine: C) -> Option<A>
+    where
+        L: Fn(&K, &V) -> A,
+        C: Fn(Vec<A>) -> A,
+    {
+        self.cursor()
+            .map(|root| cursor::fold_subtrees(root, &leaf, &combine))
+    }
+
+    /// Writes the trie as a Graphviz DOT graph, for teaching and debugging.
+    ///
+    /// Inner nodes show their `data_map` and `node_map` bitmaps, collision
+    /// nodes their shared hash, and each inline entry is a box labeled with
+    /// its key's `Debug` output. Edges from an inner node carry the hash
+    /// fragment of their slot. Render with e.g. `dot -Tsvg`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from writing to `w`.
+    pub fn write_dot<W: io::Write>(&self, w: &mut W) -> io::Result<()>
+    where
+        K: fmt::Debug,
+    {
+        dot::write_graph(&self.store, self.root, w)
+    }
+
+    /// Returns an iterator over `&K`, in the same order as [`iter`](Self::iter).
+    #[must_use]
+    pub fn keys(&self) -> Keys<'_, K, V, St> {
+        Keys::new(self.iter())
+    }
+
+    /// Returns an iterator over `&V`, in the same order as [`iter`](Self::iter).
+    #[must_use]
+    pub fn values(&self) -> Values<'_, K, V, St> {
+        Values::new(self.iter())
+    }
+
+    /// Returns an iterator over batches of up to `chunk_size` entries, in
+    /// the same order as [`iter`](Self::iter); only the last batch may be
+    /// shorter.
+    ///
+    /// Batches are collected lazily from one underlying [`Iter`], so at
+    /// most one batch is materialised at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn iter_chunks(&self, chunk_size: usize) -> impl Iterator<Item = Vec<(&K, &V)>> + '_ {
+        assert!(chunk_size != 0, "chunk size must be non-zero");
+        let mut iter = self.iter();
+        std::iter::from_fn(move || {
+            let mut chunk = Vec::with_capacity(chunk_size.min(iter.len()));
+            chunk.extend(iter.by_ref().take(chunk_size));
+            (!chunk.is_empty()).then_some(chunk)
+        })
+    }
+
+    /// Returns up to `n` entries chosen uniformly at random, without
+    /// replacement: exactly `min(n, len)` distinct entries.
+    ///
+    /// Reservoir sampling over one DFS walk: O(len) time, but only the `n`
+    /// sampled references are held, never the whole entry set. The order
+    /// of the returned entries is unspecified.
+    #[cfg(feature = "rand")]
+    #[must_use]
+    pub fn sample<R: Rng + ?Sized>(&self, n: usize, rng: &mut R) -> Vec<(&K, &V)> {
+        let mut reservoir = Vec::with_capacity(n.min(self.size));
+        for (seen, pair) in self.iter().enumerate() {
+            if seen < n {
+                reservoir.push(pair);
+            } else {
+                let slot = rng.random_range(0..=seen);
+                if slot < n {
+                    reservoir[slot] = pair;
+                }
+            }
+        }
+        reservoir
+    }
+
+    /// Returns a formatter that prints every entry as `{k: v, ...}`, in
+    /// iteration order.
+    ///
+    /// The map's own `Debug` shows only `len` and `adhash`, so it needs no
+    /// bounds on `K` or `V`; use this for small maps in tests and logs.
+    #[must_use]
+    pub fn debug_entries(&self) -> impl fmt::Debug + '_
+    where
+        K: fmt::Debug,
+        V: fmt::Debug,
+    {
+        DebugEntries(self)
+    }
+
+    /// Empties the map, returning an iterator over the owned `(K, V)` pairs.
+    ///
+    /// The map is empty (`len() == 0`, `adhash() == adhash::EMPTY`) as soon as this
+    /// returns, whether or not the iterator is consumed. Live entries are
+    /// moved out of the taken arenas, without cloning; arena capacity is
+    /// not retained. All checkpoints taken before the call are invalidated.
+    pub fn drain(&mut self) -> Drain<'_, K, V, St>
+    where
+        St: Default,
+    {
+        let store = mem::take(&mut self.store);
+        self.store.supersede(&store);
+        let root = self.root.take();
+        let size = mem::take(&mut self.size);
+        self.adhash = adhash::EMPTY;
+        Drain::new(IntoIter::new(store, root, size))
+    }
+
+    /// Consumes the map, returning an iterator over its owned keys, in the
+    /// same order as [`into_iter`](IntoIterator::into_iter).
+    #[must_use]
+    pub fn into_keys(self) -> IntoKeys<K, V, St> {
+        IntoKeys::new(self.into_iter())
+    }
+
+    /// Consumes the map, returning an iterator over its owned values, in
+    /// the same order as [`into_iter`](IntoIterator::into_iter).
+    #[must_use]
+    pub fn into_values(self) -> IntoValues<K, V, St> {
+        IntoValues::new(self.into_iter())
+    }
+
+    /// Returns an iterator over `(&K, &V)` pairs in ascending key order.
+    ///
+    /// Collects and sorts all entries: O(n log n).
+    #[must_use]
+    pub fn iter_sorted(&self) -> SortedIter<'_, K, V>
+    where
+        K: Ord,
+    {
+        SortedIter::new(self.iter())
+    }
+
+    /// Returns an iterator over the entries whose keys fall in `range`, in
+    /// ascending key order.
+    ///
+    /// Collects and sorts all entries, then binary-searches the bounds:
+    /// O(n log n). A range whose start lies past its end yields nothing.
+    #[must_use]
+    pub fn range<Q, R>(&self, range: R) -> SortedIter<'_, K, V>
+    where
+        K: Ord + Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        SortedIter::range(self.iter(), &range)
+    }
+
+    /// Returns all `(&K, &V)` pairs sorted by key, e.g. for deterministic
+    /// export or comparing snapshots in tests. O(n log n).
+    #[must_use]
+    pub fn as_sorted_vec(&self) -> Vec<(&K, &V)>
+    where
+        K: Ord,
+    {
+        let mut pairs: Vec<(&K, &V)> = self.iter().collect();
+        pairs.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        pairs
+    }
+
+    /// Consumes the map, returning all `(K, V)` pairs sorted by key.
+    /// O(n log n); entries are moved out without cloning.
+    #[must_use]
+    pub fn into_sorted_vec(self) -> Vec<(K, V)>
+    where
+        K: Ord,
+    {
+        let mut pairs: Vec<(K, V)> = self.into_iter().collect();
+        pairs.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        pairs
+    }
+
+    /// Returns an iterator over `&K` in ascending order.
+    #[must_use]
+    pub fn keys_sorted(&self) -> impl ExactSizeIterator<Item = &K> + DoubleEndedIterator
+    where
+        K: Ord,
+    {
+        self.iter_sorted().map(|(k, _)| k)
+    }
+
+    /// Returns an iterator over `&V` in ascending order of their keys.
+    #[must_use]
+    pub fn values_sorted(&self) -> impl ExactSizeIterator<Item = &V> + DoubleEndedIterator
+    where
+        K: Ord,
+    {
+        self.iter_sorted().map(|(_, v)| v)
+    }
+
+    /// Returns the entry with the smallest key.
+    ///
+    /// Single pass over all entries tracking the minimum: O(n), no
+    /// allocation.
+    #[must_use]
+    pub fn first_key_value(&self) -> Option<(&K, &V)>
+    where
+        K: Ord,
+    {
+        self.iter().min_by(|a, b| a.0.cmp(b.0))
+    }
+
+    /// Returns the entry with the largest key.
+    ///
+    /// Single pass over all entries tracking the maximum: O(n), no
+    /// allocation.
+    #[must_use]
+    pub fn last_key_value(&self) -> Option<(&K, &V)>
+    where
+        K: Ord,
+    {
+        self.iter().max_by(|a, b| a.0.cmp(b.0))
+    }
+
+    /// Returns `true` if any entry holds a value equal to `value`.
+    ///
+    /// DFS over the entries, stopping at the first match: O(n) worst case.
+    #[must_use]
+    pub fn contains_value(&self, value: &V) -> bool
+    where
+        V: PartialEq,
+    {
+        self.values().any(|v| v == value)
+    }
+
+    /// Returns the first key, in [`iter`](Self::iter) order, whose value
+    /// equals `value`.
+    ///
+    /// Canonical form fixes the DFS order for given contents, so maps with
+    /// equal entries and hashers return the same key. O(n) worst case.
+    #[must_use]
+    pub fn find_key(&self, value: &V) -> Option<&K>
+    where
+        V: PartialEq,
+    {
+        self.iter().find(|(_, v)| *v == value).map(|(k, _)| k)
     }
 }
 
@@ -199,13 +1875,48 @@ impl<K, V> ChampMap<K, V> {
 // Trait impls
 // ---------------------------------------------------------------------------
 
-impl<K, V> Default for ChampMap<K, V> {
+impl<K, V, St: ChampStore<K, V> + Default, S: Default> Default for ChampMapGeneric<K, V, St, S> {
     fn default() -> Self {
-        Self::new()
+        Self::with_store_and_hasher(St::default(), S::default())
+    }
+}
+
+/// Copies the arenas and the root pointer. The copy shares the source's
+/// trie layout index for index, and mutations on either side are independent.
+impl<K: Clone, V: Clone, St: ChampStore<K, V> + Clone, S: Clone> Clone
+    for ChampMapGeneric<K, V, St, S>
+{
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            root: self.root,
+            size: self.size,
+            adhash: self.adhash,
+            hasher: self.hasher.clone(),
+        }
+    }
+
+    /// Reuses `self`'s arena capacity instead of allocating fresh arenas.
+    ///
+    /// The arenas are cleared and only `source`'s live trie is copied in,
+    /// as by [`compact`](Self::compact), so the result holds no dead COW
+    /// copies. Unlike [`clone`](Clone::clone), indices are rewritten:
+    /// checkpoints of `source` are not valid on `self`, and `self`'s own
+    /// checkpoints are invalidated.
+    fn clone_from(&mut self, source: &Self) {
+        self.store.clear();
+        let mut copied = 0;
+        self.root = source
+            .root
+            .map(|root| merge::copy_subtree(&mut self.store, &source.store, root, &mut copied));
+        debug_assert_eq!(copied, source.size);
+        self.size = source.size;
+        self.adhash = source.adhash;
+        self.hasher.clone_from(&source.hasher);
     }
 }
 
-impl<K, V> fmt::Debug for ChampMap<K, V> {
+impl<K, V, St: ChampStore<K, V>, S> fmt::Debug for ChampMapGeneric<K, V, St, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ChampMap")
             .field("len", &self.size)
@@ -214,35 +1925,250 @@ impl<K, V> fmt::Debug for ChampMap<K, V> {
     }
 }
 
-impl<K: Hash + Eq + Clone, V: Hash + Clone> Extend<(K, V)> for ChampMap<K, V> {
+/// Formatter returned by [`ChampMapGeneric::debug_entries`].
+struct DebugEntries<'a, K, V, St: ChampStore<K, V>, S>(&'a ChampMapGeneric<K, V, St, S>);
+
+impl<K, V, St, S> fmt::Debug for DebugEntries<'_, K, V, St, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+    St: ChampStore<K, V>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.0.iter()).finish()
+    }
+}
+
+/// Equal `AdHash` and length is checked first for O(1) rejection; matching
+/// maps are then compared entry by entry to rule out an `AdHash` collision.
+/// Maps whose hashers differ have unrelated `AdHash` values, so for them
+/// only the lengths are checked before the entries.
+impl<K: Hash + Eq, V: PartialEq, St: ChampStore<K, V>, S: BuildHasher> PartialEq
+    for ChampMapGeneric<K, V, St, S>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size
+            && (self.adhash == other.adhash || !adhash::hash_alike(&self.hasher, &other.hasher))
+            && self.entries_in(other)
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, St: ChampStore<K, V>, S: BuildHasher> Eq
+    for ChampMapGeneric<K, V, St, S>
+{
+}
+
+/// Compares the key-sorted `(key, value)` sequences lexicographically, as
+/// [`as_sorted_vec`](ChampMapGeneric::as_sorted_vec) returns them: O(n log n).
+/// Consistent with `PartialEq`, since keys are unique.
+impl<K: Hash + Ord, V: PartialOrd, St: ChampStore<K, V>, S: BuildHasher> PartialOrd
+    for ChampMapGeneric<K, V, St, S>
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.as_sorted_vec().partial_cmp(&other.as_sorted_vec())
+    }
+}
+
+impl<K: Hash + Ord, V: Ord, St: ChampStore<K, V>, S: BuildHasher> Ord
+    for ChampMapGeneric<K, V, St, S>
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_sorted_vec().cmp(&other.as_sorted_vec())
+    }
+}
+
+/// Compares lengths, then looks up each entry of `self` in `other`.
+impl<K, V, St, S, H> PartialEq<HashMap<K, V, H>> for ChampMapGeneric<K, V, St, S>
+where
+    K: Hash + Eq,
+    V: PartialEq,
+    St: ChampStore<K, V>,
+    S: BuildHasher,
+    H: BuildHasher,
+{
+    fn eq(&self, other: &HashMap<K, V, H>) -> bool {
+        self.size == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K, V, St, S, H> PartialEq<ChampMapGeneric<K, V, St, S>> for HashMap<K, V, H>
+where
+    K: Hash + Eq,
+    V: PartialEq,
+    St: ChampStore<K, V>,
+    S: BuildHasher,
+    H: BuildHasher,
+{
+    fn eq(&self, other: &ChampMapGeneric<K, V, St, S>) -> bool {
+        other == self
+    }
+}
+
+/// Writes the length and the `AdHash` the entries have under
+/// [`DefaultHashBuilder`]. For a map that hashes like it — the default —
+/// that is the maintained `AdHash`, so hashing is O(1); under any other
+/// hasher it is recomputed, in O(n). Either way it does not depend on the
+/// map's hasher, so it is consistent with `PartialEq`, which compares
+/// contents: equal maps hash equally. Maps that collide on `AdHash` (2⁻⁶⁴
+/// chance) hash equally but are still told apart by `PartialEq`.
+impl<K: Hash, V: Hash, St: ChampStore<K, V>, S: BuildHasher> Hash for ChampMapGeneric<K, V, St, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let content = if adhash::hash_alike(&self.hasher, &DefaultHashBuilder::default()) {
+            self.adhash
+        } else {
+            self.iter().fold(adhash::EMPTY, |acc, (k, v)| {
+                adhash::combine(
+                    acc,
+                    adhash::entry_adhash(adhash::hash_one(k), adhash::hash_one(v)),
+                )
+            })
+        };
+        state.write_u64(content);
+        state.write_usize(self.size);
+    }
+}
+
+/// Calls [`reserve`](ChampMapGeneric::reserve) with the iterator's lower size bound,
+/// then inserts pair by pair.
+///
+/// `Extend::extend_one` and `extend_reserve` are unstable and cannot be
+/// overridden here. Their defaults come to the same thing: `extend_one`
+/// extends by a one-item iterator, which reserves one slot and calls
+/// [`insert`](ChampMapGeneric::insert).
+impl<K, V, St, S> Extend<(K, V)> for ChampMapGeneric<K, V, St, S>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    St: ChampStore<K, V>,
+    S: BuildHasher,
+{
     fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
         for (k, v) in iter {
             self.insert(k, v);
         }
     }
 }
 
-impl<K: Hash + Eq + Clone, V: Hash + Clone> FromIterator<(K, V)> for ChampMap<K, V> {
+/// Clones each pair on insert, so another map's entries can be added with
+/// `dest.extend(src.iter())`.
+impl<'a, K, V, St, S> Extend<(&'a K, &'a V)> for ChampMapGeneric<K, V, St, S>
+where
+    K: Hash + Eq + Clone + 'a,
+    V: Hash + Clone + 'a,
+    St: ChampStore<K, V>,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (k, v) in iter {
+            self.insert(k.clone(), v.clone());
+        }
+    }
+}
+
+/// Builds through a [`Transient`], so no dead COW copies are allocated. Each
+/// arena is sized exactly once, when the builder is frozen; the shared arena
+/// of [`ChampMapSync`](crate::ChampMapSync) cannot be pre-sized and grows in
+/// chunks instead.
+impl<K, V, St, S> FromIterator<(K, V)> for ChampMapGeneric<K, V, St, S>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    St: ChampStore<K, V> + Default,
+    S: BuildHasher + Default,
+{
     fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
-        let mut map = Self::new();
-        map.extend(iter);
-        map
+        let mut transient = Transient::default();
+        transient.extend(iter);
+        Self::from(transient)
+    }
+}
+
+impl<K, V, St: ChampStore<K, V> + Default, S> From<Transient<K, V, S>>
+    for ChampMapGeneric<K, V, St, S>
+{
+    fn from(transient: Transient<K, V, S>) -> Self {
+        let mut store = St::default();
+        let (root, size, adhash, hasher) = transient.freeze_into(&mut store);
+        Self {
+            store,
+            root,
+            size,
+            adhash,
+            hasher,
+        }
+    }
+}
+
+/// Builds through a [`Transient`]; the result is canonical regardless of the
+/// source's iteration order.
+impl<K, V, St, S> From<HashMap<K, V, S>> for ChampMapGeneric<K, V, St>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    St: ChampStore<K, V> + Default,
+{
+    fn from(map: HashMap<K, V, S>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+/// Builds through a [`Transient`].
+impl<K, V, St> From<BTreeMap<K, V>> for ChampMapGeneric<K, V, St>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    St: ChampStore<K, V> + Default,
+{
+    fn from(map: BTreeMap<K, V>) -> Self {
+        map.into_iter().collect()
     }
 }
 
-impl<K: Hash + Eq, V> ops::Index<&K> for ChampMap<K, V> {
+impl<K, V, St, S, H> From<ChampMapGeneric<K, V, St, S>> for HashMap<K, V, H>
+where
+    K: Hash + Eq,
+    St: ChampStore<K, V>,
+    H: BuildHasher + Default,
+{
+    fn from(map: ChampMapGeneric<K, V, St, S>) -> Self {
+        let mut out = Self::with_capacity_and_hasher(map.len(), H::default());
+        out.extend(map);
+        out
+    }
+}
+
+impl<K, V, St, S, Q> ops::Index<&Q> for ChampMapGeneric<K, V, St, S>
+where
+    K: Hash + Eq + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+    St: ChampStore<K, V>,
+    S: BuildHasher,
+{
     type Output = V;
 
-    fn index(&self, key: &K) -> &V {
+    fn index(&self, key: &Q) -> &V {
         self.get(key).expect("key not found")
     }
 }
 
-impl<'a, K, V> IntoIterator for &'a ChampMap<K, V> {
+impl<'a, K, V, St: ChampStore<K, V>, S> IntoIterator for &'a ChampMapGeneric<K, V, St, S> {
     type Item = (&'a K, &'a V);
-    type IntoIter = Iter<'a, K, V>;
+    type IntoIter = Iter<'a, K, V, St>;
 
-    fn into_iter(self) -> Iter<'a, K, V> {
+    fn into_iter(self) -> Iter<'a, K, V, St> {
         self.iter()
     }
 }
+
+impl<K, V, St: ChampStore<K, V>, S> IntoIterator for ChampMapGeneric<K, V, St, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, St>;
+
+    fn into_iter(self) -> IntoIter<K, V, St> {
+        IntoIter::new(self.store, self.root, self.size)
+    }
+}
@@ -1,192 +1,129 @@
 //! Multi-threaded CHAMP map.
 
-use std::fmt;
-use std::hash::Hash;
-use std::ops;
+use std::hash::{BuildHasher, Hash};
 
-use safe_bump::Idx;
+#[cfg(feature = "rayon")]
+use rayon::iter::{FromParallelIterator, IntoParallelIterator, ParallelExtend, ParallelIterator};
 
-use crate::ChampCheckpoint;
-use crate::adhash;
+use crate::adhash::{self, DefaultHashBuilder};
 use crate::arena_sync::ChampArenaSync;
-use crate::iter::Iter;
-use crate::node::{self, Entry, Node};
-use crate::ops::get::get_recursive;
-use crate::ops::insert::insert_recursive;
-use crate::ops::remove::{RemoveOutcome, remove_recursive};
+use crate::frozen::FrozenChampMap;
+use crate::map::ChampMapGeneric;
+use crate::ops::transform;
+#[cfg(feature = "rayon")]
+use crate::par::ParIter;
 use crate::store::ChampStore;
+#[cfg(feature = "rayon")]
+use crate::transient::Transient;
 
-/// Persistent hash map based on a CHAMP trie, multi-threaded.
+/// Persistent hash map based on a CHAMP trie, multi-threaded: a
+/// [`ChampMapGeneric`] over [`SharedArena`](safe_bump::SharedArena)
+/// storage.
 ///
-/// Identical API to [`ChampMap`](crate::ChampMap) but backed by
-/// [`SharedArena`](safe_bump::SharedArena) for `Send + Sync` support.
-pub struct ChampMapSync<K, V> {
-    store: ChampArenaSync<K, V>,
-    root: Option<safe_bump::Idx<crate::node::Node<K, V>>>,
-    size: usize,
-    adhash: u64,
-}
+/// Identical API to [`ChampMap`](crate::ChampMap), plus `Send + Sync`
+/// support, [`freeze`](Self::freeze) and [`fork`](Self::fork).
+pub type ChampMapSync<K, V, S = DefaultHashBuilder> =
+    ChampMapGeneric<K, V, ChampArenaSync<K, V>, S>;
 
 // ---------------------------------------------------------------------------
 // Construction & accessors — no trait bounds
 // ---------------------------------------------------------------------------
 
 impl<K, V> ChampMapSync<K, V> {
-    /// Creates an empty map.
+    /// Creates an empty map with the [`DefaultHashBuilder`].
     #[must_use]
     pub const fn new() -> Self {
-        Self {
-            store: ChampArenaSync::new(),
-            root: None,
-            size: 0,
-            adhash: 0,
-        }
-    }
-
-    /// Returns the number of key-value pairs.
-    #[must_use]
-    pub const fn len(&self) -> usize {
-        self.size
-    }
-
-    /// Returns `true` if the map contains no entries.
-    #[must_use]
-    pub const fn is_empty(&self) -> bool {
-        self.size == 0
+        Self::with_hasher(DefaultHashBuilder::new())
     }
 
-    /// Returns the current `AdHash` value.
+    /// Creates an empty map. Provided for parity with
+    /// [`ChampMap::with_capacity`](crate::ChampMap::with_capacity): the
+    /// shared arena cannot be pre-sized, so `n` is ignored.
     #[must_use]
-    pub const fn adhash(&self) -> u64 {
-        self.adhash
-    }
-
-    /// Saves the current map state for later rollback.
-    #[must_use]
-    pub fn checkpoint(&self) -> ChampCheckpoint<K, V> {
-        ChampCheckpoint {
-            store: self.store.checkpoint(),
-            root: self.root,
-            size: self.size,
-            adhash: self.adhash,
-        }
-    }
-
-    /// Returns the total number of allocated items in each arena:
-    /// `(nodes, entries, children)`.
-    ///
-    /// Includes dead COW copies — reflects true memory footprint.
-    #[must_use]
-    pub fn arena_len(&self) -> (usize, usize, usize) {
-        self.store.arena_len()
-    }
-
-    /// Restores the map to a previously saved checkpoint.
-    pub fn rollback(&mut self, cp: ChampCheckpoint<K, V>) {
-        self.store.rollback(cp.store);
-        self.root = cp.root;
-        self.size = cp.size;
-        self.adhash = cp.adhash;
+    pub fn with_capacity(n: usize) -> Self {
+        Self::with_capacity_and_hasher(n, DefaultHashBuilder::new())
     }
 }
 
-// ---------------------------------------------------------------------------
-// Read operations
-// ---------------------------------------------------------------------------
-
-impl<K: Hash + Eq, V> ChampMapSync<K, V> {
-    /// Returns a reference to the value associated with `key`.
+impl<K, V, S> ChampMapSync<K, V, S> {
+    /// Creates an empty map that hashes keys and values with `hasher`.
+    ///
+    /// See [`ChampMap::with_hasher`](crate::ChampMap::with_hasher) for
+    /// reproducible `AdHash` values.
     #[must_use]
-    pub fn get(&self, key: &K) -> Option<&V> {
-        let root = self.root?;
-        get_recursive(&self.store, root, adhash::hash_one(key), key, 0)
+    pub const fn with_hasher(hasher: S) -> Self {
+        Self::with_store_and_hasher(ChampArenaSync::new(), hasher)
     }
 
-    /// Returns `true` if the map contains the given key.
+    /// Freezes the map into a [`FrozenChampMap`]: an `Arc`-shared,
+    /// read-only version that clones in O(1) and serves readers on many
+    /// threads at once.
     #[must_use]
-    pub fn contains_key(&self, key: &K) -> bool {
-        self.get(key).is_some()
+    pub fn freeze(self) -> FrozenChampMap<K, V, S> {
+        FrozenChampMap::from(self)
     }
 }
 
 // ---------------------------------------------------------------------------
-// Write operations
+// Value transforms — K: Clone
 // ---------------------------------------------------------------------------
 
-impl<K: Hash + Eq + Clone, V: Hash + Clone> ChampMapSync<K, V> {
-    /// Inserts a key-value pair into the map.
-    ///
-    /// Returns `None` if the key was new, or `Some(old_value)` if an existing
-    /// value was replaced.
+impl<K: Clone, V, S: BuildHasher + Clone> ChampMapSync<K, V, S> {
+    /// Returns a new map with the same keys and every value mapped through `f`.
     ///
-    /// # Panics
-    ///
-    /// Panics if internal arena allocation returns an unexpected `None`.
-    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        let hash = adhash::hash_one(&key);
-        let entry = Entry { hash, key, value };
-
-        if let Some(root) = self.root {
-            let outcome = insert_recursive(&mut self.store, root, entry, 0);
-            self.root = Some(outcome.node);
-            self.adhash = self.adhash.wrapping_add(outcome.adhash_delta);
-            if outcome.old_value.is_none() {
-                self.size += 1;
-            }
-            outcome.old_value
-        } else {
-            let value_hash = adhash::hash_one(&entry.value);
-            let contribution = adhash::entry_adhash(hash, value_hash);
-            let frag = node::fragment(hash, 0);
-            let bit = node::mask(frag);
-            let data_start = self
-                .store
-                .alloc_entries(std::iter::once(entry))
-                .expect("single entry");
-            let new_node = self.store.alloc_node(Node::Inner {
-                data_map: bit,
-                node_map: 0,
-                data_start,
-                children_start: Idx::from_raw(0),
-                adhash: contribution,
-            });
-            self.root = Some(new_node);
-            self.size = 1;
-            self.adhash = contribution;
-            None
-        }
-    }
-
-    /// Removes a key from the map. Returns the removed value, or `None` if
-    /// the key was not present.
-    pub fn remove(&mut self, key: &K) -> Option<V> {
-        let root = self.root?;
-        let hash = adhash::hash_one(key);
-        match remove_recursive(&mut self.store, root, hash, key, 0) {
-            RemoveOutcome::NotFound => None,
-            RemoveOutcome::Removed {
-                node,
-                adhash_delta,
-                removed_value,
-            } => {
-                self.root = node;
-                self.size -= 1;
-                self.adhash = self.adhash.wrapping_sub(adhash_delta);
-                Some(removed_value)
-            }
+    /// Keys are not re-hashed: the key set and trie shape are unchanged, so
+    /// the node structure is copied as-is into a fresh arena and only the
+    /// entries (and `AdHash` values) are rebuilt. Dead COW copies are not
+    /// carried over.
+    #[must_use]
+    pub fn map_values<W: Hash + Clone, F: FnMut(&V) -> W>(
+        &self,
+        mut f: F,
+    ) -> ChampMapSync<K, W, S> {
+        let mut store = ChampArenaSync::new();
+        let root = self.root.map(|root| {
+            transform::map_subtree(&self.store, &self.hasher, &mut store, root, &mut f)
+        });
+        let adhash = root.map_or(adhash::EMPTY, |r| store.get_node(r).adhash());
+        ChampMapSync {
+            store,
+            root,
+            size: self.size,
+            adhash,
+            hasher: self.hasher.clone(),
         }
     }
 }
 
 // ---------------------------------------------------------------------------
-// Iterator stubs
+// Maintenance — K: Clone, V: Clone
 // ---------------------------------------------------------------------------
 
-impl<K, V> ChampMapSync<K, V> {
-    /// Returns an iterator over `(&K, &V)` pairs.
+impl<K: Clone, V: Clone, S: Clone> ChampMapSync<K, V, S> {
+    /// Returns an independent, writable map sharing this one's arena. O(1).
+    ///
+    /// Parent and fork start at the same root, with the same contents and
+    /// `AdHash`, and share every node. Writes path-copy into the shared
+    /// arena and never modify a node, so writes to either map are invisible
+    /// to the other and each costs O(depth), as on a single map. The two may
+    /// be written from different threads.
+    ///
+    /// While the arena is shared, nothing in it is freed:
+    /// [`rollback`](Self::rollback) and [`clear`](Self::clear) restore or
+    /// empty the map but leave its items in place, and
+    /// [`arena_len`](Self::arena_len) counts the items of both maps. Once
+    /// one of them is dropped, the other frees memory as usual again;
+    /// [`compact`](Self::compact) moves a map into an arena of its own at
+    /// any time. Checkpoints taken before the fork are valid in both maps.
     #[must_use]
-    pub fn iter(&self) -> Iter<'_, K, V> {
-        Iter::new(&self.store, self.root)
+    pub fn fork(&self) -> Self {
+        Self {
+            store: self.store.share(),
+            root: self.root,
+            size: self.size,
+            adhash: self.adhash,
+            hasher: self.hasher.clone(),
+        }
     }
 }
 
@@ -194,50 +131,51 @@ impl<K, V> ChampMapSync<K, V> {
 // Trait impls
 // ---------------------------------------------------------------------------
 
-impl<K, V> Default for ChampMapSync<K, V> {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl<K, V> fmt::Debug for ChampMapSync<K, V> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("ChampMapSync")
-            .field("len", &self.size)
-            .field("adhash", &format_args!("{:#018x}", self.adhash))
-            .finish_non_exhaustive()
-    }
-}
-
-impl<K: Hash + Eq + Clone, V: Hash + Clone> Extend<(K, V)> for ChampMapSync<K, V> {
-    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
-        for (k, v) in iter {
-            self.insert(k, v);
-        }
-    }
-}
+/// Enables `map.par_iter()` through rayon's `IntoParallelRefIterator`.
+#[cfg(feature = "rayon")]
+impl<'a, K: Send + Sync, V: Send + Sync, S> IntoParallelIterator for &'a ChampMapSync<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type Iter = ParIter<'a, K, V>;
 
-impl<K: Hash + Eq + Clone, V: Hash + Clone> FromIterator<(K, V)> for ChampMapSync<K, V> {
-    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
-        let mut map = Self::new();
-        map.extend(iter);
-        map
+    fn into_par_iter(self) -> ParIter<'a, K, V> {
+        ParIter::new(&self.store, self.root)
     }
 }
 
-impl<K: Hash + Eq, V> ops::Index<&K> for ChampMapSync<K, V> {
-    type Output = V;
-
-    fn index(&self, key: &K) -> &V {
-        self.get(key).expect("key not found")
+/// Builds one [`Transient`] per rayon partition, freezes each, and merges
+/// them with [`union`](ChampMapSync::union). The result is canonical, so it
+/// is identical to a sequential build; for duplicate keys the value that
+/// comes last in iteration order wins, as with `FromIterator`.
+#[cfg(feature = "rayon")]
+impl<K, V, S> FromParallelIterator<(K, V)> for ChampMapSync<K, V, S>
+where
+    K: Hash + Eq + Clone + Send + Sync,
+    V: Hash + Clone + Send + Sync,
+    S: BuildHasher + Default + Clone + Send,
+{
+    fn from_par_iter<I: IntoParallelIterator<Item = (K, V)>>(par_iter: I) -> Self {
+        par_iter
+            .into_par_iter()
+            .fold(Transient::default, |mut transient, (k, v)| {
+                transient.insert(k, v);
+                transient
+            })
+            .map(Transient::freeze_sync)
+            .reduce(Self::default, |a, b| a.union(&b, |_, newer| newer.clone()))
     }
 }
 
-impl<'a, K, V> IntoIterator for &'a ChampMapSync<K, V> {
-    type Item = (&'a K, &'a V);
-    type IntoIter = Iter<'a, K, V>;
-
-    fn into_iter(self) -> Iter<'a, K, V> {
-        self.iter()
+/// Builds the new pairs in parallel as in `FromParallelIterator`, then
+/// unions them into the map; new values replace existing ones.
+#[cfg(feature = "rayon")]
+impl<K, V, S> ParallelExtend<(K, V)> for ChampMapSync<K, V, S>
+where
+    K: Hash + Eq + Clone + Send + Sync,
+    V: Hash + Clone + Send + Sync,
+    S: BuildHasher + Default + Clone + Send,
+{
+    fn par_extend<I: IntoParallelIterator<Item = (K, V)>>(&mut self, par_iter: I) {
+        let other: Self = par_iter.into_par_iter().collect();
+        *self = self.union(&other, |_, newer| newer.clone());
     }
 }
@@ -5,12 +5,30 @@ use std::fmt;
 use safe_bump::Idx;
 
 /// Bits per trie level (5 → 32-way branching).
+#[cfg(not(feature = "branch-16"))]
 pub const BITS_PER_LEVEL: u32 = 5;
 
-/// Maximum bit-shift value (depth 12, last level uses 4 bits).
-pub const MAX_SHIFT: u32 = 60;
+/// Bits per trie level (4 → 16-way branching, `branch-16` feature).
+#[cfg(feature = "branch-16")]
+pub const BITS_PER_LEVEL: u32 = 4;
+
+// Bitmaps are `u32`: one bit per slot.
+const _: () = assert!(BITS_PER_LEVEL >= 1 && BITS_PER_LEVEL <= 5);
+
+/// Number of slots per inner node.
+pub const BRANCHING: usize = 1 << BITS_PER_LEVEL;
+
+/// Maximum bit-shift value: the shift of the deepest inner level, which may
+/// use fewer than [`BITS_PER_LEVEL`] bits (60 for both 5 and 4 bits — depth
+/// 12 with 4 bits left, or depth 15 with all 4).
+pub const MAX_SHIFT: u32 = (u64::BITS - 1) / BITS_PER_LEVEL * BITS_PER_LEVEL;
+
+/// Depth of collision nodes, one level below [`MAX_SHIFT`]: 13 for 5 bits
+/// per level, 16 for 4.
+pub const COLLISION_DEPTH: usize = (MAX_SHIFT / BITS_PER_LEVEL) as usize + 1;
 
 /// Inline entry storing a key-value pair with its precomputed hash.
+#[derive(Clone)]
 pub struct Entry<K, V> {
     /// Precomputed 64-bit hash of the key.
     pub hash: u64,
@@ -50,7 +68,7 @@ pub enum Node<K, V> {
         /// Index of the first entry in the entries arena.
         entries_start: Idx<Entry<K, V>>,
         /// Number of collision entries.
-        entries_len: u8,
+        entries_len: u32,
         /// `AdHash` of this subtree.
         adhash: u64,
     },
@@ -60,14 +78,25 @@ pub enum Node<K, V> {
 // Bitmap helpers
 // ---------------------------------------------------------------------------
 
-/// Extracts the 5-bit hash fragment at the given bit-shift depth.
+/// Extracts the [`BITS_PER_LEVEL`]-bit hash fragment at the given bit-shift
+/// depth, counting from the most significant bit.
+///
+/// The root level takes the top bits, so slots in ascending fragment order
+/// hold ascending hashes and a depth-first walk meets entries in numeric
+/// hash order. The deepest level, at [`MAX_SHIFT`], may have fewer bits
+/// left; they are padded with zeros at the bottom, which keeps that order.
 #[inline]
 #[must_use]
 pub const fn fragment(hash: u64, shift: u32) -> u32 {
-    ((hash >> shift) & 0x1F) as u32
+    let frag = if shift + BITS_PER_LEVEL <= u64::BITS {
+        hash >> (u64::BITS - BITS_PER_LEVEL - shift)
+    } else {
+        hash << (shift + BITS_PER_LEVEL - u64::BITS)
+    };
+    (frag & ((1 << BITS_PER_LEVEL) - 1)) as u32
 }
 
-/// Returns the single-bit mask for the given fragment (0..31).
+/// Returns the single-bit mask for the given fragment (`0..BRANCHING`).
 #[inline]
 #[must_use]
 pub const fn mask(frag: u32) -> u32 {
@@ -0,0 +1,131 @@
+//! Change detection between two tries in the same store.
+
+use safe_bump::Idx;
+
+use crate::diff::Change;
+use crate::node::{self, Entry, Node};
+use crate::ops::merge::{SlotRef, occupied, slot_ref};
+use crate::store::ChampStore;
+
+/// Appends to `out` the changes that turn the `old` position into the `new`
+/// one. Both sides live in `store`.
+///
+/// Identical or equal-`AdHash` subtrees are skipped without being walked.
+/// Only child-against-child positions are recursed into; every other pairing
+/// is resolved by matching the keys of the two sides directly.
+pub fn diff_slots<K, V, S>(
+    store: &S,
+    old: Option<SlotRef<K, V>>,
+    new: Option<SlotRef<K, V>>,
+    out: &mut Vec<Change<K>>,
+) where
+    K: Eq + Clone,
+    V: PartialEq,
+    S: ChampStore<K, V>,
+{
+    if let (Some(SlotRef::Child(a)), Some(SlotRef::Child(b))) = (old, new) {
+        diff_nodes(store, a, b, out);
+    } else {
+        let mut old_entries = Vec::new();
+        let mut new_entries = Vec::new();
+        collect(store, old, &mut old_entries);
+        collect(store, new, &mut new_entries);
+        diff_entries(&old_entries, &new_entries, out);
+    }
+}
+
+/// Diffs two aligned nodes.
+fn diff_nodes<K, V, S>(store: &S, a: Idx<Node<K, V>>, b: Idx<Node<K, V>>, out: &mut Vec<Change<K>>)
+where
+    K: Eq + Clone,
+    V: PartialEq,
+    S: ChampStore<K, V>,
+{
+    let node_a = store.get_node(a);
+    let node_b = store.get_node(b);
+    if a == b || node_a.adhash() == node_b.adhash() {
+        return;
+    }
+    if let (Node::Inner { .. }, Node::Inner { .. }) = (node_a, node_b) {
+        let mut bits = occupied(node_a) | occupied(node_b);
+        while bits != 0 {
+            let bit = bits & bits.wrapping_neg();
+            bits &= bits - 1;
+            diff_slots(
+                store,
+                slot_ref(store, node_a, bit),
+                slot_ref(store, node_b, bit),
+                out,
+            );
+        }
+    } else {
+        let mut old_entries = Vec::new();
+        let mut new_entries = Vec::new();
+        collect_node(store, a, &mut old_entries);
+        collect_node(store, b, &mut new_entries);
+        diff_entries(&old_entries, &new_entries, out);
+    }
+}
+
+/// Classifies two entry lists by key.
+fn diff_entries<K: Eq + Clone, V: PartialEq>(
+    old: &[&Entry<K, V>],
+    new: &[&Entry<K, V>],
+    out: &mut Vec<Change<K>>,
+) {
+    let same_key = |a: &Entry<K, V>, b: &Entry<K, V>| a.hash == b.hash && a.key == b.key;
+    for a in old {
+        match new.iter().find(|b| same_key(a, b)) {
+            Some(b) if a.value != b.value => out.push(Change::Updated(a.key.clone())),
+            Some(_) => {}
+            None => out.push(Change::Removed(a.key.clone())),
+        }
+    }
+    for b in new {
+        if !old.iter().any(|a| same_key(a, b)) {
+            out.push(Change::Added(b.key.clone()));
+        }
+    }
+}
+
+/// Collects every entry held by a slot.
+fn collect<'a, K, V, S: ChampStore<K, V>>(
+    store: &'a S,
+    slot: Option<SlotRef<K, V>>,
+    out: &mut Vec<&'a Entry<K, V>>,
+) {
+    match slot {
+        None => {}
+        Some(SlotRef::Data(idx)) => out.push(store.get_entry(idx)),
+        Some(SlotRef::Child(idx)) => collect_node(store, idx, out),
+    }
+}
+
+/// Collects every entry of the subtree rooted at `idx`.
+fn collect_node<'a, K, V, S: ChampStore<K, V>>(
+    store: &'a S,
+    idx: Idx<Node<K, V>>,
+    out: &mut Vec<&'a Entry<K, V>>,
+) {
+    let node = store.get_node(idx);
+    let (data_start, children_start) = match *node {
+        Node::Inner {
+            data_start,
+            children_start,
+            ..
+        } => (data_start, Some(children_start)),
+        Node::Collision { entries_start, .. } => (entries_start, None),
+    };
+    for i in 0..node.data_len() {
+        out.push(store.get_entry(node::offset(data_start, i)));
+    }
+    if let Some(children_start) = children_start {
+        for i in 0..node.children_len() {
+            collect_node(
+                store,
+                *store.get_child(node::offset(children_start, i)),
+                out,
+            );
+        }
+    }
+}
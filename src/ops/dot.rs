@@ -0,0 +1,104 @@
+//! Graphviz DOT rendering of a trie.
+
+use std::fmt::Debug;
+use std::io::{self, Write};
+
+use safe_bump::Idx;
+
+use crate::node::{self, Node};
+use crate::store::ChampStore;
+
+/// Writes the trie under `root` as a DOT digraph.
+///
+/// Nodes are named after their arena index (`n<idx>`) and inline entries
+/// after theirs (`e<idx>`), so a map's dumps before and after a write show
+/// which nodes were path-copied. Edges out of an inner node are labeled with
+/// the hash fragment of their slot.
+pub fn write_graph<K, V, S, W>(
+    store: &S,
+    root: Option<Idx<Node<K, V>>>,
+    w: &mut W,
+) -> io::Result<()>
+where
+    K: Debug,
+    S: ChampStore<K, V>,
+    W: Write,
+{
+    writeln!(w, "digraph champ {{")?;
+    writeln!(w, "    node [fontname=monospace];")?;
+    if let Some(root) = root {
+        write_node(store, root, w)?;
+    }
+    writeln!(w, "}}")
+}
+
+fn write_node<K, V, S, W>(store: &S, idx: Idx<Node<K, V>>, w: &mut W) -> io::Result<()>
+where
+    K: Debug,
+    S: ChampStore<K, V>,
+    W: Write,
+{
+    let id = idx.into_raw();
+    match *store.get_node(idx) {
+        Node::Inner {
+            data_map,
+            node_map,
+            data_start,
+            children_start,
+            ..
+        } => {
+            writeln!(
+                w,
+                "    n{id} [shape=ellipse, label=\"data_map {data_map:#x}\\nnode_map {node_map:#x}\"];"
+            )?;
+            for (i, frag) in fragments(data_map).enumerate() {
+                let entry = node::offset(data_start, i);
+                write_entry(store, entry, w)?;
+                writeln!(w, "    n{id} -> e{} [label=\"{frag}\"];", entry.into_raw())?;
+            }
+            for (i, frag) in fragments(node_map).enumerate() {
+                let child = *store.get_child(node::offset(children_start, i));
+                writeln!(w, "    n{id} -> n{} [label=\"{frag}\"];", child.into_raw())?;
+                write_node(store, child, w)?;
+            }
+        }
+        Node::Collision {
+            hash,
+            entries_start,
+            entries_len,
+            ..
+        } => {
+            writeln!(
+                w,
+                "    n{id} [shape=octagon, label=\"collision {hash:#018x}\"];"
+            )?;
+            for i in 0..entries_len as usize {
+                let entry = node::offset(entries_start, i);
+                write_entry(store, entry, w)?;
+                writeln!(w, "    n{id} -> e{};", entry.into_raw())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_entry<K, V, S, W>(store: &S, idx: Idx<node::Entry<K, V>>, w: &mut W) -> io::Result<()>
+where
+    K: Debug,
+    S: ChampStore<K, V>,
+    W: Write,
+{
+    let label = format!("{:?}", store.get_entry(idx).key);
+    writeln!(
+        w,
+        "    e{} [shape=box, label=\"{}\"];",
+        idx.into_raw(),
+        label.escape_debug()
+    )
+}
+
+/// Yields the positions of the set bits of `bitmap`, lowest first — the
+/// fragments of the slots it marks, in storage order.
+fn fragments(bitmap: u32) -> impl Iterator<Item = u32> {
+    (0..u32::BITS).filter(move |&b| bitmap & (1 << b) != 0)
+}
@@ -1,24 +1,28 @@
 //! Lookup operation — traverses the trie to find a key.
 
+use std::borrow::Borrow;
+
 use safe_bump::Idx;
 
-use crate::node::{self, Node};
-use crate::store::ChampStore;
+use crate::node::{self, Entry, Node};
+use crate::store::{ChampStore, StoreError};
 
 /// Searches for `key` in the subtree rooted at `node_idx`.
 ///
-/// Returns a reference to the value if found.
-pub fn get_recursive<'a, K, V, S>(
+/// `key` may be any borrowed form of the stored key type. Returns the
+/// stored entry if found, so callers can reach both key and value.
+pub fn get_recursive<'a, K, V, S, Q>(
     store: &'a S,
     node_idx: Idx<Node<K, V>>,
     hash: u64,
-    key: &K,
+    key: &Q,
     shift: u32,
-) -> Option<&'a V>
+) -> Option<&'a Entry<K, V>>
 where
-    K: Eq + 'a,
+    K: Borrow<Q> + 'a,
     V: 'a,
     S: ChampStore<K, V>,
+    Q: Eq + ?Sized,
 {
     match *store.get_node(node_idx) {
         Node::Inner {
@@ -35,8 +39,8 @@ where
                 // Position has an inline entry.
                 let idx = node::index(data_map, bit);
                 let entry = store.get_entry(node::offset(data_start, idx));
-                if entry.hash == hash && entry.key == *key {
-                    Some(&entry.value)
+                if entry.hash == hash && entry.key.borrow() == key {
+                    Some(entry)
                 } else {
                     None
                 }
@@ -60,13 +64,69 @@ where
                 return None;
             }
             // Linear search through collision entries.
-            for i in 0..usize::from(entries_len) {
+            for i in 0..entries_len as usize {
                 let entry = store.get_entry(node::offset(entries_start, i));
-                if entry.key == *key {
-                    return Some(&entry.value);
+                if entry.key.borrow() == key {
+                    return Some(entry);
                 }
             }
             None
         }
     }
 }
+
+/// Fallible [`get_recursive`]: reads through the store's `try_get_*`
+/// accessors and stops at the first one that fails.
+pub fn try_get_recursive<'a, K, V, S, Q>(
+    store: &'a S,
+    node_idx: Idx<Node<K, V>>,
+    hash: u64,
+    key: &Q,
+    shift: u32,
+) -> Result<Option<&'a Entry<K, V>>, StoreError>
+where
+    K: Borrow<Q> + 'a,
+    V: 'a,
+    S: ChampStore<K, V>,
+    Q: Eq + ?Sized,
+{
+    match *store.try_get_node(node_idx)? {
+        Node::Inner {
+            data_map,
+            node_map,
+            data_start,
+            children_start,
+            ..
+        } => {
+            let bit = node::mask(node::fragment(hash, shift));
+            if data_map & bit != 0 {
+                let idx = node::index(data_map, bit);
+                let entry = store.try_get_entry(node::offset(data_start, idx))?;
+                Ok((entry.hash == hash && entry.key.borrow() == key).then_some(entry))
+            } else if node_map & bit != 0 {
+                let idx = node::index(node_map, bit);
+                let child_idx = *store.try_get_child(node::offset(children_start, idx))?;
+                try_get_recursive(store, child_idx, hash, key, shift + node::BITS_PER_LEVEL)
+            } else {
+                Ok(None)
+            }
+        }
+        Node::Collision {
+            hash: node_hash,
+            entries_start,
+            entries_len,
+            ..
+        } => {
+            if hash != node_hash {
+                return Ok(None);
+            }
+            for i in 0..entries_len as usize {
+                let entry = store.try_get_entry(node::offset(entries_start, i))?;
+                if entry.key.borrow() == key {
+                    return Ok(Some(entry));
+                }
+            }
+            Ok(None)
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! Insertion operation — COW path-copy insert with `AdHash` maintenance.
 
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash};
 
 use safe_bump::Idx;
 
@@ -19,8 +19,9 @@ pub struct InsertOutcome<K, V> {
 }
 
 /// Inserts `entry` into the subtree rooted at `node_idx` via COW path-copy.
-pub fn insert_recursive<K, V, S>(
+pub fn insert_recursive<K, V, S, H>(
     store: &mut S,
+    hasher: &H,
     node_idx: Idx<Node<K, V>>,
     entry: Entry<K, V>,
     shift: u32,
@@ -29,6 +30,7 @@ where
     K: Hash + Eq + Clone,
     V: Hash + Clone,
     S: ChampStore<K, V>,
+    H: BuildHasher,
 {
     let node = *store.get_node(node_idx);
     match node {
@@ -40,6 +42,7 @@ where
             adhash,
         } => insert_into_inner(
             store,
+            hasher,
             data_map,
             node_map,
             data_start,
@@ -53,17 +56,47 @@ where
             entries_start,
             entries_len,
             adhash,
-        } => insert_into_collision(store, node_hash, entries_start, entries_len, adhash, entry),
+        } => insert_into_collision(
+            store,
+            hasher,
+            node_hash,
+            entries_start,
+            entries_len,
+            adhash,
+            entry,
+        ),
     }
 }
 
+/// Allocates a root node holding a single entry, for insertion into an empty map.
+pub fn singleton<K, V, S, H>(store: &mut S, hasher: &H, entry: Entry<K, V>) -> Idx<Node<K, V>>
+where
+    V: Hash,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    let contribution = adhash::entry_adhash(entry.hash, hasher.hash_one(&entry.value));
+    let bit = node::mask(node::fragment(entry.hash, 0));
+    let data_start = store
+        .alloc_entries(std::iter::once(entry))
+        .expect("single entry");
+    store.alloc_node(Node::Inner {
+        data_map: bit,
+        node_map: 0,
+        data_start,
+        children_start: Idx::from_raw(0),
+        adhash: contribution,
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Inner node insert
 // ---------------------------------------------------------------------------
 
-#[allow(clippy::too_many_arguments)]
-fn insert_into_inner<K, V, S>(
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
+fn insert_into_inner<K, V, S, H>(
     store: &mut S,
+    hasher: &H,
     data_map: u32,
     node_map: u32,
     data_start: Idx<Entry<K, V>>,
@@ -76,6 +109,7 @@ where
     K: Hash + Eq + Clone,
     V: Hash + Clone,
     S: ChampStore<K, V>,
+    H: BuildHasher,
 {
     let frag = node::fragment(entry.hash, shift);
     let bit = node::mask(frag);
@@ -87,15 +121,15 @@ where
         let (existing_hash, existing_key_eq, old_contrib, old_value) = {
             let e = store.get_entry(node::offset(data_start, pos));
             let eq = e.hash == entry.hash && e.key == entry.key;
-            let contrib = adhash::entry_adhash(e.hash, adhash::hash_one(&e.value));
+            let contrib = adhash::entry_adhash(e.hash, hasher.hash_one(&e.value));
             let val = e.value.clone();
             (e.hash, eq, contrib, val)
         };
 
         if existing_key_eq {
             // Same key → update value.
-            let new_contrib = adhash::entry_adhash(entry.hash, adhash::hash_one(&entry.value));
-            let delta = new_contrib.wrapping_sub(old_contrib);
+            let new_contrib = adhash::entry_adhash(entry.hash, hasher.hash_one(&entry.value));
+            let delta = adhash::difference(new_contrib, old_contrib);
             let entries = build_entries_replacing(store, data_start, data_len, pos, entry);
             let new_data = store.alloc_entries(entries).expect("non-empty");
             let new_node = store.alloc_node(Node::Inner {
@@ -103,7 +137,7 @@ where
                 node_map,
                 data_start: new_data,
                 children_start,
-                adhash: adhash.wrapping_add(delta),
+                adhash: adhash::combine(adhash, delta),
             });
             InsertOutcome {
                 node: new_node,
@@ -113,11 +147,16 @@ where
         } else {
             // Different key at same position → push both into a subtree.
             let existing_cloned = clone_entry(store, node::offset(data_start, pos));
-            let new_contrib = adhash::entry_adhash(entry.hash, adhash::hash_one(&entry.value));
+            let new_contrib = adhash::entry_adhash(entry.hash, hasher.hash_one(&entry.value));
             let _ = existing_hash; // used above for eq check
 
-            let subtree =
-                create_subtree(store, existing_cloned, entry, shift + node::BITS_PER_LEVEL);
+            let subtree = create_subtree(
+                store,
+                hasher,
+                existing_cloned,
+                entry,
+                shift + node::BITS_PER_LEVEL,
+            );
 
             let new_data_map = data_map & !bit;
             let new_node_map = node_map | bit;
@@ -135,7 +174,7 @@ where
                 node_map: new_node_map,
                 data_start: new_data,
                 children_start: new_children,
-                adhash: adhash.wrapping_add(new_contrib),
+                adhash: adhash::combine(adhash, new_contrib),
             });
             InsertOutcome {
                 node: new_node,
@@ -147,7 +186,13 @@ where
         // Position has child subtree → recurse.
         let child_pos = node::index(node_map, bit);
         let old_child = *store.get_child(node::offset(children_start, child_pos));
-        let outcome = insert_recursive(store, old_child, entry, shift + node::BITS_PER_LEVEL);
+        let outcome = insert_recursive(
+            store,
+            hasher,
+            old_child,
+            entry,
+            shift + node::BITS_PER_LEVEL,
+        );
 
         let children =
             build_children_replacing(store, children_start, children_len, child_pos, outcome.node);
@@ -158,7 +203,7 @@ where
             node_map,
             data_start,
             children_start: new_children,
-            adhash: adhash.wrapping_add(outcome.adhash_delta),
+            adhash: adhash::combine(adhash, outcome.adhash_delta),
         });
         InsertOutcome {
             node: new_node,
@@ -169,7 +214,7 @@ where
         // Position empty → add inline entry.
         let new_data_map = data_map | bit;
         let insert_at = node::index(new_data_map, bit);
-        let new_contrib = adhash::entry_adhash(entry.hash, adhash::hash_one(&entry.value));
+        let new_contrib = adhash::entry_adhash(entry.hash, hasher.hash_one(&entry.value));
         let entries = build_entries_inserting(store, data_start, data_len, insert_at, entry);
         let new_data = store.alloc_entries(entries).expect("non-empty");
 
@@ -178,7 +223,7 @@ where
             node_map,
             data_start: new_data,
             children_start,
-            adhash: adhash.wrapping_add(new_contrib),
+            adhash: adhash::combine(adhash, new_contrib),
         });
         InsertOutcome {
             node: new_node,
@@ -192,11 +237,12 @@ where
 // Collision node insert
 // ---------------------------------------------------------------------------
 
-fn insert_into_collision<K, V, S>(
+fn insert_into_collision<K, V, S, H>(
     store: &mut S,
+    hasher: &H,
     node_hash: u64,
     entries_start: Idx<Entry<K, V>>,
-    entries_len: u8,
+    entries_len: u32,
     adhash: u64,
     entry: Entry<K, V>,
 ) -> InsertOutcome<K, V>
@@ -204,28 +250,29 @@ where
     K: Hash + Eq + Clone,
     V: Hash + Clone,
     S: ChampStore<K, V>,
+    H: BuildHasher,
 {
-    let len = usize::from(entries_len);
+    let len = entries_len as usize;
 
     // Search for existing key.
     for i in 0..len {
         let (key_eq, old_contrib, old_val) = {
             let e = store.get_entry(node::offset(entries_start, i));
             let eq = e.key == entry.key;
-            let contrib = adhash::entry_adhash(e.hash, adhash::hash_one(&e.value));
+            let contrib = adhash::entry_adhash(e.hash, hasher.hash_one(&e.value));
             let val = e.value.clone();
             (eq, contrib, val)
         };
         if key_eq {
-            let new_contrib = adhash::entry_adhash(entry.hash, adhash::hash_one(&entry.value));
-            let delta = new_contrib.wrapping_sub(old_contrib);
+            let new_contrib = adhash::entry_adhash(entry.hash, hasher.hash_one(&entry.value));
+            let delta = adhash::difference(new_contrib, old_contrib);
             let entries = build_entries_replacing(store, entries_start, len, i, entry);
             let new_start = store.alloc_entries(entries).expect("non-empty");
             let new_node = store.alloc_node(Node::Collision {
                 hash: node_hash,
                 entries_start: new_start,
                 entries_len,
-                adhash: adhash.wrapping_add(delta),
+                adhash: adhash::combine(adhash, delta),
             });
             return InsertOutcome {
                 node: new_node,
@@ -236,10 +283,10 @@ where
     }
 
     // Key not found → append.
-    let new_contrib = adhash::entry_adhash(entry.hash, adhash::hash_one(&entry.value));
+    let new_contrib = adhash::entry_adhash(entry.hash, hasher.hash_one(&entry.value));
     let new_len = entries_len
         .checked_add(1)
-        .expect("collision node overflow (>255 entries)");
+        .expect("collision node overflow (>u32::MAX entries)");
     let mut entries = Vec::with_capacity(len + 1);
     for i in 0..len {
         entries.push(clone_entry(store, node::offset(entries_start, i)));
@@ -250,7 +297,7 @@ where
         hash: node_hash,
         entries_start: new_start,
         entries_len: new_len,
-        adhash: adhash.wrapping_add(new_contrib),
+        adhash: adhash::combine(adhash, new_contrib),
     });
     InsertOutcome {
         node: new_node,
@@ -267,8 +314,9 @@ where
 ///
 /// Recursively descends until hash fragments differ, or creates a collision
 /// node at `MAX_SHIFT`.
-fn create_subtree<K, V, S>(
+pub fn create_subtree<K, V, S, H>(
     store: &mut S,
+    hasher: &H,
     e1: Entry<K, V>,
     e2: Entry<K, V>,
     shift: u32,
@@ -277,17 +325,18 @@ where
     K: Hash + Clone,
     V: Hash + Clone,
     S: ChampStore<K, V>,
+    H: BuildHasher,
 {
     if shift > node::MAX_SHIFT {
         let hash = e1.hash;
-        let c1 = adhash::entry_adhash(e1.hash, adhash::hash_one(&e1.value));
-        let c2 = adhash::entry_adhash(e2.hash, adhash::hash_one(&e2.value));
+        let c1 = adhash::entry_adhash(e1.hash, hasher.hash_one(&e1.value));
+        let c2 = adhash::entry_adhash(e2.hash, hasher.hash_one(&e2.value));
         let start = store.alloc_entries([e1, e2]).expect("two entries");
         return store.alloc_node(Node::Collision {
             hash,
             entries_start: start,
             entries_len: 2,
-            adhash: c1.wrapping_add(c2),
+            adhash: adhash::combine(c1, c2),
         });
     }
 
@@ -295,7 +344,7 @@ where
     let f2 = node::fragment(e2.hash, shift);
 
     if f1 == f2 {
-        let child = create_subtree(store, e1, e2, shift + node::BITS_PER_LEVEL);
+        let child = create_subtree(store, hasher, e1, e2, shift + node::BITS_PER_LEVEL);
         let child_adhash = store.get_node(child).adhash();
         let children_start = store.alloc_children([child]).expect("one child");
         store.alloc_node(Node::Inner {
@@ -306,8 +355,8 @@ where
             adhash: child_adhash,
         })
     } else {
-        let c1 = adhash::entry_adhash(e1.hash, adhash::hash_one(&e1.value));
-        let c2 = adhash::entry_adhash(e2.hash, adhash::hash_one(&e2.value));
+        let c1 = adhash::entry_adhash(e1.hash, hasher.hash_one(&e1.value));
+        let c2 = adhash::entry_adhash(e2.hash, hasher.hash_one(&e2.value));
         let entries: [Entry<K, V>; 2] = if f1 < f2 { [e1, e2] } else { [e2, e1] };
         let data_start = store.alloc_entries(entries).expect("two entries");
         store.alloc_node(Node::Inner {
@@ -315,7 +364,7 @@ where
             node_map: 0,
             data_start,
             children_start: Idx::from_raw(0),
-            adhash: c1.wrapping_add(c2),
+            adhash: adhash::combine(c1, c2),
         })
     }
 }
@@ -324,7 +373,7 @@ where
 // Entry / children block builders
 // ---------------------------------------------------------------------------
 
-fn clone_entry<K: Clone, V: Clone, S: ChampStore<K, V>>(
+pub fn clone_entry<K: Clone, V: Clone, S: ChampStore<K, V>>(
     store: &S,
     idx: Idx<Entry<K, V>>,
 ) -> Entry<K, V> {
@@ -354,7 +403,7 @@ fn build_entries_inserting<K: Clone, V: Clone, S: ChampStore<K, V>>(
     out
 }
 
-fn build_entries_replacing<K: Clone, V: Clone, S: ChampStore<K, V>>(
+pub fn build_entries_replacing<K: Clone, V: Clone, S: ChampStore<K, V>>(
     store: &S,
     start: Idx<Entry<K, V>>,
     len: usize,
@@ -405,7 +454,7 @@ fn build_children_inserting<K, V, S: ChampStore<K, V>>(
     out
 }
 
-fn build_children_replacing<K, V, S: ChampStore<K, V>>(
+pub fn build_children_replacing<K, V, S: ChampStore<K, V>>(
     store: &S,
     start: Idx<Idx<Node<K, V>>>,
     len: usize,
@@ -428,7 +477,7 @@ fn build_children_replacing<K, V, S: ChampStore<K, V>>(
 /// Used when a bitmap is zero (no entries/children) and the start index
 /// is dead state — never accessed because the bitmap guards it.
 #[allow(clippy::option_if_let_else)]
-const fn alloc_or_sentinel<T>(idx: Option<Idx<T>>) -> Idx<T> {
+pub const fn alloc_or_sentinel<T>(idx: Option<Idx<T>>) -> Idx<T> {
     match idx {
         Some(i) => i,
         None => Idx::from_raw(0),
@@ -0,0 +1,86 @@
+//! Recursive structural check of a trie.
+
+use std::hash::{BuildHasher, Hash};
+
+use safe_bump::Idx;
+
+use crate::adhash;
+use crate::invariants::InvariantError;
+use crate::node::{self, Node};
+use crate::store::ChampStore;
+
+/// Checks the subtree rooted at `idx`, returning its entry count and the
+/// `AdHash` recomputed from its entries.
+///
+/// Child `AdHash` values are recomputed rather than trusted, so a mismatch
+/// is reported at the deepest node where it occurs.
+pub fn check_node<K, V, S, H>(
+    store: &S,
+    hasher: &H,
+    idx: Idx<Node<K, V>>,
+    is_root: bool,
+) -> Result<(usize, u64), InvariantError>
+where
+    V: Hash,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    let raw = idx.into_raw();
+    let node = *store.get_node(idx);
+    let (data_start, len) = match node {
+        Node::Inner {
+            data_map,
+            node_map,
+            data_start,
+            ..
+        } => {
+            if data_map & node_map != 0 {
+                return Err(InvariantError::OverlappingBitmaps {
+                    node: raw,
+                    data_map,
+                    node_map,
+                });
+            }
+            if !is_root && node_map == 0 && data_map.count_ones() < 2 {
+                return Err(InvariantError::NotInlined { node: raw });
+            }
+            (data_start, node.data_len())
+        }
+        Node::Collision {
+            entries_start,
+            entries_len,
+            ..
+        } => {
+            if entries_len < 2 {
+                return Err(InvariantError::UndersizedCollision {
+                    node: raw,
+                    len: entries_len as usize,
+                });
+            }
+            (entries_start, entries_len as usize)
+        }
+    };
+
+    let mut count = len;
+    let mut computed = (0..len).fold(adhash::EMPTY, |acc, i| {
+        let e = store.get_entry(node::offset(data_start, i));
+        adhash::combine(acc, adhash::entry_adhash(e.hash, hasher.hash_one(&e.value)))
+    });
+    if let Node::Inner { children_start, .. } = node {
+        for i in 0..node.children_len() {
+            let child = *store.get_child(node::offset(children_start, i));
+            let (child_count, child_adhash) = check_node(store, hasher, child, false)?;
+            count += child_count;
+            computed = adhash::combine(computed, child_adhash);
+        }
+    }
+
+    if node.adhash() != computed {
+        return Err(InvariantError::NodeAdHash {
+            node: raw,
+            stored: node.adhash(),
+            computed,
+        });
+    }
+    Ok((count, computed))
+}
@@ -0,0 +1,1039 @@
+//! Set operations over two tries — node-aligned merge with `AdHash` pruning.
+//!
+//! Both tries use the same hash function, so nodes at the same position and
+//! depth cover the same hash prefix. Aligned subtrees with equal `AdHash`
+//! hold the same entries (up to the 2⁻⁶⁴ collision bound) and are shared or
+//! skipped wholesale instead of being walked.
+
+use std::hash::{BuildHasher, Hash};
+
+use safe_bump::Idx;
+
+use crate::adhash;
+use crate::node::{self, Entry, Node};
+use crate::ops::get::get_recursive;
+use crate::ops::insert::{alloc_or_sentinel, clone_entry, create_subtree, insert_recursive};
+use crate::ops::remove::{RemoveOutcome, remove_recursive};
+use crate::store::ChampStore;
+
+/// Content of one bitmap position in a node under construction.
+enum Slot<K, V> {
+    Data(Entry<K, V>),
+    Child(Idx<Node<K, V>>),
+}
+
+/// Reference to one occupied bitmap position of an existing inner node.
+pub enum SlotRef<K, V> {
+    /// Inline entry.
+    Data(Idx<Entry<K, V>>),
+    /// Child subtree.
+    Child(Idx<Node<K, V>>),
+}
+
+impl<K, V> Clone for SlotRef<K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for SlotRef<K, V> {}
+
+// ---------------------------------------------------------------------------
+// Union
+// ---------------------------------------------------------------------------
+
+/// Merges the subtree `b` (in `src`) into the subtree `a` (in `dst`),
+/// returning the root of the union in `dst`.
+///
+/// Keys present on both sides get `resolve(a_value, b_value)`. With
+/// `skip_equal`, aligned subtrees with equal `AdHash` are kept as-is without
+/// calling `resolve`, which is only sound if `resolve(v, v) == v`.
+/// `added` is incremented by the number of keys found only in `b`.
+#[allow(clippy::too_many_arguments)]
+pub fn union_recursive<K, V, D, S, F, H>(
+    dst: &mut D,
+    hasher: &H,
+    a: Idx<Node<K, V>>,
+    src: &S,
+    b: Idx<Node<K, V>>,
+    shift: u32,
+    resolve: &F,
+    skip_equal: bool,
+    added: &mut usize,
+) -> Idx<Node<K, V>>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    D: ChampStore<K, V>,
+    S: ChampStore<K, V>,
+    F: Fn(&V, &V) -> V,
+    H: BuildHasher,
+{
+    let node_a = *dst.get_node(a);
+    let node_b = *src.get_node(b);
+    if skip_equal && node_a.adhash() == node_b.adhash() {
+        return a;
+    }
+
+    match (node_a, node_b) {
+        (Node::Inner { .. }, Node::Inner { .. }) => {
+            let mut slots = Vec::new();
+            let mut bits = occupied(&node_a) | occupied(&node_b);
+            while bits != 0 {
+                let bit = bits & bits.wrapping_neg();
+                bits &= bits - 1;
+                let slot = match (slot_ref(dst, &node_a, bit), slot_ref(src, &node_b, bit)) {
+                    (Some(x), None) => keep(dst, x),
+                    (None, Some(y)) => import(dst, src, y, added),
+                    (Some(x), Some(y)) => {
+                        union_slots(dst, hasher, x, src, y, shift, resolve, skip_equal, added)
+                    }
+                    (None, None) => unreachable!("bit taken from the union of bitmaps"),
+                };
+                slots.push((bit, slot));
+            }
+            build_inner(dst, hasher, slots)
+        }
+        (
+            Node::Collision {
+                hash,
+                entries_start: start_a,
+                entries_len: len_a,
+                ..
+            },
+            Node::Collision {
+                entries_start: start_b,
+                entries_len: len_b,
+                ..
+            },
+        ) => {
+            let mut entries: Vec<Entry<K, V>> = (0..len_a as usize)
+                .map(|i| clone_entry(dst, node::offset(start_a, i)))
+                .collect();
+            for i in 0..len_b as usize {
+                let eb = src.get_entry(node::offset(start_b, i));
+                if let Some(ea) = entries.iter_mut().find(|ea| ea.key == eb.key) {
+                    ea.value = resolve(&ea.value, &eb.value);
+                } else {
+                    entries.push(clone_entry(src, node::offset(start_b, i)));
+                    *added += 1;
+                }
+            }
+            build_collision(dst, hasher, hash, entries)
+        }
+        _ => unreachable!("aligned nodes share a kind"),
+    }
+}
+
+/// Unions two occupied positions at the same bit of aligned inner nodes.
+#[allow(clippy::too_many_arguments)]
+fn union_slots<K, V, D, S, F, H>(
+    dst: &mut D,
+    hasher: &H,
+    x: SlotRef<K, V>,
+    src: &S,
+    y: SlotRef<K, V>,
+    shift: u32,
+    resolve: &F,
+    skip_equal: bool,
+    added: &mut usize,
+) -> Slot<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    D: ChampStore<K, V>,
+    S: ChampStore<K, V>,
+    F: Fn(&V, &V) -> V,
+    H: BuildHasher,
+{
+    let child_shift = shift + node::BITS_PER_LEVEL;
+    match (x, y) {
+        (SlotRef::Data(ia), SlotRef::Data(ib)) => {
+            let ea = clone_entry(dst, ia);
+            let eb = clone_entry(src, ib);
+            if ea.hash == eb.hash && ea.key == eb.key {
+                let value = resolve(&ea.value, &eb.value);
+                Slot::Data(Entry { value, ..ea })
+            } else {
+                *added += 1;
+                Slot::Child(create_subtree(dst, hasher, ea, eb, child_shift))
+            }
+        }
+        (SlotRef::Data(ia), SlotRef::Child(cb)) => {
+            let copy = copy_subtree(dst, src, cb, added);
+            let ea = clone_entry(dst, ia);
+            let value = match get_recursive(dst, copy, ea.hash, &ea.key, child_shift) {
+                Some(eb) => {
+                    *added -= 1;
+                    resolve(&ea.value, &eb.value)
+                }
+                None => ea.value,
+            };
+            let entry = Entry { value, ..ea };
+            Slot::Child(insert_recursive(dst, hasher, copy, entry, child_shift).node)
+        }
+        (SlotRef::Child(ca), SlotRef::Data(ib)) => {
+            let eb = clone_entry(src, ib);
+            let value = if let Some(ea) = get_recursive(dst, ca, eb.hash, &eb.key, child_shift) {
+                resolve(&ea.value, &eb.value)
+            } else {
+                *added += 1;
+                eb.value
+            };
+            let entry = Entry { value, ..eb };
+            Slot::Child(insert_recursive(dst, hasher, ca, entry, child_shift).node)
+        }
+        (SlotRef::Child(ca), SlotRef::Child(cb)) => Slot::Child(union_recursive(
+            dst,
+            hasher,
+            ca,
+            src,
+            cb,
+            child_shift,
+            resolve,
+            skip_equal,
+            added,
+        )),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Intersection / difference
+// ---------------------------------------------------------------------------
+
+/// Which entries of `a` a filtering merge keeps.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Keep {
+    /// Keys also present in `b` (intersection).
+    Common,
+    /// Keys absent from `b` (difference).
+    Unique,
+}
+
+/// A filtered subtree in canonical form.
+///
+/// Single entries are returned by value so the parent can inline them;
+/// only subtrees with at least two entries (or a root) become nodes.
+pub enum Pruned<K, V> {
+    /// No entries left.
+    Empty,
+    /// Exactly one entry left.
+    Single(Entry<K, V>),
+    /// A subtree with at least two entries.
+    Node(Idx<Node<K, V>>),
+}
+
+/// Filters the subtree `a` (in `dst`) by key membership in the subtree `b`
+/// (in `src`), building the result in `dst`.
+///
+/// Aligned subtrees with equal `AdHash` are kept (for [`Keep::Common`]) or
+/// dropped (for [`Keep::Unique`]) without being walked. Unchanged subtrees
+/// of `a` are reused. `removed` is incremented by the number of entries of
+/// `a` left out of the result.
+#[allow(clippy::too_many_arguments)]
+pub fn filter_recursive<K, V, D, S, H>(
+    dst: &mut D,
+    hasher: &H,
+    a: Idx<Node<K, V>>,
+    src: &S,
+    b: Idx<Node<K, V>>,
+    shift: u32,
+    keep: Keep,
+    removed: &mut usize,
+) -> Pruned<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    D: ChampStore<K, V>,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    let node_a = *dst.get_node(a);
+    let node_b = *src.get_node(b);
+    if node_a.adhash() == node_b.adhash() {
+        return match keep {
+            Keep::Common => Pruned::Node(a),
+            Keep::Unique => {
+                *removed += subtree_len(dst, a);
+                Pruned::Empty
+            }
+        };
+    }
+
+    match (node_a, node_b) {
+        (Node::Inner { .. }, Node::Inner { .. }) => {
+            let mut slots = Vec::new();
+            let mut changed = false;
+            let mut bits = occupied(&node_a);
+            while bits != 0 {
+                let bit = bits & bits.wrapping_neg();
+                bits &= bits - 1;
+                let own = slot_ref(dst, &node_a, bit).expect("bit taken from a's bitmaps");
+                let slot = filter_slot(
+                    dst,
+                    hasher,
+                    own,
+                    src,
+                    slot_ref(src, &node_b, bit),
+                    shift,
+                    keep,
+                    removed,
+                );
+                changed |= match (own, &slot) {
+                    (SlotRef::Data(_), Pruned::Single(_)) => false,
+                    (SlotRef::Child(c), Pruned::Node(n)) => c != *n,
+                    _ => true,
+                };
+                match slot {
+                    Pruned::Empty => {}
+                    Pruned::Single(e) => slots.push((bit, Slot::Data(e))),
+                    Pruned::Node(c) => slots.push((bit, Slot::Child(c))),
+                }
+            }
+            if !changed {
+                return Pruned::Node(a);
+            }
+            match slots.pop() {
+                None => Pruned::Empty,
+                Some((_, Slot::Data(e))) if slots.is_empty() => Pruned::Single(e),
+                Some(last) => {
+                    slots.push(last);
+                    Pruned::Node(build_inner(dst, hasher, slots))
+                }
+            }
+        }
+        (
+            Node::Collision {
+                hash,
+                entries_start: start_a,
+                entries_len: len_a,
+                ..
+            },
+            Node::Collision {
+                hash: hash_b,
+                entries_start: start_b,
+                entries_len: len_b,
+                ..
+            },
+        ) => {
+            let len_a = len_a as usize;
+            let mut entries: Vec<Entry<K, V>> = (0..len_a)
+                .map(|i| clone_entry(dst, node::offset(start_a, i)))
+                .filter(|ea| {
+                    let present = hash == hash_b
+                        && (0..len_b as usize)
+                            .any(|i| src.get_entry(node::offset(start_b, i)).key == ea.key);
+                    present == (keep == Keep::Common)
+                })
+                .collect();
+            *removed += len_a - entries.len();
+            match entries.len() {
+                0 => Pruned::Empty,
+                1 => Pruned::Single(entries.pop().expect("one entry")),
+                n if n == len_a => Pruned::Node(a),
+                _ => Pruned::Node(build_collision(dst, hasher, hash, entries)),
+            }
+        }
+        _ => unreachable!("aligned nodes share a kind"),
+    }
+}
+
+/// Filters one occupied position of `a` against what occupies the same bit
+/// of the aligned node in `b`.
+#[allow(clippy::too_many_arguments)]
+fn filter_slot<K, V, D, S, H>(
+    dst: &mut D,
+    hasher: &H,
+    x: SlotRef<K, V>,
+    src: &S,
+    y: Option<SlotRef<K, V>>,
+    shift: u32,
+    keep: Keep,
+    removed: &mut usize,
+) -> Pruned<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    D: ChampStore<K, V>,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    let child_shift = shift + node::BITS_PER_LEVEL;
+    match (x, y) {
+        (SlotRef::Data(ia), y) => {
+            let ea = clone_entry(dst, ia);
+            let present = match y {
+                None => false,
+                Some(SlotRef::Data(ib)) => {
+                    let eb = src.get_entry(ib);
+                    eb.hash == ea.hash && eb.key == ea.key
+                }
+                Some(SlotRef::Child(cb)) => {
+                    get_recursive(src, cb, ea.hash, &ea.key, child_shift).is_some()
+                }
+            };
+            if present == (keep == Keep::Common) {
+                Pruned::Single(ea)
+            } else {
+                *removed += 1;
+                Pruned::Empty
+            }
+        }
+        (SlotRef::Child(ca), None) => match keep {
+            Keep::Common => {
+                *removed += subtree_len(dst, ca);
+                Pruned::Empty
+            }
+            Keep::Unique => Pruned::Node(ca),
+        },
+        (SlotRef::Child(ca), Some(SlotRef::Data(ib))) => {
+            let eb = src.get_entry(ib);
+            match keep {
+                Keep::Common => {
+                    let len = subtree_len(dst, ca);
+                    if let Some(ea) = get_recursive(dst, ca, eb.hash, &eb.key, child_shift) {
+                        *removed += len - 1;
+                        Pruned::Single(ea.clone())
+                    } else {
+                        *removed += len;
+                        Pruned::Empty
+                    }
+                }
+                Keep::Unique => {
+                    match remove_recursive(dst, hasher, ca, eb.hash, &eb.key, child_shift) {
+                        RemoveOutcome::NotFound => Pruned::Node(ca),
+                        RemoveOutcome::Removed { node, .. } => {
+                            *removed += 1;
+                            node.map_or(Pruned::Empty, |n| settle(dst, n))
+                        }
+                    }
+                }
+            }
+        }
+        (SlotRef::Child(ca), Some(SlotRef::Child(cb))) => {
+            filter_recursive(dst, hasher, ca, src, cb, child_shift, keep, removed)
+        }
+    }
+}
+
+/// Converts a freshly rebuilt child into canonical form: a single-entry
+/// inner or collision node is unwrapped so the parent can inline its entry.
+fn settle<K: Clone, V: Clone, S: ChampStore<K, V>>(
+    store: &S,
+    idx: Idx<Node<K, V>>,
+) -> Pruned<K, V> {
+    match *store.get_node(idx) {
+        Node::Inner {
+            data_map,
+            node_map: 0,
+            data_start,
+            ..
+        } if data_map.is_power_of_two() => Pruned::Single(clone_entry(store, data_start)),
+        Node::Collision {
+            entries_start,
+            entries_len: 1,
+            ..
+        } => Pruned::Single(clone_entry(store, entries_start)),
+        _ => Pruned::Node(idx),
+    }
+}
+
+/// Counts the entries of the subtree rooted at `idx`.
+pub fn subtree_len<K, V, S: ChampStore<K, V>>(store: &S, idx: Idx<Node<K, V>>) -> usize {
+    let node = store.get_node(idx);
+    let mut len = node.data_len();
+    if let Node::Inner { children_start, .. } = *node {
+        for i in 0..node.children_len() {
+            len += subtree_len(store, *store.get_child(node::offset(children_start, i)));
+        }
+    }
+    len
+}
+
+// ---------------------------------------------------------------------------
+// Symmetric difference
+// ---------------------------------------------------------------------------
+
+/// Builds, in `dst`, the entries of the subtrees `a` (in `dst`) and `b` (in
+/// `src`) whose keys are on exactly one side.
+///
+/// Aligned subtrees with equal `AdHash` hold the same keys and contribute
+/// nothing, so they are dropped without being walked. `common` is
+/// incremented by the number of keys found on both sides.
+pub fn symmetric_recursive<K, V, D, S, H>(
+    dst: &mut D,
+    hasher: &H,
+    a: Idx<Node<K, V>>,
+    src: &S,
+    b: Idx<Node<K, V>>,
+    shift: u32,
+    common: &mut usize,
+) -> Pruned<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    D: ChampStore<K, V>,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    let node_a = *dst.get_node(a);
+    let node_b = *src.get_node(b);
+    if node_a.adhash() == node_b.adhash() {
+        *common += subtree_len(dst, a);
+        return Pruned::Empty;
+    }
+
+    match (node_a, node_b) {
+        (Node::Inner { .. }, Node::Inner { .. }) => {
+            let mut slots = Vec::new();
+            let mut bits = occupied(&node_a) | occupied(&node_b);
+            while bits != 0 {
+                let bit = bits & bits.wrapping_neg();
+                bits &= bits - 1;
+                let slot = match (slot_ref(dst, &node_a, bit), slot_ref(src, &node_b, bit)) {
+                    (Some(x), None) => keep(dst, x),
+                    (None, Some(y)) => import(dst, src, y, &mut 0),
+                    (Some(x), Some(y)) => {
+                        match symmetric_slots(dst, hasher, x, src, y, shift, common) {
+                            Pruned::Empty => continue,
+                            Pruned::Single(entry) => Slot::Data(entry),
+                            Pruned::Node(child) => Slot::Child(child),
+                        }
+                    }
+                    (None, None) => unreachable!("bit taken from the union of bitmaps"),
+                };
+                slots.push((bit, slot));
+            }
+            match slots.pop() {
+                None => Pruned::Empty,
+                Some((_, Slot::Data(e))) if slots.is_empty() => Pruned::Single(e),
+                Some(last) => {
+                    slots.push(last);
+                    Pruned::Node(build_inner(dst, hasher, slots))
+                }
+            }
+        }
+        (
+            Node::Collision {
+                hash,
+                entries_start: start_a,
+                entries_len: len_a,
+                ..
+            },
+            Node::Collision {
+                entries_start: start_b,
+                entries_len: len_b,
+                ..
+            },
+        ) => {
+            let mut entries: Vec<Entry<K, V>> = (0..len_a as usize)
+                .map(|i| clone_entry(dst, node::offset(start_a, i)))
+                .collect();
+            for i in 0..len_b as usize {
+                let eb = src.get_entry(node::offset(start_b, i));
+                if let Some(pos) = entries.iter().position(|ea| ea.key == eb.key) {
+                    entries.swap_remove(pos);
+                    *common += 1;
+                } else {
+                    entries.push(clone_entry(src, node::offset(start_b, i)));
+                }
+            }
+            match entries.len() {
+                0 => Pruned::Empty,
+                1 => Pruned::Single(entries.pop().expect("one entry")),
+                _ => Pruned::Node(build_collision(dst, hasher, hash, entries)),
+            }
+        }
+        _ => unreachable!("aligned nodes share a kind"),
+    }
+}
+
+/// Symmetric difference of two occupied positions at the same bit of
+/// aligned inner nodes.
+fn symmetric_slots<K, V, D, S, H>(
+    dst: &mut D,
+    hasher: &H,
+    x: SlotRef<K, V>,
+    src: &S,
+    y: SlotRef<K, V>,
+    shift: u32,
+    common: &mut usize,
+) -> Pruned<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    D: ChampStore<K, V>,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    let child_shift = shift + node::BITS_PER_LEVEL;
+    // A lone entry facing a subtree toggles in the subtree: removed if its
+    // key is there, inserted otherwise.
+    let toggle = |dst: &mut D, c: Idx<Node<K, V>>, e: Entry<K, V>, common: &mut usize| {
+        match remove_recursive(dst, hasher, c, e.hash, &e.key, child_shift) {
+            RemoveOutcome::NotFound => {
+                Pruned::Node(insert_recursive(dst, hasher, c, e, child_shift).node)
+            }
+            RemoveOutcome::Removed { node, .. } => {
+                *common += 1;
+                node.map_or(Pruned::Empty, |n| settle(dst, n))
+            }
+        }
+    };
+    match (x, y) {
+        (SlotRef::Data(ia), SlotRef::Data(ib)) => {
+            let ea = clone_entry(dst, ia);
+            let eb = clone_entry(src, ib);
+            if ea.hash == eb.hash && ea.key == eb.key {
+                *common += 1;
+                Pruned::Empty
+            } else {
+                Pruned::Node(create_subtree(dst, hasher, ea, eb, child_shift))
+            }
+        }
+        (SlotRef::Data(ia), SlotRef::Child(cb)) => {
+            let copy = copy_subtree(dst, src, cb, &mut 0);
+            let ea = clone_entry(dst, ia);
+            toggle(dst, copy, ea, common)
+        }
+        (SlotRef::Child(ca), SlotRef::Data(ib)) => toggle(dst, ca, clone_entry(src, ib), common),
+        (SlotRef::Child(ca), SlotRef::Child(cb)) => {
+            symmetric_recursive(dst, hasher, ca, src, cb, child_shift, common)
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Batch removal
+// ---------------------------------------------------------------------------
+
+/// Removes every key of `keys` present in the subtree `a`, rebuilding each
+/// touched node once.
+///
+/// `keys` holds `(hash, key)` pairs sorted by hash, so keys sharing a route
+/// through the trie are adjacent at every depth. Untouched
+/// subtrees of `a` are reused. `removed` is incremented by the number of
+/// entries actually removed; absent and repeated keys are not counted.
+pub fn remove_keys<K, V, S, H>(
+    store: &mut S,
+    hasher: &H,
+    a: Idx<Node<K, V>>,
+    keys: &[(u64, K)],
+    shift: u32,
+    removed: &mut usize,
+) -> Pruned<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    let node_a = *store.get_node(a);
+    match node_a {
+        Node::Inner { .. } => {
+            let mut changes = Vec::new();
+            for group in
+                keys.chunk_by(|x, y| node::fragment(x.0, shift) == node::fragment(y.0, shift))
+            {
+                let bit = node::mask(node::fragment(group[0].0, shift));
+                let Some(own) = slot_ref(store, &node_a, bit) else {
+                    continue;
+                };
+                let slot = remove_keys_slot(store, hasher, own, group, shift, removed);
+                let modified = match (own, &slot) {
+                    (SlotRef::Data(_), Pruned::Single(_)) => false,
+                    (SlotRef::Child(c), Pruned::Node(n)) => c != *n,
+                    _ => true,
+                };
+                if modified {
+                    changes.push((bit, slot));
+                }
+            }
+            if changes.is_empty() {
+                return Pruned::Node(a);
+            }
+            let mut slots = Vec::new();
+            let mut changes = changes.into_iter().peekable();
+            let mut bits = occupied(&node_a);
+            while bits != 0 {
+                let bit = bits & bits.wrapping_neg();
+                bits &= bits - 1;
+                let slot = match changes.next_if(|(b, _)| *b == bit) {
+                    Some((_, Pruned::Empty)) => continue,
+                    Some((_, Pruned::Single(e))) => Slot::Data(e),
+                    Some((_, Pruned::Node(c))) => Slot::Child(c),
+                    None => keep(store, slot_ref(store, &node_a, bit).expect("occupied bit")),
+                };
+                slots.push((bit, slot));
+            }
+            match slots.pop() {
+                None => Pruned::Empty,
+                Some((_, Slot::Data(e))) if slots.is_empty() => Pruned::Single(e),
+                Some(last) => {
+                    slots.push(last);
+                    Pruned::Node(build_inner(store, hasher, slots))
+                }
+            }
+        }
+        Node::Collision {
+            hash,
+            entries_start,
+            entries_len,
+            ..
+        } => {
+            let len = entries_len as usize;
+            let mut entries: Vec<Entry<K, V>> = (0..len)
+                .map(|i| clone_entry(store, node::offset(entries_start, i)))
+                .filter(|e| !keys.iter().any(|(h, k)| *h == hash && *k == e.key))
+                .collect();
+            *removed += len - entries.len();
+            match entries.len() {
+                0 => Pruned::Empty,
+                1 => Pruned::Single(entries.pop().expect("one entry")),
+                n if n == len => Pruned::Node(a),
+                _ => Pruned::Node(build_collision(store, hasher, hash, entries)),
+            }
+        }
+    }
+}
+
+/// Removes the keys of one fragment group from an occupied position.
+fn remove_keys_slot<K, V, S, H>(
+    store: &mut S,
+    hasher: &H,
+    own: SlotRef<K, V>,
+    group: &[(u64, K)],
+    shift: u32,
+    removed: &mut usize,
+) -> Pruned<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    let child_shift = shift + node::BITS_PER_LEVEL;
+    match own {
+        SlotRef::Data(ia) => {
+            let e = store.get_entry(ia);
+            if group.iter().any(|(h, k)| *h == e.hash && *k == e.key) {
+                *removed += 1;
+                Pruned::Empty
+            } else {
+                Pruned::Single(clone_entry(store, ia))
+            }
+        }
+        SlotRef::Child(c) => {
+            if let [(hash, key)] = group {
+                match remove_recursive(store, hasher, c, *hash, key, child_shift) {
+                    RemoveOutcome::NotFound => Pruned::Node(c),
+                    RemoveOutcome::Removed { node, .. } => {
+                        *removed += 1;
+                        node.map_or(Pruned::Empty, |n| settle(store, n))
+                    }
+                }
+            } else {
+                remove_keys(store, hasher, c, group, child_shift, removed)
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Subset
+// ---------------------------------------------------------------------------
+
+/// Returns `true` if every entry of the subtree `a` (in `sa`) is present
+/// with an equal value in the aligned subtree `b` (in `sb`).
+///
+/// Aligned subtrees with equal `AdHash` are accepted without being walked.
+/// A child subtree of `a` facing a single inline entry of `b` is rejected
+/// outright: a non-root subtree always holds at least two entries.
+pub fn subset_recursive<K, V, S>(
+    sa: &S,
+    a: Idx<Node<K, V>>,
+    sb: &S,
+    b: Idx<Node<K, V>>,
+    shift: u32,
+) -> bool
+where
+    K: Eq,
+    V: PartialEq,
+    S: ChampStore<K, V>,
+{
+    let node_a = *sa.get_node(a);
+    let node_b = *sb.get_node(b);
+    if node_a.adhash() == node_b.adhash() {
+        return true;
+    }
+
+    match (node_a, node_b) {
+        (Node::Inner { .. }, Node::Inner { .. }) => {
+            let child_shift = shift + node::BITS_PER_LEVEL;
+            let mut bits = occupied(&node_a);
+            while bits != 0 {
+                let bit = bits & bits.wrapping_neg();
+                bits &= bits - 1;
+                let own = slot_ref(sa, &node_a, bit).expect("bit taken from a's bitmaps");
+                let contained = match (own, slot_ref(sb, &node_b, bit)) {
+                    (_, None) | (SlotRef::Child(_), Some(SlotRef::Data(_))) => false,
+                    (SlotRef::Data(ia), Some(SlotRef::Data(ib))) => {
+                        let (ea, eb) = (sa.get_entry(ia), sb.get_entry(ib));
+                        ea.hash == eb.hash && ea.key == eb.key && ea.value == eb.value
+                    }
+                    (SlotRef::Data(ia), Some(SlotRef::Child(cb))) => {
+                        let ea = sa.get_entry(ia);
+                        get_recursive(sb, cb, ea.hash, &ea.key, child_shift)
+                            .is_some_and(|eb| eb.value == ea.value)
+                    }
+                    (SlotRef::Child(ca), Some(SlotRef::Child(cb))) => {
+                        subset_recursive(sa, ca, sb, cb, child_shift)
+                    }
+                };
+                if !contained {
+                    return false;
+                }
+            }
+            true
+        }
+        (
+            Node::Collision {
+                hash: hash_a,
+                entries_start: start_a,
+                entries_len: len_a,
+                ..
+            },
+            Node::Collision {
+                hash: hash_b,
+                entries_start: start_b,
+                entries_len: len_b,
+                ..
+            },
+        ) => {
+            hash_a == hash_b
+                && (0..len_a as usize).all(|i| {
+                    let ea = sa.get_entry(node::offset(start_a, i));
+                    (0..len_b as usize).any(|j| {
+                        let eb = sb.get_entry(node::offset(start_b, j));
+                        eb.key == ea.key && eb.value == ea.value
+                    })
+                })
+        }
+        _ => unreachable!("aligned nodes share a kind"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Cross-store copy
+// ---------------------------------------------------------------------------
+
+/// Deep-copies the subtree `idx` from `src` into `dst`, returning the new
+/// root. `count` is incremented by the number of entries copied.
+pub fn copy_subtree<K, V, D, S>(
+    dst: &mut D,
+    src: &S,
+    idx: Idx<Node<K, V>>,
+    count: &mut usize,
+) -> Idx<Node<K, V>>
+where
+    K: Clone,
+    V: Clone,
+    D: ChampStore<K, V>,
+    S: ChampStore<K, V>,
+{
+    match *src.get_node(idx) {
+        Node::Inner {
+            data_map,
+            node_map,
+            data_start,
+            children_start,
+            adhash,
+        } => {
+            let data_len = data_map.count_ones() as usize;
+            let entries: Vec<Entry<K, V>> = (0..data_len)
+                .map(|i| clone_entry(src, node::offset(data_start, i)))
+                .collect();
+            let children: Vec<Idx<Node<K, V>>> = (0..node_map.count_ones() as usize)
+                .map(|i| {
+                    let child = *src.get_child(node::offset(children_start, i));
+                    copy_subtree(dst, src, child, count)
+                })
+                .collect();
+            *count += data_len;
+            let data_start = alloc_or_sentinel(dst.alloc_entries(entries));
+            let children_start = alloc_or_sentinel(dst.alloc_children(children));
+            dst.alloc_node(Node::Inner {
+                data_map,
+                node_map,
+                data_start,
+                children_start,
+                adhash,
+            })
+        }
+        Node::Collision {
+            hash,
+            entries_start,
+            entries_len,
+            adhash,
+        } => {
+            let len = entries_len as usize;
+            let entries: Vec<Entry<K, V>> = (0..len)
+                .map(|i| clone_entry(src, node::offset(entries_start, i)))
+                .collect();
+            *count += len;
+            let entries_start = dst.alloc_entries(entries).expect("non-empty");
+            dst.alloc_node(Node::Collision {
+                hash,
+                entries_start,
+                entries_len,
+                adhash,
+            })
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Slot helpers
+// ---------------------------------------------------------------------------
+
+/// Returns the bitmap of all occupied positions of an inner node.
+pub const fn occupied<K, V>(node: &Node<K, V>) -> u32 {
+    match *node {
+        Node::Inner {
+            data_map, node_map, ..
+        } => data_map | node_map,
+        Node::Collision { .. } => 0,
+    }
+}
+
+/// Returns what occupies `bit` in an inner node, if anything.
+pub fn slot_ref<K, V, S: ChampStore<K, V>>(
+    store: &S,
+    node: &Node<K, V>,
+    bit: u32,
+) -> Option<SlotRef<K, V>> {
+    let Node::Inner {
+        data_map,
+        node_map,
+        data_start,
+        children_start,
+        ..
+    } = *node
+    else {
+        return None;
+    };
+    if data_map & bit != 0 {
+        Some(SlotRef::Data(node::offset(
+            data_start,
+            node::index(data_map, bit),
+        )))
+    } else if node_map & bit != 0 {
+        let pos = node::index(node_map, bit);
+        Some(SlotRef::Child(
+            *store.get_child(node::offset(children_start, pos)),
+        ))
+    } else {
+        None
+    }
+}
+
+/// Keeps a slot of a node that already lives in `dst`.
+fn keep<K: Clone, V: Clone, D: ChampStore<K, V>>(dst: &D, x: SlotRef<K, V>) -> Slot<K, V> {
+    match x {
+        SlotRef::Data(i) => Slot::Data(clone_entry(dst, i)),
+        SlotRef::Child(c) => Slot::Child(c),
+    }
+}
+
+/// Copies a slot of a node in `src` into `dst`, counting the entries it holds.
+fn import<K, V, D, S>(dst: &mut D, src: &S, y: SlotRef<K, V>, count: &mut usize) -> Slot<K, V>
+where
+    K: Clone,
+    V: Clone,
+    D: ChampStore<K, V>,
+    S: ChampStore<K, V>,
+{
+    match y {
+        SlotRef::Data(i) => {
+            *count += 1;
+            Slot::Data(clone_entry(src, i))
+        }
+        SlotRef::Child(c) => Slot::Child(copy_subtree(dst, src, c, count)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Node builders
+// ---------------------------------------------------------------------------
+
+/// Allocates an inner node from slots given in ascending bit order.
+fn build_inner<K, V, S, H>(
+    store: &mut S,
+    hasher: &H,
+    slots: Vec<(u32, Slot<K, V>)>,
+) -> Idx<Node<K, V>>
+where
+    V: Hash,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    let mut data_map = 0;
+    let mut node_map = 0;
+    let mut adhash = adhash::EMPTY;
+    let mut entries = Vec::new();
+    let mut children = Vec::new();
+    for (bit, slot) in slots {
+        match slot {
+            Slot::Data(e) => {
+                data_map |= bit;
+                adhash = adhash::combine(adhash, contribution(hasher, &e));
+                entries.push(e);
+            }
+            Slot::Child(c) => {
+                node_map |= bit;
+                adhash = adhash::combine(adhash, store.get_node(c).adhash());
+                children.push(c);
+            }
+        }
+    }
+    let data_start = alloc_or_sentinel(store.alloc_entries(entries));
+    let children_start = alloc_or_sentinel(store.alloc_children(children));
+    store.alloc_node(Node::Inner {
+        data_map,
+        node_map,
+        data_start,
+        children_start,
+        adhash,
+    })
+}
+
+/// Allocates a collision node holding at least two entries.
+fn build_collision<K, V, S, H>(
+    store: &mut S,
+    hasher: &H,
+    hash: u64,
+    entries: Vec<Entry<K, V>>,
+) -> Idx<Node<K, V>>
+where
+    V: Hash,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    let entries_len =
+        u32::try_from(entries.len()).expect("collision node overflow (>u32::MAX entries)");
+    let adhash = entries.iter().fold(adhash::EMPTY, |acc, e| {
+        adhash::combine(acc, contribution(hasher, e))
+    });
+    let entries_start = store.alloc_entries(entries).expect("non-empty");
+    store.alloc_node(Node::Collision {
+        hash,
+        entries_start,
+        entries_len,
+        adhash,
+    })
+}
+
+/// Returns the `AdHash` contribution of an entry.
+fn contribution<K, V: Hash, H: BuildHasher>(hasher: &H, e: &Entry<K, V>) -> u64 {
+    adhash::entry_adhash(e.hash, hasher.hash_one(&e.value))
+}
@@ -1,5 +1,13 @@
-//! Trie operations: get, insert, remove.
+//! Trie operations: get, insert, remove, merge, diff, path descent, value
+//! transforms, invariant checks, DOT rendering, trie-order descents.
 
+pub mod diff;
+pub mod dot;
 pub mod get;
 pub mod insert;
+pub mod invariants;
+pub mod merge;
+pub mod order;
+pub mod path;
 pub mod remove;
+pub mod transform;
@@ -0,0 +1,105 @@
+//! Descents in hash order.
+//!
+//! The root level takes the most significant bits of the hash, so a DFS
+//! that visits slots in ascending fragment order yields entries in
+//! ascending hash order. These descents find entries at the ends of that
+//! order, or at or after a given hash, in O(depth).
+
+use safe_bump::Idx;
+
+use crate::node::{self, Entry, Node};
+use crate::store::ChampStore;
+
+/// Returns the entry under `idx` with the smallest hash.
+pub fn min_entry<K, V, S: ChampStore<K, V>>(store: &S, idx: Idx<Node<K, V>>) -> &Entry<K, V> {
+    extreme(store, idx, u32::trailing_zeros)
+}
+
+/// Returns the entry under `idx` with the largest hash.
+pub fn max_entry<K, V, S: ChampStore<K, V>>(store: &S, idx: Idx<Node<K, V>>) -> &Entry<K, V> {
+    extreme(store, idx, |occupied| {
+        u32::BITS - 1 - occupied.leading_zeros()
+    })
+}
+
+/// Returns the entry under `idx`, a node at bit-shift `shift`, with the
+/// smallest hash `>= target`, or `None` if every hash is below it.
+///
+/// Descends along `target`'s fragments; where the matching slot holds
+/// nothing late enough, the answer is the first entry of the next occupied
+/// slot of the same node, found by [`min_entry`].
+pub fn successor<K, V, S: ChampStore<K, V>>(
+    store: &S,
+    idx: Idx<Node<K, V>>,
+    target: u64,
+    shift: u32,
+) -> Option<&Entry<K, V>> {
+    match *store.get_node(idx) {
+        Node::Inner {
+            data_map,
+            node_map,
+            data_start,
+            children_start,
+            ..
+        } => {
+            let bit = node::mask(node::fragment(target, shift));
+            if data_map & bit != 0 {
+                let e = store.get_entry(node::offset(data_start, node::index(data_map, bit)));
+                if e.hash >= target {
+                    return Some(e);
+                }
+            } else if node_map & bit != 0 {
+                let child =
+                    *store.get_child(node::offset(children_start, node::index(node_map, bit)));
+                if let Some(e) = successor(store, child, target, shift + node::BITS_PER_LEVEL) {
+                    return Some(e);
+                }
+            }
+            let later = (data_map | node_map) & !(bit | (bit - 1));
+            if later == 0 {
+                return None;
+            }
+            let next = node::mask(later.trailing_zeros());
+            Some(if data_map & next != 0 {
+                store.get_entry(node::offset(data_start, node::index(data_map, next)))
+            } else {
+                let child =
+                    *store.get_child(node::offset(children_start, node::index(node_map, next)));
+                min_entry(store, child)
+            })
+        }
+        Node::Collision {
+            hash,
+            entries_start,
+            ..
+        } => (hash >= target).then(|| store.get_entry(entries_start)),
+    }
+}
+
+/// Follows the slot `pick` chooses out of each node's occupied bitmap down
+/// to an entry. The entries of a collision node share one hash; its first
+/// entry is returned.
+fn extreme<K, V, S, F>(store: &S, mut idx: Idx<Node<K, V>>, pick: F) -> &Entry<K, V>
+where
+    S: ChampStore<K, V>,
+    F: Fn(u32) -> u32,
+{
+    loop {
+        match *store.get_node(idx) {
+            Node::Inner {
+                data_map,
+                node_map,
+                data_start,
+                children_start,
+                ..
+            } => {
+                let bit = node::mask(pick(data_map | node_map));
+                if data_map & bit != 0 {
+                    return store.get_entry(node::offset(data_start, node::index(data_map, bit)));
+                }
+                idx = *store.get_child(node::offset(children_start, node::index(node_map, bit)));
+            }
+            Node::Collision { entries_start, .. } => return store.get_entry(entries_start),
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Path descent — records the route to a key's slot for targeted COW rewrites.
+
+use std::borrow::Borrow;
+
+use safe_bump::Idx;
+
+use crate::adhash;
+use crate::node::{self, Entry, Node};
+use crate::ops::insert::{build_children_replacing, build_entries_replacing};
+use crate::store::ChampStore;
+
+/// Route from the root to the node that owns a hash's slot.
+pub struct Path<K, V> {
+    /// Inner nodes above [`node`](Self::node), each with the compact child
+    /// position taken during descent. Ordered root first.
+    pub frames: Vec<(Idx<Node<K, V>>, usize)>,
+    /// Node owning the slot: an inner node whose position is inline or
+    /// empty, or a collision node.
+    pub node: Idx<Node<K, V>>,
+    /// Bit-shift depth of [`node`](Self::node).
+    pub shift: u32,
+}
+
+/// Descends from `root` following `hash` until the slot is not a child subtree.
+pub fn descend<K, V, S: ChampStore<K, V>>(
+    store: &S,
+    root: Idx<Node<K, V>>,
+    hash: u64,
+) -> Path<K, V> {
+    let mut frames = Vec::new();
+    let mut node_idx = root;
+    let mut shift = 0;
+
+    while let Node::Inner {
+        node_map,
+        children_start,
+        ..
+    } = *store.get_node(node_idx)
+    {
+        let bit = node::mask(node::fragment(hash, shift));
+        if node_map & bit == 0 {
+            break;
+        }
+        let child_pos = node::index(node_map, bit);
+        frames.push((node_idx, child_pos));
+        node_idx = *store.get_child(node::offset(children_start, child_pos));
+        shift += node::BITS_PER_LEVEL;
+    }
+
+    Path {
+        frames,
+        node: node_idx,
+        shift,
+    }
+}
+
+/// Returns the position of `key` within the data block of `path.node`,
+/// or `None` if the key is absent.
+pub fn find_entry<K, V, S, Q>(store: &S, path: &Path<K, V>, hash: u64, key: &Q) -> Option<usize>
+where
+    K: Borrow<Q>,
+    S: ChampStore<K, V>,
+    Q: Eq + ?Sized,
+{
+    match *store.get_node(path.node) {
+        Node::Inner {
+            data_map,
+            data_start,
+            ..
+        } => {
+            let bit = node::mask(node::fragment(hash, path.shift));
+            if data_map & bit == 0 {
+                return None;
+            }
+            let pos = node::index(data_map, bit);
+            let e = store.get_entry(node::offset(data_start, pos));
+            (e.hash == hash && e.key.borrow() == key).then_some(pos)
+        }
+        Node::Collision {
+            hash: node_hash,
+            entries_start,
+            entries_len,
+            ..
+        } => {
+            if hash != node_hash {
+                return None;
+            }
+            (0..entries_len as usize)
+                .find(|&i| store.get_entry(node::offset(entries_start, i)).key.borrow() == key)
+        }
+    }
+}
+
+/// Returns the index of the entry at `pos` within the data block of `node_idx`.
+pub fn entry_at<K, V, S: ChampStore<K, V>>(
+    store: &S,
+    node_idx: Idx<Node<K, V>>,
+    pos: usize,
+) -> Idx<Entry<K, V>> {
+    match *store.get_node(node_idx) {
+        Node::Inner { data_start, .. } => node::offset(data_start, pos),
+        Node::Collision { entries_start, .. } => node::offset(entries_start, pos),
+    }
+}
+
+/// Locates the entry just inserted for `hash` into the subtree `node_idx`
+/// at `shift`, without comparing keys.
+///
+/// Along a hash's route, the first inline entry is the one inserted for it:
+/// any other entry there would have been pushed down into a subtree. In a
+/// collision node, the inserted entry is appended last.
+pub fn inserted_entry<K, V, S: ChampStore<K, V>>(
+    store: &S,
+    mut node_idx: Idx<Node<K, V>>,
+    hash: u64,
+    mut shift: u32,
+) -> Idx<Entry<K, V>> {
+    loop {
+        match *store.get_node(node_idx) {
+            Node::Inner {
+                data_map,
+                node_map,
+                data_start,
+                children_start,
+                ..
+            } => {
+                let bit = node::mask(node::fragment(hash, shift));
+                if data_map & bit != 0 {
+                    return node::offset(data_start, node::index(data_map, bit));
+                }
+                debug_assert!(node_map & bit != 0, "inserted hash has a slot");
+                node_idx =
+                    *store.get_child(node::offset(children_start, node::index(node_map, bit)));
+                shift += node::BITS_PER_LEVEL;
+            }
+            Node::Collision {
+                entries_start,
+                entries_len,
+                ..
+            } => return node::offset(entries_start, entries_len as usize - 1),
+        }
+    }
+}
+
+/// COW-copies `node_idx` with the entry at `pos` replaced by `entry`.
+///
+/// `delta` is the wrapping `AdHash` change caused by the replacement.
+pub fn replace_entry<K: Clone, V: Clone, S: ChampStore<K, V>>(
+    store: &mut S,
+    node_idx: Idx<Node<K, V>>,
+    pos: usize,
+    entry: Entry<K, V>,
+    delta: u64,
+) -> Idx<Node<K, V>> {
+    match *store.get_node(node_idx) {
+        Node::Inner {
+            data_map,
+            node_map,
+            data_start,
+            children_start,
+            adhash,
+        } => {
+            let len = data_map.count_ones() as usize;
+            let entries = build_entries_replacing(store, data_start, len, pos, entry);
+            let new_data = store.alloc_entries(entries).expect("non-empty");
+            store.alloc_node(Node::Inner {
+                data_map,
+                node_map,
+                data_start: new_data,
+                children_start,
+                adhash: adhash::combine(adhash, delta),
+            })
+        }
+        Node::Collision {
+            hash,
+            entries_start,
+            entries_len,
+            adhash,
+        } => {
+            let len = entries_len as usize;
+            let entries = build_entries_replacing(store, entries_start, len, pos, entry);
+            let new_start = store.alloc_entries(entries).expect("non-empty");
+            store.alloc_node(Node::Collision {
+                hash,
+                entries_start: new_start,
+                entries_len,
+                adhash: adhash::combine(adhash, delta),
+            })
+        }
+    }
+}
+
+/// COW-copies every frame of a path bottom-up, pointing each copy at the
+/// rewritten child below it. Returns the new root.
+///
+/// `delta` is the wrapping `AdHash` change of the rewritten subtree.
+pub fn rebuild_spine<K, V, S: ChampStore<K, V>>(
+    store: &mut S,
+    frames: &[(Idx<Node<K, V>>, usize)],
+    mut child: Idx<Node<K, V>>,
+    delta: u64,
+) -> Idx<Node<K, V>> {
+    for &(node_idx, child_pos) in frames.iter().rev() {
+        let Node::Inner {
+            data_map,
+            node_map,
+            data_start,
+            children_start,
+            adhash,
+        } = *store.get_node(node_idx)
+        else {
+            unreachable!("path frames are inner nodes");
+        };
+        let children_len = node_map.count_ones() as usize;
+        let children =
+            build_children_replacing(store, children_start, children_len, child_pos, child);
+        let new_children = store.alloc_children(children).expect("non-empty");
+        child = store.alloc_node(Node::Inner {
+            data_map,
+            node_map,
+            data_start,
+            children_start: new_children,
+            adhash: adhash::combine(adhash, delta),
+        });
+    }
+    child
+}
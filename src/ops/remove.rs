@@ -1,6 +1,7 @@
 //! Removal operation — COW path-copy delete with canonical inlining.
 
-use std::hash::Hash;
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
 
 use safe_bump::Idx;
 
@@ -24,17 +25,22 @@ pub enum RemoveOutcome<K, V> {
 }
 
 /// Removes `key` from the subtree rooted at `node_idx` via COW path-copy.
-pub fn remove_recursive<K, V, S>(
+///
+/// `key` may be any borrowed form of the stored key type.
+pub fn remove_recursive<K, V, S, Q, H>(
     store: &mut S,
+    hasher: &H,
     node_idx: Idx<Node<K, V>>,
     hash: u64,
-    key: &K,
+    key: &Q,
     shift: u32,
 ) -> RemoveOutcome<K, V>
 where
-    K: Hash + Eq + Clone,
+    K: Borrow<Q> + Clone,
     V: Hash + Clone,
     S: ChampStore<K, V>,
+    Q: Eq + ?Sized,
+    H: BuildHasher,
 {
     let node = *store.get_node(node_idx);
     match node {
@@ -46,6 +52,7 @@ where
             adhash,
         } => remove_from_inner(
             store,
+            hasher,
             data_map,
             node_map,
             data_start,
@@ -62,12 +69,14 @@ where
             adhash,
         } => remove_from_collision(
             store,
+            hasher,
             node_hash,
             entries_start,
             entries_len,
             adhash,
             hash,
             key,
+            shift,
         ),
     }
 }
@@ -77,21 +86,24 @@ where
 // ---------------------------------------------------------------------------
 
 #[allow(clippy::too_many_arguments)]
-fn remove_from_inner<K, V, S>(
+fn remove_from_inner<K, V, S, Q, H>(
     store: &mut S,
+    hasher: &H,
     data_map: u32,
     node_map: u32,
     data_start: Idx<Entry<K, V>>,
     children_start: Idx<Idx<Node<K, V>>>,
     adhash: u64,
     hash: u64,
-    key: &K,
+    key: &Q,
     shift: u32,
 ) -> RemoveOutcome<K, V>
 where
-    K: Hash + Eq + Clone,
+    K: Borrow<Q> + Clone,
     V: Hash + Clone,
     S: ChampStore<K, V>,
+    Q: Eq + ?Sized,
+    H: BuildHasher,
 {
     let frag = node::fragment(hash, shift);
     let bit = node::mask(frag);
@@ -102,8 +114,8 @@ where
         let pos = node::index(data_map, bit);
         let (found, removed_contrib, removed_val) = {
             let e = store.get_entry(node::offset(data_start, pos));
-            let found = e.hash == hash && e.key == *key;
-            let contrib = adhash::entry_adhash(e.hash, adhash::hash_one(&e.value));
+            let found = e.hash == hash && e.key.borrow() == key;
+            let contrib = adhash::entry_adhash(e.hash, hasher.hash_one(&e.value));
             let val = e.value.clone();
             (found, contrib, val)
         };
@@ -130,7 +142,7 @@ where
             node_map,
             data_start: new_data,
             children_start,
-            adhash: adhash.wrapping_sub(removed_contrib),
+            adhash: adhash::difference(adhash, removed_contrib),
         });
         RemoveOutcome::Removed {
             node: Some(new_node),
@@ -140,6 +152,7 @@ where
     } else if node_map & bit != 0 {
         remove_from_child(
             store,
+            hasher,
             data_map,
             node_map,
             data_start,
@@ -160,8 +173,9 @@ where
 /// Recurses into a child subtree and handles the outcome:
 /// inline, replace pointer, or remove empty child.
 #[allow(clippy::too_many_arguments)]
-fn remove_from_child<K, V, S>(
+fn remove_from_child<K, V, S, Q, H>(
     store: &mut S,
+    hasher: &H,
     data_map: u32,
     node_map: u32,
     data_start: Idx<Entry<K, V>>,
@@ -169,19 +183,28 @@ fn remove_from_child<K, V, S>(
     adhash: u64,
     bit: u32,
     hash: u64,
-    key: &K,
+    key: &Q,
     shift: u32,
     data_len: usize,
     children_len: usize,
 ) -> RemoveOutcome<K, V>
 where
-    K: Hash + Eq + Clone,
+    K: Borrow<Q> + Clone,
     V: Hash + Clone,
     S: ChampStore<K, V>,
+    Q: Eq + ?Sized,
+    H: BuildHasher,
 {
     let child_pos = node::index(node_map, bit);
     let old_child = *store.get_child(node::offset(children_start, child_pos));
-    let outcome = remove_recursive(store, old_child, hash, key, shift + node::BITS_PER_LEVEL);
+    let outcome = remove_recursive(
+        store,
+        hasher,
+        old_child,
+        hash,
+        key,
+        shift + node::BITS_PER_LEVEL,
+    );
 
     match outcome {
         RemoveOutcome::NotFound => RemoveOutcome::NotFound,
@@ -222,7 +245,7 @@ where
                         node_map,
                         data_start,
                         children_start: new_children,
-                        adhash: adhash.wrapping_sub(adhash_delta),
+                        adhash: adhash::difference(adhash, adhash_delta),
                     });
                     RemoveOutcome::Removed {
                         node: Some(new_node),
@@ -247,7 +270,7 @@ where
                     node_map: new_node_map,
                     data_start,
                     children_start: new_children,
-                    adhash: adhash.wrapping_sub(adhash_delta),
+                    adhash: adhash::difference(adhash, adhash_delta),
                 });
                 RemoveOutcome::Removed {
                     node: Some(new_node),
@@ -259,14 +282,15 @@ where
     }
 }
 
-/// Canonical form: a child with exactly one entry and no children
-/// should be inlined back into the parent.
+/// Canonical form: a child with exactly one entry and no children — an
+/// inner node, or a collision node reduced to one entry — should be inlined
+/// back into the parent.
 const fn should_inline<K, V>(node: &Node<K, V>) -> bool {
     match node {
         Node::Inner {
             data_map, node_map, ..
         } => data_map.is_power_of_two() && *node_map == 0,
-        Node::Collision { .. } => false,
+        Node::Collision { entries_len, .. } => *entries_len == 1,
     }
 }
 
@@ -296,7 +320,7 @@ where
     let child = *store.get_node(child_idx);
     let child_data_start = match child {
         Node::Inner { data_start, .. } => data_start,
-        Node::Collision { .. } => unreachable!("should_inline returned false for collision"),
+        Node::Collision { entries_start, .. } => entries_start,
     };
     let inlined_entry = clone_entry(store, child_data_start);
 
@@ -319,7 +343,7 @@ where
         node_map: new_node_map,
         data_start: new_data,
         children_start: new_children,
-        adhash: adhash.wrapping_sub(adhash_delta),
+        adhash: adhash::difference(adhash, adhash_delta),
     });
     RemoveOutcome::Removed {
         node: Some(new_node),
@@ -332,69 +356,60 @@ where
 // Collision node remove
 // ---------------------------------------------------------------------------
 
-fn remove_from_collision<K, V, S>(
+#[allow(clippy::too_many_arguments)]
+fn remove_from_collision<K, V, S, Q, H>(
     store: &mut S,
+    hasher: &H,
     node_hash: u64,
     entries_start: Idx<Entry<K, V>>,
-    entries_len: u8,
+    entries_len: u32,
     adhash: u64,
     hash: u64,
-    key: &K,
+    key: &Q,
+    shift: u32,
 ) -> RemoveOutcome<K, V>
 where
-    K: Hash + Eq + Clone,
+    K: Borrow<Q> + Clone,
     V: Hash + Clone,
     S: ChampStore<K, V>,
+    Q: Eq + ?Sized,
+    H: BuildHasher,
 {
+    debug_assert!(shift > node::MAX_SHIFT, "collision node above max depth");
     if hash != node_hash {
         return RemoveOutcome::NotFound;
     }
 
-    let len = usize::from(entries_len);
-    for i in 0..len {
-        let (found, removed_contrib, removed_val) = {
-            let e = store.get_entry(node::offset(entries_start, i));
-            let found = e.key == *key;
-            let contrib = adhash::entry_adhash(e.hash, adhash::hash_one(&e.value));
-            let val = e.value.clone();
-            (found, contrib, val)
-        };
-
-        if !found {
-            continue;
-        }
-
-        if len == 2 {
-            // Collision with 2 entries → removing one leaves a single entry.
-            // Promote it to a regular inner node at this depth.
-            let other = 1 - i;
-            let remaining = clone_entry(store, node::offset(entries_start, other));
-            let remaining_contrib =
-                adhash::entry_adhash(remaining.hash, adhash::hash_one(&remaining.value));
-            let frag = node::fragment(remaining.hash, 0);
-            let bit = node::mask(frag);
-            let data_start = store.alloc_entries([remaining]).expect("single entry");
-            let new_node = store.alloc_node(Node::Inner {
-                data_map: bit,
-                node_map: 0,
-                data_start,
-                children_start: Idx::from_raw(0),
-                adhash: remaining_contrib,
-            });
-            return RemoveOutcome::Removed {
-                node: Some(new_node),
-                adhash_delta: removed_contrib,
-                removed_value: removed_val,
-            };
-        }
+    let len = entries_len as usize;
+    // Locate the key before building anything: every path below reports a
+    // removal, so none may be reached for a key the node does not hold.
+    let Some(i) =
+        (0..len).find(|&i| store.get_entry(node::offset(entries_start, i)).key.borrow() == key)
+    else {
+        return RemoveOutcome::NotFound;
+    };
+    let (removed_contrib, removed_val) = {
+        let e = store.get_entry(node::offset(entries_start, i));
+        let contrib = adhash::entry_adhash(e.hash, hasher.hash_one(&e.value));
+        (contrib, e.value.clone())
+    };
 
-        let entries = build_entries_removing(store, entries_start, len, i);
-        let new_start = store.alloc_entries(entries).expect("at least 2 remaining");
+    if len == 2 {
+        // Collision with 2 entries → removing one leaves a single entry.
+        // Collision nodes sit below `MAX_SHIFT`, where no hash bits are
+        // left to place the entry in an inner node's bitmap. Return it
+        // as a single-entry collision node; `should_inline` makes the
+        // parent pull it back up, so it never persists in the trie.
+        let other = 1 - i;
+        let remaining = clone_entry(store, node::offset(entries_start, other));
+        let remaining_contrib =
+            adhash::entry_adhash(remaining.hash, hasher.hash_one(&remaining.value));
+        let data_start = store.alloc_entries([remaining]).expect("single entry");
         let new_node = store.alloc_node(Node::Collision {
             hash: node_hash,
-            entries_start: new_start,
-            entries_len: entries_len - 1,
-            adhash: adhash.wrapping_sub(removed_contrib),
+            entries_start: data_start,
+            entries_len: 1,
+            adhash: remaining_contrib,
         });
         return RemoveOutcome::Removed {
             node: Some(new_node),
@@ -403,7 +418,19 @@ where
         };
     }
 
-    RemoveOutcome::NotFound
+    let entries = build_entries_removing(store, entries_start, len, i);
+    let new_start = store.alloc_entries(entries).expect("at least 2 remaining");
+    let new_node = store.alloc_node(Node::Collision {
+        hash: node_hash,
+        entries_start: new_start,
+        entries_len: entries_len - 1,
+        adhash: adhash::difference(adhash, removed_contrib),
+    });
+    RemoveOutcome::Removed {
+        node: Some(new_node),
+        adhash_delta: removed_contrib,
+        removed_value: removed_val,
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -0,0 +1,385 @@
+//! Value transforms — rebuild a trie with new values and an unchanged shape.
+//!
+//! The key set, and therefore every bitmap, stays the same; only the entry
+//! blocks and the per-node `AdHash` are rebuilt.
+
+use std::hash::{BuildHasher, Hash};
+
+use safe_bump::Idx;
+
+use crate::adhash;
+use crate::node::{self, Entry, Node};
+use crate::ops::insert::{alloc_or_sentinel, clone_entry};
+use crate::store::ChampStore;
+
+/// COW-copies the whole subtree rooted at `node_idx`, applying `f` to every
+/// value. Returns the new root.
+///
+/// The original nodes are left untouched, so checkpoints taken before the
+/// call remain valid for rollback.
+pub fn update_values<K, V, S, F, H>(
+    store: &mut S,
+    hasher: &H,
+    node_idx: Idx<Node<K, V>>,
+    f: &mut F,
+) -> Idx<Node<K, V>>
+where
+    K: Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    F: FnMut(&K, &mut V),
+    H: BuildHasher,
+{
+    match *store.get_node(node_idx) {
+        Node::Inner {
+            data_map,
+            node_map,
+            data_start,
+            children_start,
+            ..
+        } => {
+            let (entries, mut adhash) =
+                updated_entries(store, hasher, data_start, data_map.count_ones() as usize, f);
+            let old_children: Vec<Idx<Node<K, V>>> = (0..node_map.count_ones() as usize)
+                .map(|i| *store.get_child(node::offset(children_start, i)))
+                .collect();
+            let mut children = Vec::with_capacity(old_children.len());
+            for child in old_children {
+                let new_child = update_values(store, hasher, child, f);
+                adhash = adhash::combine(adhash, store.get_node(new_child).adhash());
+                children.push(new_child);
+            }
+            let data_start = alloc_or_sentinel(store.alloc_entries(entries));
+            let children_start = alloc_or_sentinel(store.alloc_children(children));
+            store.alloc_node(Node::Inner {
+                data_map,
+                node_map,
+                data_start,
+                children_start,
+                adhash,
+            })
+        }
+        Node::Collision {
+            hash,
+            entries_start,
+            entries_len,
+            ..
+        } => {
+            let (entries, adhash) =
+                updated_entries(store, hasher, entries_start, entries_len as usize, f);
+            let entries_start = store.alloc_entries(entries).expect("non-empty");
+            store.alloc_node(Node::Collision {
+                hash,
+                entries_start,
+                entries_len,
+                adhash,
+            })
+        }
+    }
+}
+
+/// Overwrites the values of the keys in `updates` found in the subtree
+/// `node_idx`, rebuilding each touched node once. Returns the new root.
+///
+/// `updates` holds `(hash, key, value)` triples sorted by hash, as for
+/// [`remove_keys`](super::merge::remove_keys); values are taken out as they
+/// are applied. Absent keys are ignored, and a key given more than once gets
+/// its last value. Untouched subtrees are reused, and `node_idx` itself is
+/// returned if nothing changed. `applied` is incremented by the number of
+/// entries overwritten.
+pub fn overwrite_values<K, V, S, H>(
+    store: &mut S,
+    hasher: &H,
+    node_idx: Idx<Node<K, V>>,
+    updates: &mut [(u64, K, Option<V>)],
+    shift: u32,
+    applied: &mut usize,
+) -> Idx<Node<K, V>>
+where
+    K: Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    match *store.get_node(node_idx) {
+        Node::Inner {
+            data_map,
+            node_map,
+            data_start,
+            children_start,
+            ..
+        } => {
+            let mut entries: Vec<Option<Entry<K, V>>> =
+                (0..data_map.count_ones()).map(|_| None).collect();
+            let mut children: Vec<Idx<Node<K, V>>> = (0..node_map.count_ones() as usize)
+                .map(|i| *store.get_child(node::offset(children_start, i)))
+                .collect();
+            let mut changed = false;
+            for group in updates
+                .chunk_by_mut(|x, y| node::fragment(x.0, shift) == node::fragment(y.0, shift))
+            {
+                let bit = node::mask(node::fragment(group[0].0, shift));
+                if data_map & bit != 0 {
+                    let i = node::index(data_map, bit);
+                    let e = store.get_entry(node::offset(data_start, i));
+                    if let Some(value) = take_last(group, e.hash, &e.key) {
+                        let mut e = clone_entry(store, node::offset(data_start, i));
+                        e.value = value;
+                        entries[i] = Some(e);
+                        *applied += 1;
+                        changed = true;
+                    }
+                } else if node_map & bit != 0 {
+                    let i = node::index(node_map, bit);
+                    let child = overwrite_values(
+                        store,
+                        hasher,
+                        children[i],
+                        group,
+                        shift + node::BITS_PER_LEVEL,
+                        applied,
+                    );
+                    changed |= child != children[i];
+                    children[i] = child;
+                }
+            }
+            if !changed {
+                return node_idx;
+            }
+
+            let mut adhash = adhash::EMPTY;
+            let entries: Vec<Entry<K, V>> = entries
+                .into_iter()
+                .enumerate()
+                .map(|(i, e)| {
+                    let e = e.unwrap_or_else(|| clone_entry(store, node::offset(data_start, i)));
+                    adhash = adhash::combine(adhash, contribution(hasher, &e));
+                    e
+                })
+                .collect();
+            for &child in &children {
+                adhash = adhash::combine(adhash, store.get_node(child).adhash());
+            }
+            let data_start = alloc_or_sentinel(store.alloc_entries(entries));
+            let children_start = alloc_or_sentinel(store.alloc_children(children));
+            store.alloc_node(Node::Inner {
+                data_map,
+                node_map,
+                data_start,
+                children_start,
+                adhash,
+            })
+        }
+        Node::Collision {
+            hash,
+            entries_start,
+            entries_len,
+            ..
+        } => {
+            let before = *applied;
+            let mut adhash = adhash::EMPTY;
+            let entries: Vec<Entry<K, V>> = (0..entries_len as usize)
+                .map(|i| {
+                    let mut e = clone_entry(store, node::offset(entries_start, i));
+                    if let Some(value) = take_last(updates, hash, &e.key) {
+                        e.value = value;
+                        *applied += 1;
+                    }
+                    adhash = adhash::combine(adhash, contribution(hasher, &e));
+                    e
+                })
+                .collect();
+            if *applied == before {
+                return node_idx;
+            }
+            let entries_start = store.alloc_entries(entries).expect("non-empty");
+            store.alloc_node(Node::Collision {
+                hash,
+                entries_start,
+                entries_len,
+                adhash,
+            })
+        }
+    }
+}
+
+/// Takes every value given for `(hash, key)` in `group`, returning the last.
+fn take_last<K: Eq, V>(group: &mut [(u64, K, Option<V>)], hash: u64, key: &K) -> Option<V> {
+    let mut last = None;
+    for (h, k, v) in group.iter_mut() {
+        if *h == hash && k == key {
+            last = v.take().or(last);
+        }
+    }
+    last
+}
+
+/// Returns the `AdHash` contribution of `e`.
+fn contribution<K, V: Hash, H: BuildHasher>(hasher: &H, e: &Entry<K, V>) -> u64 {
+    adhash::entry_adhash(e.hash, hasher.hash_one(&e.value))
+}
+
+/// Appends the index of every entry under `node_idx` to `out`, in the order
+/// [`update_values`] visits them: a node's own entries, then each child's
+/// subtree.
+pub fn entry_indices<K, V, S: ChampStore<K, V>>(
+    store: &S,
+    node_idx: Idx<Node<K, V>>,
+    out: &mut Vec<Idx<Entry<K, V>>>,
+) {
+    match *store.get_node(node_idx) {
+        Node::Inner {
+            data_map,
+            node_map,
+            data_start,
+            children_start,
+            ..
+        } => {
+            out.extend((0..data_map.count_ones() as usize).map(|i| node::offset(data_start, i)));
+            for i in 0..node_map.count_ones() as usize {
+                entry_indices(
+                    store,
+                    *store.get_child(node::offset(children_start, i)),
+                    out,
+                );
+            }
+        }
+        Node::Collision {
+            entries_start,
+            entries_len,
+            ..
+        } => out.extend((0..entries_len as usize).map(|i| node::offset(entries_start, i))),
+    }
+}
+
+/// Copies the subtree rooted at `node_idx` from `src` into `dst`, mapping
+/// every value through `f`. Returns the new root.
+///
+/// Keys and hashes are cloned as-is, so the copy has the same bitmaps and
+/// the same number of live nodes; only `AdHash` values are recomputed.
+pub fn map_subtree<K, V, W, S, D, F, H>(
+    src: &S,
+    hasher: &H,
+    dst: &mut D,
+    node_idx: Idx<Node<K, V>>,
+    f: &mut F,
+) -> Idx<Node<K, W>>
+where
+    K: Clone,
+    W: Hash,
+    S: ChampStore<K, V>,
+    D: ChampStore<K, W>,
+    F: FnMut(&V) -> W,
+    H: BuildHasher,
+{
+    match *src.get_node(node_idx) {
+        Node::Inner {
+            data_map,
+            node_map,
+            data_start,
+            children_start,
+            ..
+        } => {
+            let (entries, mut adhash) =
+                mapped_entries(src, hasher, data_start, data_map.count_ones() as usize, f);
+            let mut children = Vec::with_capacity(node_map.count_ones() as usize);
+            for i in 0..node_map.count_ones() as usize {
+                let child = *src.get_child(node::offset(children_start, i));
+                let new_child = map_subtree(src, hasher, dst, child, f);
+                adhash = adhash::combine(adhash, dst.get_node(new_child).adhash());
+                children.push(new_child);
+            }
+            let data_start = alloc_or_sentinel(dst.alloc_entries(entries));
+            let children_start = alloc_or_sentinel(dst.alloc_children(children));
+            dst.alloc_node(Node::Inner {
+                data_map,
+                node_map,
+                data_start,
+                children_start,
+                adhash,
+            })
+        }
+        Node::Collision {
+            hash,
+            entries_start,
+            entries_len,
+            ..
+        } => {
+            let (entries, adhash) =
+                mapped_entries(src, hasher, entries_start, entries_len as usize, f);
+            let entries_start = dst.alloc_entries(entries).expect("non-empty");
+            dst.alloc_node(Node::Collision {
+                hash,
+                entries_start,
+                entries_len,
+                adhash,
+            })
+        }
+    }
+}
+
+/// Clones an entry block with `f` applied to each value. Returns the new
+/// entries and the sum of their `AdHash` contributions.
+fn updated_entries<K, V, S, F, H>(
+    store: &S,
+    hasher: &H,
+    start: Idx<Entry<K, V>>,
+    len: usize,
+    f: &mut F,
+) -> (Vec<Entry<K, V>>, u64)
+where
+    K: Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    F: FnMut(&K, &mut V),
+    H: BuildHasher,
+{
+    let mut adhash = adhash::EMPTY;
+    let entries = (0..len)
+        .map(|i| {
+            let mut e = clone_entry(store, node::offset(start, i));
+            f(&e.key, &mut e.value);
+            adhash = adhash::combine(
+                adhash,
+                adhash::entry_adhash(e.hash, hasher.hash_one(&e.value)),
+            );
+            e
+        })
+        .collect();
+    (entries, adhash)
+}
+
+/// Maps an entry block into entries with values `f(value)`. Returns the new
+/// entries and the sum of their `AdHash` contributions.
+fn mapped_entries<K, V, W, S, F, H>(
+    store: &S,
+    hasher: &H,
+    start: Idx<Entry<K, V>>,
+    len: usize,
+    f: &mut F,
+) -> (Vec<Entry<K, W>>, u64)
+where
+    K: Clone,
+    W: Hash,
+    S: ChampStore<K, V>,
+    F: FnMut(&V) -> W,
+    H: BuildHasher,
+{
+    let mut adhash = adhash::EMPTY;
+    let entries = (0..len)
+        .map(|i| {
+            let e = store.get_entry(node::offset(start, i));
+            let value = f(&e.value);
+            adhash = adhash::combine(
+                adhash,
+                adhash::entry_adhash(e.hash, hasher.hash_one(&value)),
+            );
+            Entry {
+                hash: e.hash,
+                key: e.key.clone(),
+                value,
+            }
+        })
+        .collect();
+    (entries, adhash)
+}
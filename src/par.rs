@@ -0,0 +1,172 @@
+//! Parallel iteration over [`ChampMapSync`](crate::ChampMapSync), enabled by
+//! the `rayon` feature.
+//!
+//! Work is split along the trie itself: a subtree is split into its inline
+//! entry block and its (up to [`BRANCHING`](crate::node::BRANCHING)) child
+//! subtrees, each an independent task, and large entry blocks are halved.
+//! Splitting recurses as far as rayon's adaptive splitter asks for. Only the
+//! thread-safe map is supported, since its `SharedArena` backend is `Sync`.
+
+use rayon::iter::ParallelIterator;
+use rayon::iter::plumbing::{Folder, UnindexedConsumer, UnindexedProducer, bridge_unindexed};
+use safe_bump::Idx;
+
+use crate::arena_sync::ChampArenaSync;
+use crate::node::{self, Entry, Node};
+use crate::store::ChampStore;
+
+/// Parallel iterator over `(&K, &V)` pairs of a
+/// [`ChampMapSync`](crate::ChampMapSync), in unspecified order.
+///
+/// Created by `par_iter()` via rayon's `IntoParallelRefIterator`.
+pub struct ParIter<'a, K, V> {
+    store: &'a ChampArenaSync<K, V>,
+    root: Option<Idx<Node<K, V>>>,
+}
+
+impl<'a, K, V> ParIter<'a, K, V> {
+    pub(crate) const fn new(
+        store: &'a ChampArenaSync<K, V>,
+        root: Option<Idx<Node<K, V>>>,
+    ) -> Self {
+        Self { store, root }
+    }
+}
+
+impl<'a, K: Send + Sync, V: Send + Sync> ParallelIterator for ParIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn drive_unindexed<C: UnindexedConsumer<Self::Item>>(self, consumer: C) -> C::Result {
+        let producer = TrieProducer {
+            store: self.store,
+            work: self.root.map(Work::Node).into_iter().collect(),
+        };
+        bridge_unindexed(producer, consumer)
+    }
+}
+
+/// One unit of work: a whole subtree, or a run of a node's entries.
+enum Work<K, V> {
+    Node(Idx<Node<K, V>>),
+    Entries { start: Idx<Entry<K, V>>, len: usize },
+}
+
+impl<K, V> Clone for Work<K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for Work<K, V> {}
+
+/// Splittable set of disjoint work units.
+struct TrieProducer<'a, K, V> {
+    store: &'a ChampArenaSync<K, V>,
+    work: Vec<Work<K, V>>,
+}
+
+impl<'a, K: Send + Sync, V: Send + Sync> UnindexedProducer for TrieProducer<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn split(mut self) -> (Self, Option<Self>) {
+        // A lone subtree is opened up into its entry block and children.
+        while let [Work::Node(idx)] = self.work[..] {
+            self.work = expand(self.store, idx);
+        }
+        if let [Work::Entries { start, len }] = self.work[..] {
+            if len < 2 {
+                return (self, None);
+            }
+            let half = len / 2;
+            self.work = vec![Work::Entries { start, len: half }];
+            let right = Work::Entries {
+                start: node::offset(start, half),
+                len: len - half,
+            };
+            let store = self.store;
+            return (
+                self,
+                Some(Self {
+                    store,
+                    work: vec![right],
+                }),
+            );
+        }
+        if self.work.len() < 2 {
+            return (self, None);
+        }
+        let right = self.work.split_off(self.work.len() / 2);
+        let store = self.store;
+        (self, Some(Self { store, work: right }))
+    }
+
+    fn fold_with<F: Folder<Self::Item>>(self, mut folder: F) -> F {
+        for work in self.work {
+            folder = fold_work(self.store, work, folder);
+            if folder.full() {
+                break;
+            }
+        }
+        folder
+    }
+}
+
+/// Returns the work units of a node: its entry block, then its children.
+fn expand<K, V>(store: &ChampArenaSync<K, V>, idx: Idx<Node<K, V>>) -> Vec<Work<K, V>> {
+    let node = store.get_node(idx);
+    let mut work = Vec::with_capacity(1 + node.children_len());
+    match *node {
+        Node::Inner {
+            data_start,
+            children_start,
+            ..
+        } => {
+            if node.data_len() > 0 {
+                work.push(Work::Entries {
+                    start: data_start,
+                    len: node.data_len(),
+                });
+            }
+            work.extend(
+                (0..node.children_len())
+                    .map(|i| Work::Node(*store.get_child(node::offset(children_start, i)))),
+            );
+        }
+        Node::Collision {
+            entries_start,
+            entries_len,
+            ..
+        } => work.push(Work::Entries {
+            start: entries_start,
+            len: entries_len as usize,
+        }),
+    }
+    work
+}
+
+/// Feeds every entry of `work` to `folder`, stopping early once it is full.
+fn fold_work<'a, K, V, F>(store: &'a ChampArenaSync<K, V>, work: Work<K, V>, mut folder: F) -> F
+where
+    F: Folder<(&'a K, &'a V)>,
+{
+    match work {
+        Work::Entries { start, len } => {
+            for i in 0..len {
+                let e = store.get_entry(node::offset(start, i));
+                folder = folder.consume((&e.key, &e.value));
+                if folder.full() {
+                    break;
+                }
+            }
+        }
+        Work::Node(idx) => {
+            for work in expand(store, idx) {
+                folder = fold_work(store, work, folder);
+                if folder.full() {
+                    break;
+                }
+            }
+        }
+    }
+    folder
+}
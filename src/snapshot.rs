@@ -0,0 +1,165 @@
+//! Read-only versions of a map that survive further mutation.
+//!
+//! Path copying never modifies a node once it is allocated: every write
+//! appends a new root, and the old root still describes the old contents.
+//! A [`Snapshot`] records such a root together with its length and
+//! `AdHash`. It holds no borrow, so the map stays writable; reading it back
+//! goes through the map's arena with
+//! [`ChampMap::view`](crate::ChampMap::view).
+//!
+//! A snapshot stays valid as long as its nodes stay in the arena — until
+//! the map is rolled back past it, [`compact`](crate::ChampMap::compact)ed,
+//! [`clear`](crate::ChampMap::clear)ed or drained. Each snapshot carries the
+//! arena's rollback generation, so viewing an invalidated one panics instead
+//! of reading whatever nodes took its place.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+use safe_bump::Idx;
+
+use crate::adhash::DefaultHashBuilder;
+use crate::arena::ChampArena;
+use crate::iter::Iter;
+use crate::node::Node;
+use crate::ops::get::get_recursive;
+use crate::store::{ChampStore, Generation};
+
+/// Frozen version of a map, created by
+/// [`ChampMap::snapshot`](crate::ChampMap::snapshot) or
+/// [`ChampMapSync::snapshot`](crate::ChampMapSync::snapshot).
+///
+/// Taking a snapshot is O(1) and allocates nothing. Its length and
+/// `AdHash` are available directly; lookups and iteration need the arena,
+/// via the map's `view`.
+pub struct Snapshot<K, V> {
+    pub(crate) root: Option<Idx<Node<K, V>>>,
+    pub(crate) size: usize,
+    pub(crate) adhash: u64,
+    pub(crate) generation: Generation,
+}
+
+// Snapshot contains only an index, a Generation (Copy) and primitives — no
+// actual K/V data.
+
+impl<K, V> Clone for Snapshot<K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for Snapshot<K, V> {}
+
+impl<K, V> fmt::Debug for Snapshot<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Snapshot")
+            .field("len", &self.size)
+            .field("adhash", &format_args!("{:#018x}", self.adhash))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<K, V> Snapshot<K, V> {
+    /// Returns the number of key-value pairs at snapshot time.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if the map was empty at snapshot time.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the `AdHash` at snapshot time.
+    #[must_use]
+    pub const fn adhash(&self) -> u64 {
+        self.adhash
+    }
+}
+
+/// A [`Snapshot`] read through the arena of the map it was taken from.
+///
+/// Created by [`ChampMap::view`](crate::ChampMap::view). Borrows the map
+/// immutably; drop the view to write to the map again — the snapshot itself
+/// can be kept and viewed later.
+pub struct SnapshotView<'a, K, V, S = ChampArena<K, V>, H = DefaultHashBuilder> {
+    store: &'a S,
+    hasher: &'a H,
+    snapshot: Snapshot<K, V>,
+}
+
+impl<'a, K, V, S, H> SnapshotView<'a, K, V, S, H> {
+    /// Creates a view of `snapshot` over `store`, hashing probes with `hasher`.
+    pub(crate) const fn new(store: &'a S, hasher: &'a H, snapshot: Snapshot<K, V>) -> Self {
+        Self {
+            store,
+            hasher,
+            snapshot,
+        }
+    }
+
+    /// Returns the number of key-value pairs in the snapshot.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.snapshot.size
+    }
+
+    /// Returns `true` if the snapshot contains no entries.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.snapshot.size == 0
+    }
+
+    /// Returns the snapshot's `AdHash`.
+    #[must_use]
+    pub const fn adhash(&self) -> u64 {
+        self.snapshot.adhash
+    }
+}
+
+impl<'a, K: 'a, V: 'a, S: ChampStore<K, V>, H> SnapshotView<'a, K, V, S, H> {
+    /// Returns an iterator over the snapshot's `(&K, &V)` pairs.
+    #[must_use]
+    pub fn iter(&self) -> Iter<'a, K, V, S> {
+        Iter::new(self.store, self.snapshot.root, self.snapshot.size)
+    }
+}
+
+impl<'a, K, V, S, H> SnapshotView<'a, K, V, S, H>
+where
+    K: Hash + Eq + 'a,
+    V: 'a,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    /// Returns the value `key` had at snapshot time.
+    #[must_use]
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&'a V>
+    where
+        K: Borrow<Q>,
+    {
+        let root = self.snapshot.root?;
+        get_recursive(self.store, root, self.hasher.hash_one(key), key, 0).map(|e| &e.value)
+    }
+
+    /// Returns `true` if the snapshot contains `key`.
+    #[must_use]
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+}
+
+impl<'a, K: 'a, V: 'a, S: ChampStore<K, V>, H> IntoIterator for &SnapshotView<'a, K, V, S, H> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -1,5 +1,7 @@
 //! Storage abstraction for CHAMP trie operations.
 
+use std::fmt;
+
 use safe_bump::{Checkpoint, Idx};
 
 use crate::node::{Entry, Node};
@@ -12,9 +14,12 @@ pub struct StoreCheckpoint<K, V> {
     pub entries: Checkpoint<Entry<K, V>>,
     /// Children arena checkpoint.
     pub children: Checkpoint<Idx<Node<K, V>>>,
+    /// Position in the store's rollback history.
+    pub generation: Generation,
 }
 
-// StoreCheckpoint contains only Checkpoint<T> values (Copy) — no K/V data.
+// StoreCheckpoint contains only Checkpoint<T> values and a Generation
+// (Copy) — no K/V data.
 
 impl<K, V> Clone for StoreCheckpoint<K, V> {
     fn clone(&self) -> Self {
@@ -24,11 +29,127 @@ impl<K, V> Clone for StoreCheckpoint<K, V> {
 
 impl<K, V> Copy for StoreCheckpoint<K, V> {}
 
+/// When a checkpoint was taken: the number of rollbacks performed before it
+/// and the total number of items allocated at that point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Generation {
+    rollbacks: u64,
+    len: usize,
+}
+
+/// Rollback history of a store, used to reject checkpoints that an earlier
+/// rollback invalidated.
+///
+/// Rolling back to a checkpoint truncates the arenas, so every checkpoint
+/// taken after it now points past data that later writes will overwrite. A
+/// checkpoint is stale exactly when some rollback performed after it was
+/// taken went to a smaller allocation count. Only the rollbacks that can
+/// still decide that are kept: a later rollback to a smaller count
+/// supersedes every earlier one, so `floors` is increasing in both fields.
+#[derive(Clone, Debug, Default)]
+pub struct Generations {
+    rollbacks: u64,
+    floors: Vec<(u64, usize)>,
+}
+
+impl Generations {
+    /// Creates an empty history.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            rollbacks: 0,
+            floors: Vec::new(),
+        }
+    }
+
+    /// Stamps a checkpoint taken with `len` items allocated in total.
+    #[must_use]
+    pub const fn stamp(&self, len: usize) -> Generation {
+        Generation {
+            rollbacks: self.rollbacks,
+            len,
+        }
+    }
+
+    /// Returns `false` if a rollback since `cp` was taken went below it.
+    #[must_use]
+    pub fn is_valid(&self, cp: Generation) -> bool {
+        let first_later = self.floors.partition_point(|&(r, _)| r < cp.rollbacks);
+        self.floors
+            .get(first_later)
+            .is_none_or(|&(_, len)| len >= cp.len)
+    }
+
+    /// Records a rollback to `cp`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cp` was invalidated by an earlier rollback.
+    pub fn rollback(&mut self, cp: Generation) {
+        assert!(
+            self.is_valid(cp),
+            "rollback to a checkpoint invalidated by an earlier rollback"
+        );
+        self.truncate(cp.len);
+    }
+
+    /// Records that the store was emptied: every checkpoint taken with
+    /// items allocated becomes invalid.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Returns the history for a store that replaces this one wholesale:
+    /// this history followed by a [`clear`](Self::clear), so checkpoints
+    /// taken on the old store stay rejected.
+    #[must_use]
+    pub fn cleared(&self) -> Self {
+        let mut next = self.clone();
+        next.clear();
+        next
+    }
+
+    fn truncate(&mut self, len: usize) {
+        while self.floors.last().is_some_and(|&(_, l)| l >= len) {
+            self.floors.pop();
+        }
+        self.floors.push((self.rollbacks, len));
+        self.rollbacks += 1;
+    }
+}
+
+/// An arena item a [`ChampStore`] could not read. Positions are raw indices
+/// into the respective arena.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoreError {
+    /// A node could not be read.
+    Node(usize),
+    /// An entry could not be read.
+    Entry(usize),
+    /// A child slot could not be read.
+    Child(usize),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Node(i) => write!(f, "node {i} could not be read"),
+            Self::Entry(i) => write!(f, "entry {i} could not be read"),
+            Self::Child(i) => write!(f, "child slot {i} could not be read"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
 /// Storage backend for CHAMP operations.
 ///
 /// Abstracts over [`Arena`](safe_bump::Arena) (single-thread) and
 /// [`SharedArena`](safe_bump::SharedArena) (multi-thread) backends.
 pub trait ChampStore<K, V> {
+    /// Owning iterator over every allocated entry, in index order.
+    type IntoEntries: Iterator<Item = Entry<K, V>>;
+
     /// Allocates a single node, returning its index.
     fn alloc_node(&mut self, node: Node<K, V>) -> Idx<Node<K, V>>;
 
@@ -55,15 +176,97 @@ pub trait ChampStore<K, V> {
     /// Returns a reference to the child index at `idx`.
     fn get_child(&self, idx: Idx<Idx<Node<K, V>>>) -> &Idx<Node<K, V>>;
 
+    /// Returns a reference to the node at `idx`, for backends whose reads
+    /// can fail. Defaults to [`get_node`](Self::get_node).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StoreError::Node`] if the node cannot be read.
+    fn try_get_node(&self, idx: Idx<Node<K, V>>) -> Result<&Node<K, V>, StoreError> {
+        Ok(self.get_node(idx))
+    }
+
+    /// Returns a reference to the entry at `idx`, for backends whose reads
+    /// can fail. Defaults to [`get_entry`](Self::get_entry).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StoreError::Entry`] if the entry cannot be read.
+    fn try_get_entry(&self, idx: Idx<Entry<K, V>>) -> Result<&Entry<K, V>, StoreError> {
+        Ok(self.get_entry(idx))
+    }
+
+    /// Returns a reference to the child index at `idx`, for backends whose
+    /// reads can fail. Defaults to [`get_child`](Self::get_child).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StoreError::Child`] if the child slot cannot be read.
+    fn try_get_child(&self, idx: Idx<Idx<Node<K, V>>>) -> Result<&Idx<Node<K, V>>, StoreError> {
+        Ok(self.get_child(idx))
+    }
+
     /// Saves the current state of all three arenas.
     fn checkpoint(&self) -> StoreCheckpoint<K, V>;
 
     /// Rolls back all three arenas to a previous checkpoint.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a rollback to an earlier checkpoint has invalidated `cp`.
     fn rollback(&mut self, cp: StoreCheckpoint<K, V>);
 
+    /// Returns `false` if a rollback or clear since `generation` was stamped
+    /// discarded items allocated before it.
+    fn is_valid(&self, generation: Generation) -> bool;
+
+    /// Drops every allocated item, resetting all three arenas to empty.
+    ///
+    /// Allocated capacity is retained for reuse. Checkpoints taken before
+    /// the call are invalidated.
+    fn clear(&mut self);
+
+    /// Takes over the rollback history of `old`, an emptied or discarded
+    /// store that this one replaces wholesale, as if `old` had been
+    /// cleared: checkpoints taken on `old` are rejected by
+    /// [`rollback`](Self::rollback) here instead of being applied to
+    /// unrelated items.
+    fn supersede(&mut self, old: &Self);
+
     /// Returns the total number of allocated items in each arena:
     /// `(nodes, entries, children)`.
     ///
     /// Includes dead COW copies — reflects true memory footprint.
     fn arena_len(&self) -> (usize, usize, usize);
+
+    /// Returns the allocated capacity of each arena, used or not:
+    /// `(nodes, entries, children)`.
+    ///
+    /// A backend that cannot tell reports [`arena_len`](Self::arena_len).
+    fn arena_capacity(&self) -> (usize, usize, usize);
+
+    /// Reserves capacity for at least `nodes`, `entries` and `children`
+    /// more items in the respective arenas, so that many allocations grow
+    /// each arena at most once.
+    ///
+    /// May be a no-op for backends that cannot be pre-sized.
+    fn reserve(&mut self, nodes: usize, entries: usize, children: usize);
+
+    /// Releases unused capacity in all three arenas. Allocated items,
+    /// indices and checkpoints are unaffected.
+    ///
+    /// May be a no-op for backends that cannot give capacity back.
+    fn shrink_to_fit(&mut self);
+
+    /// Consumes the store, yielding every allocated entry in index order —
+    /// dead COW copies included.
+    fn into_entries(self) -> Self::IntoEntries;
+}
+
+/// Returns the bytes taken by `(nodes, entries, children)` arena items:
+/// the items themselves, not heap memory owned by keys or values.
+pub(crate) const fn arena_bytes<K, V>((nodes, entries, children): (usize, usize, usize)) -> usize {
+    nodes * size_of::<Node<K, V>>()
+        + entries * size_of::<Entry<K, V>>()
+        + children * size_of::<Idx<Node<K, V>>>()
 }
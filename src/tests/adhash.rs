@@ -1,14 +1,14 @@
 use crate::ChampMap;
-use crate::adhash::{entry_adhash, hash_one};
+use crate::adhash::{EMPTY, combine, entry_adhash, hash_one, remove_contribution};
 
-/// φ(∅) = 0.
+/// φ(∅) is the identity of `combine`: 0, or 1 with `strong-hash`.
 #[test]
 fn empty_adhash_is_zero() {
     let map: ChampMap<String, String> = ChampMap::new();
-    assert_eq!(map.adhash(), 0);
+    assert_eq!(map.adhash(), EMPTY);
 }
 
-/// φ(S ∪ {e}) = φ(S) + f(e).
+/// φ(S ∪ {e}) = combine(φ(S), f(e)).
 #[test]
 fn incremental_insert() {
     let mut map = ChampMap::new();
@@ -16,16 +16,16 @@ fn incremental_insert() {
 
     map.insert("a", 1);
     let h1 = map.adhash();
-    let expected = h0.wrapping_add(entry_adhash(hash_one(&"a"), hash_one(&1)));
+    let expected = combine(h0, entry_adhash(hash_one(&"a"), hash_one(&1)));
     assert_eq!(h1, expected);
 
     map.insert("b", 2);
     let h2 = map.adhash();
-    let expected2 = h1.wrapping_add(entry_adhash(hash_one(&"b"), hash_one(&2)));
+    let expected2 = combine(h1, entry_adhash(hash_one(&"b"), hash_one(&2)));
     assert_eq!(h2, expected2);
 }
 
-/// Insert + remove roundtrip: φ returns to 0.
+/// Insert + remove roundtrip: φ returns to φ(∅).
 #[test]
 fn roundtrip_to_zero() {
     let mut map = ChampMap::new();
@@ -35,7 +35,7 @@ fn roundtrip_to_zero() {
     map.remove(&1);
     map.remove(&2);
     map.remove(&3);
-    assert_eq!(map.adhash(), 0);
+    assert_eq!(map.adhash(), EMPTY);
 }
 
 /// Commutativity: φ({a,b}) = φ({b,a}).
@@ -52,7 +52,7 @@ fn commutativity() {
     assert_eq!(m1.adhash(), m2.adhash());
 }
 
-/// Two seeds prevent degeneration: even when `hash(value) = 0`, adhash
+/// Seeding prevents degeneration: even when `hash(value) = 0`, adhash
 /// is still non-trivial.
 #[test]
 fn two_seed_no_degeneration() {
@@ -62,6 +62,16 @@ fn two_seed_no_degeneration() {
     assert_ne!(contribution, 0);
 }
 
+/// Zero key hashes do not vanish either: distinct values under `key_hash = 0`
+/// contribute distinct, non-zero amounts.
+#[test]
+fn zero_key_hash_no_degeneration() {
+    assert_ne!(entry_adhash(0, 0), 0);
+    assert_ne!(entry_adhash(0, 1), 0);
+    assert_ne!(entry_adhash(0, 0), entry_adhash(0, 1));
+    assert_ne!(entry_adhash(0, 1), entry_adhash(1, 0));
+}
+
 /// Mixing function is not symmetric: f(k, v) ≠ f(v, k) in general.
 #[test]
 fn mixing_not_symmetric() {
@@ -81,3 +91,71 @@ fn overwrite_changes_adhash() {
     let h2 = map.adhash();
     assert_ne!(h1, h2);
 }
+
+#[test]
+fn recompute_matches_maintained() {
+    let mut map: ChampMap<u64, u64> = (0..300).map(|i| (i, i)).collect();
+    assert_eq!(map.recompute_adhash(), map.adhash());
+    for i in (0..300).step_by(3) {
+        map.remove(&i);
+    }
+    assert_eq!(map.recompute_adhash(), map.adhash());
+    assert_eq!(ChampMap::<u64, u64>::new().recompute_adhash(), EMPTY);
+}
+
+/// φ(A ∪ B) = combine(φ(A), φ(B)) for disjoint A, B.
+#[test]
+fn combine_disjoint_parts() {
+    let a: ChampMap<u32, u32> = (0..300).map(|i| (i, i * 7)).collect();
+    let b: ChampMap<u32, u32> = (300..500).map(|i| (i, i * 7)).collect();
+    let union = a.union(&b, |x, _| *x);
+    assert_eq!(combine(a.adhash(), b.adhash()), union.adhash());
+    assert_eq!(combine(EMPTY, a.adhash()), a.adhash());
+}
+
+/// An overlapping key is counted twice until its contribution is removed.
+#[test]
+fn combine_overlap_and_remove_contribution() {
+    let a: ChampMap<u32, u32> = (0..10).map(|i| (i, i)).collect();
+    let b: ChampMap<u32, u32> = (9..20).map(|i| (i, i)).collect();
+    let union = a.union(&b, |x, _| *x);
+    let combined = combine(a.adhash(), b.adhash());
+    assert_ne!(combined, union.adhash());
+    assert_eq!(
+        remove_contribution(combined, hash_one(&9_u32), hash_one(&9_u32)),
+        union.adhash()
+    );
+
+    let mut smaller = a.clone();
+    smaller.remove(&3);
+    assert_eq!(
+        remove_contribution(a.adhash(), hash_one(&3_u32), hash_one(&3_u32)),
+        smaller.adhash()
+    );
+}
+
+/// The multiplicative combiner is commutative and associative, so maps built
+/// in any order still agree, and removal takes contributions back out.
+#[cfg(feature = "strong-hash")]
+#[test]
+fn strong_combiner_is_order_independent() {
+    use crate::adhash::mix_in;
+
+    let (a, b, c) = (entry_adhash(1, 2), entry_adhash(3, 4), entry_adhash(5, 6));
+    assert_eq!(mix_in(a, b), mix_in(b, a));
+    assert_eq!(mix_in(mix_in(a, b), c), mix_in(a, mix_in(b, c)));
+    assert_eq!(mix_in(EMPTY, a), a);
+
+    let forward: ChampMap<u32, u32> = (0..2_000).map(|i| (i, i * 3)).collect();
+    let mut backward = ChampMap::new();
+    for i in (0..2_000).rev() {
+        backward.insert(i, i * 3);
+    }
+    assert_eq!(forward.adhash(), backward.adhash());
+    assert_eq!(forward.recompute_adhash(), forward.adhash());
+
+    for i in 0..2_000 {
+        backward.remove(&i);
+    }
+    assert_eq!(backward.adhash(), EMPTY);
+}
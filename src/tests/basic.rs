@@ -1,11 +1,13 @@
 use crate::ChampMap;
+use crate::adhash::EMPTY;
+use crate::node;
 
 #[test]
 fn empty_map() {
     let map: ChampMap<String, i32> = ChampMap::new();
     assert_eq!(map.len(), 0);
     assert!(map.is_empty());
-    assert_eq!(map.adhash(), 0);
+    assert_eq!(map.adhash(), EMPTY);
 }
 
 #[test]
@@ -15,7 +17,7 @@ fn insert_one() {
     assert_eq!(old, None);
     assert_eq!(map.len(), 1);
     assert!(!map.is_empty());
-    assert_ne!(map.adhash(), 0);
+    assert_ne!(map.adhash(), EMPTY);
 }
 
 #[test]
@@ -67,6 +69,21 @@ fn contains_key_false() {
     assert!(!map.contains_key(&2));
 }
 
+#[test]
+fn contains_all_keys_short_circuits() {
+    let map: ChampMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+    assert!(map.contains_all_keys([3, 50, 99]));
+    assert!(map.contains_all_keys([0, 1, 2].iter()));
+    assert!(map.contains_all_keys(std::iter::empty::<u32>()));
+    assert!(!map.contains_all_keys([3, 100, 50]));
+
+    // Nothing past the first missing key is looked up.
+    let mut probed = Vec::new();
+    let keys = [1, 200, 2].into_iter().inspect(|k| probed.push(*k));
+    assert!(!map.contains_all_keys(keys));
+    assert_eq!(probed, [1, 200]);
+}
+
 #[test]
 fn remove_existing() {
     let mut map = ChampMap::new();
@@ -96,7 +113,7 @@ fn remove_all() {
     assert_eq!(map.remove(&2), Some(20));
     assert_eq!(map.remove(&3), Some(30));
     assert!(map.is_empty());
-    assert_eq!(map.adhash(), 0);
+    assert_eq!(map.adhash(), EMPTY);
 }
 
 #[test]
@@ -120,3 +137,151 @@ fn adhash_changes_on_overwrite() {
     let h2 = map.adhash();
     assert_ne!(h1, h2);
 }
+
+#[test]
+fn clear_empties_map() {
+    let mut map: ChampMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+    map.insert(5_000, 1);
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(map.len(), 0);
+    assert_eq!(map.adhash(), EMPTY);
+    assert_eq!(map.arena_len(), (0, 0, 0));
+    assert_eq!(map.get(&5), None);
+    assert_eq!(map.iter().count(), 0);
+}
+
+#[test]
+fn insert_after_clear_is_canonical() {
+    let mut map: ChampMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+    map.clear();
+    for i in (0..500).rev() {
+        map.insert(i, i * 2);
+    }
+    let expected: ChampMap<u64, u64> = (0..500).map(|i| (i, i * 2)).collect();
+    assert_eq!(map.len(), 500);
+    assert_eq!(map.adhash(), expected.adhash());
+    assert_eq!(map, expected);
+}
+
+#[test]
+fn remove_all_counts_present_keys() {
+    let mut map: ChampMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+    // Every third key, some absent keys, and a repeat.
+    let keys = (0..1_000).step_by(3).chain(5_000..5_100).chain([3, 6]);
+    assert_eq!(map.remove_all(keys), 334);
+    assert_eq!(map.len(), 666);
+
+    let expected: ChampMap<u64, u64> = (0..1_000).filter(|i| i % 3 != 0).map(|i| (i, i)).collect();
+    assert_eq!(map.adhash(), expected.adhash());
+    assert_eq!(map, expected);
+    assert_eq!(map.debug_invariants(), Ok(()));
+}
+
+#[test]
+fn remove_all_down_to_one_and_empty() {
+    let mut map: ChampMap<u64, u64> = (0..500).map(|i| (i, i)).collect();
+    assert_eq!(map.remove_all(1..500), 499);
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.get(&0), Some(&0));
+    assert_eq!(map.debug_invariants(), Ok(()));
+
+    assert_eq!(map.remove_all([7, 0, 0]), 1);
+    assert!(map.is_empty());
+    assert_eq!(map.adhash(), EMPTY);
+    assert_eq!(map.remove_all([1]), 0);
+}
+
+#[test]
+fn remove_all_absent_keys_allocates_nothing() {
+    let mut map: ChampMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+    let before = map.arena_len();
+    assert_eq!(map.remove_all(1_000..2_000), 0);
+    assert_eq!(map.arena_len(), before);
+}
+
+#[test]
+fn extract_if_returns_removed_entries() {
+    let mut map: ChampMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    let mut odd = map.extract_if(|_, v| v % 2 == 1);
+    odd.sort_unstable();
+    assert_eq!(
+        odd,
+        (0..100)
+            .filter(|i| i % 2 == 1)
+            .map(|i| (i, i))
+            .collect::<Vec<_>>()
+    );
+
+    let even: ChampMap<u64, u64> = (0..100).step_by(2).map(|i| (i, i)).collect();
+    assert_eq!(map.len(), 50);
+    assert_eq!(map.adhash(), even.adhash());
+    assert_eq!(map.debug_invariants(), Ok(()));
+
+    assert!(map.extract_if(|_, _| false).is_empty());
+    assert_eq!(map.extract_if(|_, _| true).len(), 50);
+    assert!(map.is_empty());
+    assert_eq!(map.adhash(), EMPTY);
+}
+
+#[test]
+fn split_off_partitions_by_key() {
+    let mut map: ChampMap<u64, u64> = (0..100).map(|i| (i, i * 3)).collect();
+    let high = map.split_off(|k| *k >= 50);
+
+    let expected_low: ChampMap<u64, u64> = (0..50).map(|i| (i, i * 3)).collect();
+    let expected_high: ChampMap<u64, u64> = (50..100).map(|i| (i, i * 3)).collect();
+    assert_eq!(map.len(), 50);
+    assert_eq!(high.len(), 50);
+    assert!(map.keys().all(|k| *k < 50));
+    assert!(high.keys().all(|k| *k >= 50));
+    assert_eq!(map.adhash(), expected_low.adhash());
+    assert_eq!(high.adhash(), expected_high.adhash());
+    assert_eq!(map.debug_invariants(), Ok(()));
+    assert_eq!(high.debug_invariants(), Ok(()));
+
+    let none = map.split_off(|_| false);
+    assert!(none.is_empty());
+    assert_eq!(none.adhash(), EMPTY);
+    assert_eq!(map, expected_low);
+}
+
+#[test]
+fn retain_keys_and_values() {
+    let mut by_key: ChampMap<u64, u64> = (0..200).map(|i| (i, 199 - i)).collect();
+    let mut by_value = by_key.clone();
+    let mut by_both = by_key.clone();
+
+    by_key.retain_keys(|k| k % 3 == 0);
+    by_both.retain(|k, _| k % 3 == 0);
+    let expected: ChampMap<u64, u64> = (0..200).step_by(3).map(|i| (i, 199 - i)).collect();
+    assert_eq!(by_key.adhash(), expected.adhash());
+    assert_eq!(by_key, by_both);
+    assert_eq!(by_key.debug_invariants(), Ok(()));
+
+    by_value.retain_values(|v| *v < 10);
+    let expected: ChampMap<u64, u64> = (190..200).map(|i| (i, 199 - i)).collect();
+    assert_eq!(by_value.len(), 10);
+    assert_eq!(by_value.adhash(), expected.adhash());
+    assert_eq!(by_value.debug_invariants(), Ok(()));
+
+    by_value.retain_values(|_| false);
+    assert!(by_value.is_empty());
+    assert_eq!(by_value.adhash(), EMPTY);
+}
+
+#[test]
+fn retain_shares_untouched_subtrees() {
+    let mut map: ChampMap<u64, u64> = (0..10_000).map(|i| (i, i)).collect();
+    let (root, arena) = (map.snapshot().root, map.arena_len());
+    map.retain(|_, _| true);
+    assert_eq!(map.arena_len(), arena);
+    assert!(map.snapshot().root == root);
+
+    // One removal path-copies one route, not the whole trie.
+    map.retain_keys(|k| *k != 1234);
+    let (nodes, entries, _) = map.arena_len();
+    assert!(nodes - arena.0 <= node::COLLISION_DEPTH);
+    assert!(entries - arena.1 < 200);
+    assert_eq!(map.len(), 9_999);
+}
@@ -0,0 +1,121 @@
+use crate::{ChampMap, ChampMapSync};
+
+#[test]
+fn string_keys_by_str() {
+    let mut map: ChampMap<String, i32> = (0..100).map(|i| (format!("k{i}"), i)).collect();
+    assert_eq!(map.get("k42"), Some(&42));
+    assert!(map.contains_key("k0"));
+    assert!(!map.contains_key("k100"));
+    assert_eq!(map["k7"], 7);
+
+    *map.get_mut("k1").unwrap() += 10;
+    assert_eq!(map.get("k1"), Some(&11));
+
+    assert_eq!(map.remove("k42"), Some(42));
+    assert_eq!(map.remove("k42"), None);
+    assert_eq!(map.len(), 99);
+}
+
+#[test]
+#[should_panic(expected = "key not found")]
+fn index_by_str_missing_panics() {
+    let map: ChampMapSync<String, i32> = std::iter::once(("present".to_string(), 1)).collect();
+    assert_eq!(map["present"], 1);
+    let _ = map["absent"];
+}
+
+#[test]
+fn integer_keys_by_reference() {
+    let mut map: ChampMap<i32, i32> = (0..100).map(|i| (i, -i)).collect();
+    let probe: &i32 = &5;
+    assert_eq!(map.get(probe), Some(&-5));
+    assert!(map.contains_key(probe));
+    assert_eq!(map.remove(probe), Some(-5));
+    assert!(!map.contains_key(probe));
+}
+
+#[test]
+fn borrowed_remove_matches_owned() {
+    let mut by_str: ChampMap<String, i32> = (0..50).map(|i| (i.to_string(), i)).collect();
+    let mut by_owned = by_str.clone();
+    for i in (0..50).step_by(3) {
+        let key = i.to_string();
+        by_str.remove(key.as_str());
+        by_owned.remove(&key);
+    }
+    assert_eq!(by_str, by_owned);
+    assert_eq!(by_str.adhash(), by_owned.adhash());
+}
+
+#[test]
+fn sync_string_keys_by_str() {
+    let mut map: ChampMapSync<String, i32> = (0..100).map(|i| (format!("k{i}"), i)).collect();
+    assert_eq!(map.get("k42"), Some(&42));
+    assert!(map.contains_key("k99"));
+    assert_eq!(map.remove("k99"), Some(99));
+    assert!(!map.contains_key("k99"));
+}
+
+/// Key whose equality and hash ignore the `tag` payload.
+#[derive(Clone, Debug)]
+struct Tagged {
+    id: u32,
+    tag: &'static str,
+}
+
+impl PartialEq for Tagged {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Tagged {}
+
+impl std::hash::Hash for Tagged {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+#[test]
+fn get_key_value_returns_stored_key() {
+    let mut map = ChampMap::new();
+    for id in 0..50 {
+        map.insert(Tagged { id, tag: "stored" }, id * 2);
+    }
+    let probe = Tagged {
+        id: 7,
+        tag: "probe",
+    };
+    let (key, value) = map.get_key_value(&probe).unwrap();
+    assert_eq!(key.tag, "stored");
+    assert_eq!(key.id, 7);
+    assert_eq!(*value, 14);
+    assert!(
+        map.get_key_value(&Tagged {
+            id: 99,
+            tag: "probe"
+        })
+        .is_none()
+    );
+}
+
+#[test]
+fn sync_get_key_value_returns_stored_key() {
+    let mut map = ChampMapSync::new();
+    map.insert(
+        Tagged {
+            id: 1,
+            tag: "stored",
+        },
+        "one",
+    );
+    let (key, value) = map
+        .get_key_value(&Tagged {
+            id: 1,
+            tag: "probe",
+        })
+        .unwrap();
+    assert_eq!(key.tag, "stored");
+    assert_eq!(*value, "one");
+}
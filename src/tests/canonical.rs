@@ -84,6 +84,7 @@ fn delete_preserves_canonicity() {
     map_b.insert(3, 30);
     map_b.insert(1, 10);
 
+    assert!(map_a.verify_adhash());
     assert_eq!(map_a.adhash(), map_b.adhash());
     assert_eq!(map_a.len(), map_b.len());
 }
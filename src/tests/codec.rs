@@ -0,0 +1,81 @@
+use std::io;
+
+use crate::ChampMap;
+
+fn decode_str(bytes: &[u8]) -> io::Result<String> {
+    String::from_utf8(bytes.to_vec()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn decode_u64(bytes: &[u8]) -> io::Result<u64> {
+    let bytes = bytes
+        .try_into()
+        .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn dump(map: &ChampMap<String, u64>) -> Vec<u8> {
+    let mut out = Vec::new();
+    map.write_entries(
+        &mut out,
+        |k, buf| buf.extend_from_slice(k.as_bytes()),
+        |v, buf| buf.extend_from_slice(&v.to_le_bytes()),
+    )
+    .unwrap();
+    out
+}
+
+#[test]
+fn entries_round_trip_with_equal_adhash() {
+    let mut map: ChampMap<String, u64> = (0..3_000).map(|i| (format!("key-{i}"), i)).collect();
+    for i in (0..3_000).step_by(4) {
+        map.remove(&format!("key-{i}"));
+    }
+    map.insert("key-1".to_owned(), 42);
+
+    let bytes = dump(&map);
+    let loaded = ChampMap::read_entries(&mut bytes.as_slice(), decode_str, decode_u64).unwrap();
+    assert_eq!(loaded.adhash(), map.adhash());
+    assert_eq!(loaded, map);
+    loaded.debug_invariants().unwrap();
+
+    // Same contents, different history: identical bytes.
+    let mut rebuilt = ChampMap::new();
+    for (k, v) in map.as_sorted_vec().into_iter().rev() {
+        rebuilt.insert(k.clone(), *v);
+    }
+    assert_ne!(rebuilt.arena_len(), map.arena_len());
+    assert_eq!(dump(&rebuilt), bytes);
+
+    let empty = dump(&ChampMap::new());
+    let loaded: ChampMap<String, u64> =
+        ChampMap::read_entries(&mut empty.as_slice(), decode_str, decode_u64).unwrap();
+    assert!(loaded.is_empty());
+}
+
+#[test]
+fn read_entries_rejects_malformed_input() {
+    let map: ChampMap<String, u64> = (0..10).map(|i| (format!("k{i}"), i)).collect();
+    let bytes = dump(&map);
+    let read = |bytes: &[u8]| {
+        ChampMap::<String, u64>::read_entries(&mut &bytes[..], decode_str, decode_u64)
+            .unwrap_err()
+            .kind()
+    };
+
+    assert_eq!(
+        read(&bytes[..bytes.len() - 1]),
+        io::ErrorKind::UnexpectedEof
+    );
+    assert_eq!(read(b"JSON{}"), io::ErrorKind::InvalidData);
+
+    // Two entries written in descending order are not a canonical dump.
+    let mut swapped = ChampMap::new();
+    swapped.insert("b".to_owned(), 1);
+    let mut bytes = dump(&swapped);
+    bytes[5..13].copy_from_slice(&2u64.to_le_bytes());
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    bytes.push(b'a');
+    bytes.extend_from_slice(&8u32.to_le_bytes());
+    bytes.extend_from_slice(&2u64.to_le_bytes());
+    assert_eq!(read(&bytes), io::ErrorKind::InvalidData);
+}
@@ -1,6 +1,9 @@
 use std::hash::{Hash, Hasher};
 
 use crate::ChampMap;
+use crate::adhash::EMPTY;
+use crate::cursor::NodeKind;
+use crate::node;
 
 /// A key type with a controllable hash value for testing hash collisions.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -74,6 +77,28 @@ fn remove_from_collision() {
     assert_eq!(map.get(&k3), Some(&30));
 }
 
+/// Removing an absent key that shares the hash of a two-entry collision
+/// node must not take the promotion path: nothing changes.
+#[test]
+fn remove_missing_key_from_two_entry_collision() {
+    let k1 = CollidingKey::new(1, 0xBBBB);
+    let k2 = CollidingKey::new(2, 0xBBBB);
+    let absent = CollidingKey::new(3, 0xBBBB);
+
+    let mut map = ChampMap::new();
+    map.insert(k1.clone(), 10);
+    map.insert(k2.clone(), 20);
+    let before = map.clone();
+
+    assert_eq!(map.remove(&absent), None);
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.adhash(), before.adhash());
+    assert_eq!(map, before);
+    assert_eq!(map.get(&k1), Some(&10));
+    assert_eq!(map.get(&k2), Some(&20));
+    assert_eq!(map.debug_invariants(), Ok(()));
+}
+
 /// Overwrite in collision node.
 #[test]
 fn overwrite_in_collision() {
@@ -102,7 +127,7 @@ fn collision_remove_all() {
     map.remove(&k1);
     map.remove(&k2);
     assert!(map.is_empty());
-    assert_eq!(map.adhash(), 0);
+    assert_eq!(map.adhash(), EMPTY);
 }
 
 /// Mixed: some keys collide, some don't.
@@ -122,3 +147,305 @@ fn mixed_collisions_and_normal() {
     assert_eq!(map.get(&collide_b), Some(&"b"));
     assert_eq!(map.get(&normal), Some(&"c"));
 }
+
+/// Equality holds for colliding keys regardless of insertion order, even
+/// though collision-node entry order follows insertion order.
+#[test]
+fn eq_across_insertion_orders() {
+    let keys: Vec<CollidingKey> = (0..4).map(|i| CollidingKey::new(i, 0xF00D)).collect();
+
+    let mut forward = ChampMap::new();
+    for (i, k) in keys.iter().enumerate() {
+        forward.insert(k.clone(), i);
+    }
+    let mut backward = ChampMap::new();
+    for (i, k) in keys.iter().enumerate().rev() {
+        backward.insert(k.clone(), i);
+    }
+
+    assert_eq!(forward, backward);
+    backward.insert(keys[0].clone(), 99);
+    assert_ne!(forward, backward);
+}
+
+/// Value transforms rebuild collision nodes with a correct `AdHash`.
+#[test]
+fn map_values_in_place_over_collisions() {
+    let mut map = ChampMap::new();
+    let mut expected = ChampMap::new();
+    for id in 0..5 {
+        map.insert(CollidingKey::new(id, 0x00C0_FFEE), id);
+        expected.insert(CollidingKey::new(id, 0x00C0_FFEE), id + 100);
+    }
+    map.map_values_in_place(|_, v| *v += 100);
+    assert_eq!(map.adhash(), expected.adhash());
+    assert_eq!(map, expected);
+}
+
+/// A transient build produces the same collision nodes as COW inserts.
+#[test]
+fn transient_build_with_collisions() {
+    let keys: Vec<CollidingKey> = (0..6)
+        .map(|id| CollidingKey::new(id, if id % 2 == 0 { 0xAB } else { 0xCD }))
+        .collect();
+    let frozen: ChampMap<CollidingKey, u32> = keys.iter().cloned().zip(0..).collect();
+
+    let mut cow = ChampMap::new();
+    for (k, v) in keys.iter().cloned().zip(0..) {
+        cow.insert(k, v);
+    }
+    assert_eq!(frozen.adhash(), cow.adhash());
+    assert_eq!(frozen, cow);
+    for (i, k) in keys.iter().enumerate() {
+        assert_eq!(frozen.get(k), Some(&u32::try_from(i).unwrap()));
+    }
+}
+
+/// `get_or_insert_with` returns the newly appended collision entry.
+#[test]
+fn get_or_insert_with_into_collision() {
+    let mut map = ChampMap::new();
+    map.insert(CollidingKey::new(1, 0xFACE), 1);
+    assert_eq!(
+        *map.get_or_insert_with(CollidingKey::new(2, 0xFACE), || 2),
+        2
+    );
+    assert_eq!(
+        *map.get_or_insert_with(CollidingKey::new(3, 0xFACE), || 3),
+        3
+    );
+    assert_eq!(
+        *map.get_or_insert_with(CollidingKey::new(1, 0xFACE), || 9),
+        1
+    );
+    assert_eq!(map.len(), 3);
+}
+
+#[test]
+fn subset_over_collisions() {
+    let keys: Vec<CollidingKey> = (0..4).map(|i| CollidingKey::new(i, 0xBEEF)).collect();
+    let small: ChampMap<CollidingKey, u32> = keys[..2].iter().cloned().zip(0..).collect();
+    let large: ChampMap<CollidingKey, u32> = keys.iter().cloned().zip(0..).collect();
+    assert!(small.is_subset(&large));
+    assert!(!large.is_subset(&small));
+
+    let mut changed = large;
+    changed.insert(keys[1].clone(), 99);
+    assert!(!small.is_subset(&changed));
+}
+
+/// A collision node at maximum depth, surrounded by ordinary keys, reduced
+/// to one entry: the survivor is inlined back up and the trie matches one
+/// where the collision never happened.
+#[test]
+fn remove_to_single_collision_entry_is_canonical() {
+    let deep_a = CollidingKey::new(1, 0x5EED);
+    let deep_b = CollidingKey::new(2, 0x5EED);
+    let others = (10..200).map(|id| CollidingKey::new(id, u64::from(id) * 0x9E37));
+
+    let mut map: ChampMap<CollidingKey, u32> = others.clone().zip(0..).collect();
+    map.insert(deep_a.clone(), 1);
+    map.insert(deep_b.clone(), 2);
+    assert_eq!(map.debug_invariants(), Ok(()));
+
+    assert_eq!(map.remove(&deep_b), Some(2));
+    assert_eq!(map.debug_invariants(), Ok(()));
+
+    let mut never_collided: ChampMap<CollidingKey, u32> = others.zip(0..).collect();
+    never_collided.insert(deep_a.clone(), 1);
+    assert_eq!(map.adhash(), never_collided.adhash());
+    assert_eq!(map, never_collided);
+    assert_eq!(map.get(&deep_a), Some(&1));
+
+    // The same reduction through a set difference.
+    let mut with_both = never_collided.clone();
+    with_both.insert(deep_b.clone(), 2);
+    let only_b: ChampMap<CollidingKey, u32> = std::iter::once((deep_b, 2)).collect();
+    let diff = with_both.difference(&only_b);
+    assert_eq!(diff.debug_invariants(), Ok(()));
+    assert_eq!(diff.adhash(), never_collided.adhash());
+}
+
+/// More keys on one full hash than the old 255-entry limit: insert, look up,
+/// overwrite and remove all of them without panicking.
+#[test]
+fn collision_beyond_255_entries() {
+    let keys: Vec<CollidingKey> = (0..300).map(|i| CollidingKey::new(i, 0x0BAD)).collect();
+
+    let mut map = ChampMap::new();
+    for (i, k) in keys.iter().enumerate() {
+        map.insert(k.clone(), i);
+    }
+    assert_eq!(map.len(), 300);
+    assert_eq!(map.debug_invariants(), Ok(()));
+    for (i, k) in keys.iter().enumerate() {
+        assert_eq!(map.get(k), Some(&i));
+    }
+
+    assert_eq!(map.insert(keys[299].clone(), 0), Some(299));
+    let built: ChampMap<CollidingKey, usize> = keys.iter().cloned().zip(0..).collect();
+    assert_eq!(built.len(), 300);
+
+    for (i, k) in keys.iter().enumerate().skip(1) {
+        assert!(map.remove(k).is_some(), "failed to remove key {i}");
+    }
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.get(&keys[0]), Some(&0));
+    assert_eq!(map.debug_invariants(), Ok(()));
+}
+
+#[test]
+fn remove_all_over_collisions() {
+    let keys: Vec<CollidingKey> = (0..5).map(|i| CollidingKey::new(i, 0x00AB_CDEF)).collect();
+    let mut map: ChampMap<CollidingKey, u32> = keys.iter().cloned().zip(0..).collect();
+    map.insert(CollidingKey::new(9, 0x1234), 9);
+
+    assert_eq!(map.remove_all(keys[1..4].iter().cloned()), 3);
+    let expected: ChampMap<CollidingKey, u32> = [(keys[0].clone(), 0), (keys[4].clone(), 4)]
+        .into_iter()
+        .chain([(CollidingKey::new(9, 0x1234), 9)])
+        .collect();
+    assert_eq!(map, expected);
+    assert_eq!(map.debug_invariants(), Ok(()));
+
+    assert_eq!(map.remove_all([keys[0].clone()]), 1);
+    assert_eq!(map.debug_invariants(), Ok(()));
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn cursor_reaches_collision_node() {
+    use crate::cursor::{Cursor, NodeKind};
+
+    fn find_collision(
+        cursor: Cursor<'_, CollidingKey, u32>,
+    ) -> Option<Cursor<'_, CollidingKey, u32>> {
+        if cursor.kind() == NodeKind::Collision {
+            return Some(cursor);
+        }
+        cursor.children().find_map(find_collision)
+    }
+
+    let map: ChampMap<CollidingKey, u32> =
+        (0..3).map(|i| (CollidingKey::new(i, 0x7777), i)).collect();
+    let collision = find_collision(map.cursor().expect("non-empty")).expect("collision node");
+    assert_eq!(collision.entries().len(), 3);
+    assert_eq!(collision.children().len(), 0);
+    assert_eq!(collision.depth(), node::COLLISION_DEPTH);
+}
+
+#[test]
+fn structure_stats_counts_collision_nodes() {
+    let map: ChampMap<CollidingKey, u32> = (0..3)
+        .map(|i| (CollidingKey::new(i, 0x7777), i))
+        .chain((0..2).map(|i| (CollidingKey::new(10 + i, 0x8888), i)))
+        .collect();
+    let stats = map.structure_stats();
+    assert_eq!(stats.collision_nodes, 2);
+    let mut sizes = stats.collision_sizes.clone();
+    sizes.sort_unstable();
+    assert_eq!(sizes, [2, 3]);
+    assert_eq!(stats.max_depth, node::COLLISION_DEPTH);
+    assert_eq!(stats.entries, 5);
+}
+
+#[test]
+fn symmetric_difference_over_collisions() {
+    let keys: Vec<CollidingKey> = (0..5).map(|i| CollidingKey::new(i, 0x0DD5)).collect();
+    let a: ChampMap<CollidingKey, u32> = keys[..3].iter().cloned().zip(0..).collect();
+    let b: ChampMap<CollidingKey, u32> = keys[2..].iter().cloned().zip(10..).collect();
+
+    let sym = a.symmetric_difference(&b);
+    let expected: ChampMap<CollidingKey, u32> = [
+        (keys[0].clone(), 0),
+        (keys[1].clone(), 1),
+        (keys[3].clone(), 11),
+        (keys[4].clone(), 12),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(sym, expected);
+    assert_eq!(sym.debug_invariants(), Ok(()));
+
+    let pair: ChampMap<CollidingKey, u32> = keys[..2].iter().cloned().zip(0..).collect();
+    let single = a.symmetric_difference(&pair);
+    assert_eq!(single.len(), 1);
+    assert_eq!(single.debug_invariants(), Ok(()));
+}
+
+/// After a random workload over colliding and ordinary keys, rebuilding the
+/// trie changes neither its `AdHash` nor its shape.
+#[test]
+fn canonicalize_is_a_no_op_on_a_canonical_map() {
+    let mut map = ChampMap::new();
+    let mut state = 0x9E37_79B9_7F4A_7C15_u64;
+    for _ in 0..4_000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let id = u32::try_from(state % 300).unwrap();
+        // A third of the keys share one of four hashes.
+        let hash = if id % 3 == 0 {
+            u64::from(id % 4)
+        } else {
+            u64::from(id)
+        };
+        let key = CollidingKey::new(id, hash);
+        if state & (1 << 40) == 0 {
+            map.remove(&key);
+        } else {
+            map.insert(key, state >> 48);
+        }
+    }
+    assert!(map.structure_stats().collision_nodes > 0);
+
+    let before = map.clone();
+    let shape = map.structure_stats();
+    map.canonicalize();
+    assert_eq!(map.debug_invariants(), Ok(()));
+    assert_eq!(map.adhash(), before.adhash());
+    assert_eq!(map.structure_stats(), shape);
+    assert_eq!(map, before);
+    assert!(map.iter().eq(before.iter()));
+}
+
+#[test]
+fn from_unique_unchecked_builds_collision_nodes() {
+    let pairs = || (0..64).map(|id| (CollidingKey::new(id, u64::from(id % 8)), id));
+    let bulk = ChampMap::from_unique_unchecked(pairs());
+    let collected: ChampMap<_, _> = pairs().collect();
+
+    assert_eq!(bulk.debug_invariants(), Ok(()));
+    assert_eq!(bulk.structure_stats().collision_nodes, 8);
+    assert_eq!(bulk.adhash(), collected.adhash());
+    assert_eq!(bulk, collected);
+}
+
+#[test]
+fn colliding_keys_share_key_path() {
+    let mut map = ChampMap::new();
+    for id in 0..3 {
+        map.insert(CollidingKey::new(id, 0xFACE), id);
+    }
+    map.insert(CollidingKey::new(9, 0xFACF), 9);
+
+    let path = map
+        .key_path(&CollidingKey::new(0, 0xFACE))
+        .expect("present");
+    assert_eq!(path.terminal, NodeKind::Collision);
+    assert_eq!(path.fragments.len(), node::COLLISION_DEPTH);
+    for id in 1..3 {
+        assert_eq!(
+            map.key_path(&CollidingKey::new(id, 0xFACE)),
+            Some(path.clone())
+        );
+    }
+    assert_eq!(map.key_path(&CollidingKey::new(3, 0xFACE)), None);
+
+    let neighbour = map
+        .key_path(&CollidingKey::new(9, 0xFACF))
+        .expect("present");
+    assert_eq!(neighbour.terminal, NodeKind::Inner);
+    assert!(neighbour.fragments.len() < path.fragments.len());
+}
@@ -0,0 +1,241 @@
+use crate::{ChampMap, ChampMapSync};
+
+fn churned() -> ChampMap<u64, u64> {
+    let mut map = ChampMap::new();
+    for i in 0..5_000 {
+        map.insert(i, i);
+    }
+    for i in (0..5_000).step_by(2) {
+        map.remove(&i);
+    }
+    for i in (1..5_000).step_by(4) {
+        map.insert(i, i * 10);
+    }
+    map
+}
+
+#[test]
+fn compact_preserves_contents() {
+    let mut map = churned();
+    let before = map.clone();
+    let stats = map.compact();
+
+    assert_eq!(map.len(), before.len());
+    assert_eq!(map.adhash(), before.adhash());
+    assert_eq!(map, before);
+    for i in 0..5_000 {
+        assert_eq!(map.get(&i), before.get(&i));
+    }
+    assert_eq!(stats.before, before.arena_len());
+    assert_eq!(stats.after, map.arena_len());
+}
+
+#[test]
+fn compact_shrinks_to_live_set() {
+    let mut map = churned();
+    let stats = map.compact();
+    assert!(stats.reclaimed() > 0);
+    assert_eq!(map.arena_len().1, map.len());
+
+    let fresh: ChampMap<u64, u64> = map.iter().map(|(k, v)| (*k, *v)).collect();
+    assert_eq!(map.arena_len(), fresh.arena_len());
+
+    // Already compact: nothing left to reclaim.
+    let again = map.compact();
+    assert_eq!(again.reclaimed(), 0);
+    assert_eq!(again.before, again.after);
+}
+
+#[test]
+fn compact_then_mutate() {
+    let mut map = churned();
+    map.compact();
+    let cp = map.checkpoint();
+    map.insert(100_000, 1);
+    map.remove(&1);
+    map.rollback(cp);
+    let mut expected = churned();
+    expected.compact();
+    assert_eq!(map, expected);
+}
+
+#[test]
+fn compact_empty_map() {
+    let mut map: ChampMap<u64, u64> = (0..10).map(|i| (i, i)).collect();
+    for i in 0..10 {
+        map.remove(&i);
+    }
+    let stats = map.compact();
+    assert!(map.is_empty());
+    assert_eq!(stats.after, (0, 0, 0));
+}
+
+#[test]
+fn sync_compact() {
+    let mut map = ChampMapSync::new();
+    for i in 0_u64..1_000 {
+        map.insert(i, i);
+        map.insert(i, i + 1);
+    }
+    let before = map.adhash();
+    map.compact();
+    assert_eq!(map.adhash(), before);
+    assert_eq!(map.arena_len().1, 1_000);
+    assert_eq!(map.get(&999), Some(&1_000));
+}
+
+#[test]
+fn shrink_to_fit_releases_capacity() {
+    let mut map: ChampMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    let cp = map.checkpoint();
+    for i in 100..5_000 {
+        map.insert(i, i);
+    }
+    map.rollback(cp);
+
+    let (len, grown) = (map.arena_len(), map.arena_capacity());
+    assert!(grown.0 >= len.0 && grown.1 > len.1 && grown.2 >= len.2);
+
+    map.shrink_to_fit();
+    let shrunk = map.arena_capacity();
+    assert!(shrunk.1 < grown.1);
+    assert!(shrunk.0 >= len.0 && shrunk.1 >= len.1 && shrunk.2 >= len.2);
+    assert_eq!(map.arena_len(), len);
+
+    // Contents and checkpoints survive.
+    let cp = map.checkpoint();
+    map.insert(100_000, 1);
+    map.rollback(cp);
+    assert_eq!(map.len(), 100);
+    assert!(map.verify_adhash());
+}
+
+/// Freezing reserves the exact live set, so collecting 10k pairs grows each
+/// arena once and leaves no doubling slack.
+#[test]
+fn from_iter_sizes_arenas_once() {
+    let map: ChampMap<u64, u64> = (0..10_000).map(|i| (i, i)).collect();
+    let (len, cap) = (map.arena_len(), map.arena_capacity());
+    assert_eq!(len.1, 10_000);
+    assert!(cap.0 >= len.0 && cap.1 >= len.1 && cap.2 >= len.2);
+    assert!(cap.0 - len.0 < len.0 / 4);
+    assert!(cap.1 - len.1 < len.1 / 4);
+    assert!(cap.2 - len.2 < len.2 / 4);
+
+    let mut extended = ChampMap::new();
+    extended.extend((0..10_000_u64).map(|i| (i, i)));
+    assert!(extended.arena_capacity().1 >= 10_000);
+    assert_eq!(extended.adhash(), map.adhash());
+}
+
+/// Counts how often any arena's capacity changes while inserting `0..n`.
+fn growth_events(mut map: ChampMap<u64, u64>, n: u64) -> usize {
+    let mut events = 0;
+    let mut cap = map.arena_capacity();
+    for i in 0..n {
+        map.insert(i, i);
+        let now = map.arena_capacity();
+        if now != cap {
+            events += 1;
+            cap = now;
+        }
+    }
+    events
+}
+
+#[test]
+fn with_capacity_grows_less_often() {
+    let presized = ChampMap::with_capacity(5_000);
+    assert!(presized.arena_capacity().1 >= 5_000);
+    assert!(presized.is_empty());
+    assert!(growth_events(presized, 5_000) < growth_events(ChampMap::new(), 5_000));
+}
+
+#[test]
+fn clone_from_reuses_arena_capacity() {
+    let mut target: ChampMap<u64, u64> = (0..5_000).map(|i| (i, i)).collect();
+    let capacity = target.arena_capacity();
+
+    let mut source: ChampMap<u64, u64> = (0..3_000).map(|i| (i, i * 2)).collect();
+    for i in 0..1_000 {
+        source.insert(i, i * 3);
+    }
+    target.clone_from(&source);
+
+    assert_eq!(target, source);
+    assert_eq!(target.adhash(), source.adhash());
+    assert_eq!(target.debug_invariants(), Ok(()));
+    // Live structure only, written into the arenas already allocated.
+    assert!(target.arena_len().0 < source.arena_len().0);
+    assert_eq!(target.arena_capacity(), capacity);
+
+    target.insert(9_999, 0);
+    assert!(!source.contains_key(&9_999));
+}
+
+/// Overwriting one key allocates exactly one node and one entry per item,
+/// so a hinted `extend` fits in the room reserved up front, while an
+/// unhinted one doubles its way past it.
+#[test]
+fn extend_reserves_size_hint() {
+    let items = || std::iter::repeat_n((1_u64, 1_u64), 10_000);
+    let mut hinted = ChampMap::new();
+    hinted.extend(items());
+    let mut unhinted = ChampMap::new();
+    unhinted.extend(items().filter(|_| true));
+
+    assert_eq!(hinted.arena_len(), (10_000, 10_000, 0));
+    assert_eq!(hinted.arena_len(), unhinted.arena_len());
+    assert_eq!(hinted.arena_capacity().0, 10_000);
+    assert!(unhinted.arena_capacity().0 > 10_000);
+    assert_eq!(hinted, unhinted);
+
+    let mut reserved: ChampMap<u64, u64> = ChampMap::new();
+    reserved.reserve(500);
+    let capacity = reserved.arena_capacity();
+    assert!(capacity.0 >= 500 && capacity.1 >= 500 && capacity.2 >= 500);
+    reserved.extend((0..500).map(|i| (i, i)).filter(|_| true));
+    assert_eq!(reserved.len(), 500);
+}
+
+/// Extending by one pair at a time — what `Extend::extend_one` does by
+/// default — leaves the same map as inserting each pair.
+#[test]
+fn single_item_extend_matches_insert() {
+    let mut extended = ChampMap::new();
+    let mut inserted = ChampMap::new();
+    for i in 0..2_000_u64 {
+        let pair = (i % 700, i);
+        extended.extend(std::iter::once(pair));
+        inserted.insert(pair.0, pair.1);
+        assert_eq!(extended.len(), inserted.len());
+        assert_eq!(extended.adhash(), inserted.adhash());
+    }
+    assert_eq!(extended, inserted);
+    assert_eq!(extended.debug_invariants(), Ok(()));
+}
+
+#[test]
+fn memory_bytes_counts_dead_copies_and_scales() {
+    let mut map: ChampMap<u64, u64> = (0..10_000).map(|i| (i, i)).collect();
+    assert_eq!(map.memory_bytes(), map.live_memory_bytes());
+    for i in 0..1_000 {
+        map.insert(i, i + 1);
+    }
+    let (dead, live) = (map.memory_bytes(), map.live_memory_bytes());
+    assert!(dead > live);
+    map.compact();
+    assert_eq!(map.memory_bytes(), live);
+    assert_eq!(map.live_memory_bytes(), live);
+
+    // At least one entry's worth per entry, and roughly linear in n.
+    let small: ChampMap<u64, u64> = (0..10_000).map(|i| (i, i)).collect();
+    let large: ChampMap<u64, u64> = (0..100_000).map(|i| (i, i)).collect();
+    assert!(small.live_memory_bytes() >= 10_000 * size_of::<(u64, u64, u64)>());
+    let (small, large) = (small.live_memory_bytes(), large.live_memory_bytes());
+    assert!(
+        large > small * 8 && large < small * 12,
+        "{small} -> {large}"
+    );
+    assert_eq!(ChampMap::<u64, u64>::new().live_memory_bytes(), 0);
+}
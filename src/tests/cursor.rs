@@ -0,0 +1,258 @@
+use std::collections::HashSet;
+
+use crate::adhash::{self, hash_one};
+use crate::cursor::{Cursor, NodeKind};
+use crate::node;
+use crate::store::ChampStore;
+use crate::{ChampMap, ChampMapSync};
+
+fn max_depth<K, V>(cursor: Cursor<'_, K, V>) -> usize {
+    let own = cursor.depth();
+    cursor.children().map(max_depth).max().unwrap_or(own)
+}
+
+fn count_entries<K, V, S: ChampStore<K, V>>(cursor: Cursor<'_, K, V, S>) -> usize {
+    cursor.entries().len() + cursor.children().map(count_entries).sum::<usize>()
+}
+
+#[test]
+fn max_depth_of_10k_map() {
+    let map: ChampMap<u64, u64> = (0..10_000).map(|i| (i, i)).collect();
+    let root = map.cursor().expect("non-empty");
+    assert_eq!(root.depth(), 0);
+    assert_eq!(root.kind(), NodeKind::Inner);
+    assert_eq!(root.adhash(), map.adhash());
+
+    let depth = max_depth(root);
+    assert!(depth >= 2, "10k entries cannot fit in two levels");
+    assert!(
+        depth <= node::COLLISION_DEPTH,
+        "depth {depth} exceeds the 64-bit hash bound"
+    );
+    assert_eq!(count_entries(root), 10_000);
+}
+
+#[test]
+fn children_report_depth_and_entries() {
+    let map: ChampMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+    let root = map.cursor().expect("non-empty");
+    for child in root.children() {
+        assert_eq!(child.depth(), 1);
+        // Non-root subtrees hold at least two entries.
+        assert!(count_entries(child) >= 2);
+        for e in child.entries() {
+            assert_eq!(map.get(&e.key), Some(&e.value));
+        }
+    }
+}
+
+#[test]
+fn empty_map_has_no_cursor() {
+    assert!(ChampMap::<u64, u64>::new().cursor().is_none());
+    assert!(ChampMapSync::<u64, u64>::new().cursor().is_none());
+}
+
+#[test]
+fn sync_cursor_counts_entries() {
+    let map: ChampMapSync<u64, u64> = (0..2_000).map(|i| (i, i)).collect();
+    assert_eq!(count_entries(map.cursor().expect("non-empty")), 2_000);
+}
+
+#[test]
+fn structure_stats_of_10k_map() {
+    let map: ChampMap<u64, u64> = (0..10_000).map(|i| (i, i)).collect();
+    let stats = map.structure_stats();
+    assert!(stats.max_depth <= node::COLLISION_DEPTH);
+    assert_eq!(stats.max_depth, max_depth(map.cursor().expect("non-empty")));
+    assert_eq!(stats.collision_nodes, 0);
+    assert!(stats.collision_sizes.is_empty());
+    assert_eq!(stats.entries, map.len());
+    assert_eq!(stats.entries_by_depth.iter().sum::<usize>(), map.len());
+    assert_eq!(
+        stats.entries_per_node.iter().sum::<usize>(),
+        stats.inner_nodes
+    );
+    assert_eq!(stats.fan_out.iter().sum::<usize>(), stats.inner_nodes);
+    // Every node but the root is some node's child.
+    let children: usize = stats.fan_out.iter().enumerate().map(|(n, c)| n * c).sum();
+    assert_eq!(children + 1, stats.nodes());
+    assert!(stats.avg_depth() > 1.0 && stats.avg_depth() < 13.0);
+}
+
+#[test]
+fn structure_stats_of_empty_map() {
+    let stats = ChampMapSync::<u64, u64>::new().structure_stats();
+    assert_eq!(stats.nodes(), 0);
+    assert_eq!(stats.entries, 0);
+    assert!(stats.avg_depth().abs() < f64::EPSILON);
+}
+
+#[test]
+fn partition_by_top_fragment_covers_every_entry() {
+    let map: ChampMap<u32, u32> = (0..5000).map(|i| (i, i)).collect();
+    let groups = map.partition_by_top_fragment();
+    assert_eq!(groups.iter().map(Vec::len).sum::<usize>(), map.len());
+    for (frag, group) in groups.iter().enumerate() {
+        for (k, v) in group {
+            assert_eq!(node::fragment(hash_one(*k), 0) as usize, frag);
+            assert_eq!(map.get(*k), Some(*v));
+        }
+    }
+
+    let sync: ChampMapSync<u32, u32> = std::iter::once((7, 7)).collect();
+    let groups = sync.partition_by_top_fragment();
+    assert_eq!(groups.iter().filter(|g| !g.is_empty()).count(), 1);
+    assert!(
+        ChampMap::<u32, u32>::new()
+            .partition_by_top_fragment()
+            .iter()
+            .all(Vec::is_empty)
+    );
+}
+
+/// Every inline entry at depth `d` is reached through `d + 1` inner nodes,
+/// taking its own hash fragments.
+#[test]
+fn key_path_matches_entry_depths() {
+    let map: ChampMap<u32, u32> = (0..5000).map(|i| (i, i)).collect();
+    let mut by_depth = Vec::new();
+    for k in 0..5000 {
+        let path = map.key_path(&k).expect("present");
+        assert_eq!(path.terminal, NodeKind::Inner);
+        for (level, frag) in path.fragments.iter().enumerate() {
+            let shift = u32::try_from(level).unwrap() * node::BITS_PER_LEVEL;
+            assert_eq!(*frag, node::fragment(hash_one(&k), shift));
+        }
+        let depth = path.fragments.len() - 1;
+        by_depth.resize(by_depth.len().max(depth + 1), 0);
+        by_depth[depth] += 1;
+    }
+    assert_eq!(by_depth, map.structure_stats().entries_by_depth);
+
+    assert_eq!(map.key_path(&5000), None);
+    assert_eq!(ChampMap::<u32, u32>::new().key_path(&0), None);
+}
+
+#[test]
+fn fold_subtrees_sums_values_and_rebuilds_adhash() {
+    let map: ChampMap<u64, u64> = (0..10_000).map(|i| (i, i * 3)).collect();
+    let total = map.fold_subtrees(|_, v| *v, |parts| parts.into_iter().sum());
+    assert_eq!(total, Some(map.values().sum()));
+
+    let adhash = map.fold_subtrees(
+        |k, v| adhash::entry_adhash(hash_one(k), hash_one(v)),
+        |parts| parts.into_iter().fold(adhash::EMPTY, adhash::combine),
+    );
+    assert_eq!(adhash, Some(map.adhash()));
+
+    let nodes = map.fold_subtrees(|_, _| 0, |parts| 1 + parts.into_iter().sum::<usize>());
+    let stats = map.structure_stats();
+    assert_eq!(nodes, Some(stats.inner_nodes + stats.collision_nodes));
+
+    assert_eq!(
+        ChampMap::<u64, u64>::new().fold_subtrees(|_, v| *v, |p| p.len() as u64),
+        None
+    );
+}
+
+#[test]
+fn prefix_iterations_partition_the_map() {
+    let map: ChampMap<u64, u64> = (0..3_000).map(|i| (i, i)).collect();
+    let all: HashSet<u64> = map.keys().copied().collect();
+
+    let branching = 1u32 << node::BITS_PER_LEVEL;
+    let mut seen = HashSet::new();
+    for frag in 0..branching {
+        for (k, _) in map.iter_under_prefix(&[frag]) {
+            assert_eq!(node::fragment(hash_one(k), 0), frag);
+            assert!(seen.insert(*k), "key {k} under two prefixes");
+        }
+        let two_level: usize = (0..branching)
+            .map(|next| map.iter_under_prefix(&[frag, next]).count())
+            .sum();
+        assert_eq!(two_level, map.iter_under_prefix(&[frag]).count());
+    }
+    assert_eq!(seen, all);
+    assert_eq!(map.iter_under_prefix(&[]).count(), map.len());
+
+    // A full path to one key's hash yields exactly that key.
+    let hash = hash_one(&7u64);
+    let path: Vec<u32> = (0..=node::MAX_SHIFT)
+        .step_by(node::BITS_PER_LEVEL as usize)
+        .map(|shift| node::fragment(hash, shift))
+        .collect();
+    let found: Vec<_> = map.iter_under_prefix(&path).collect();
+    assert_eq!(found, [(&7, &7)]);
+
+    assert_eq!(map.iter_under_prefix(&[branching]).count(), 0);
+    let mut too_long = path;
+    too_long.push(0);
+    assert_eq!(map.iter_under_prefix(&too_long).count(), 0);
+    assert_eq!(
+        ChampMap::<u64, u64>::new().iter_under_prefix(&[0]).count(),
+        0
+    );
+}
+
+#[test]
+fn write_dot_renders_every_node_and_key() {
+    let map: ChampMap<String, u32> = (0..200).map(|i| (format!("k{i}"), i)).collect();
+    let mut out = Vec::new();
+    map.write_dot(&mut out).unwrap();
+    let dot = String::from_utf8(out).unwrap();
+
+    assert!(dot.starts_with("digraph champ {"));
+    assert!(dot.trim_end().ends_with('}'));
+    let stats = map.structure_stats();
+    let nodes = dot.matches("[shape=ellipse").count() + dot.matches("[shape=octagon").count();
+    assert_eq!(nodes, stats.inner_nodes + stats.collision_nodes);
+    assert_eq!(dot.matches("[shape=box").count(), 200);
+    // One edge per non-root node and per entry.
+    assert_eq!(dot.matches(" -> ").count(), nodes - 1 + 200);
+    for i in 0..200 {
+        assert!(dot.contains(&format!(r#"label="\"k{i}\""]"#)));
+    }
+
+    let mut empty = Vec::new();
+    ChampMap::<String, u32>::new()
+        .write_dot(&mut empty)
+        .unwrap();
+    assert_eq!(String::from_utf8(empty).unwrap().lines().count(), 3);
+}
+
+/// With 4-bit levels, nodes hold at most 16 slots and the trie grows deeper,
+/// but lookups, inserts and removals behave exactly as with 32-way nodes.
+#[cfg(feature = "branch-16")]
+#[test]
+fn branch_16_get_insert_remove() {
+    assert_eq!(node::BRANCHING, 16);
+    assert_eq!(node::fragment(u64::MAX, 0), 15);
+    assert_eq!(node::COLLISION_DEPTH, 16);
+
+    let mut map: ChampMap<u64, u64> = ChampMap::new();
+    for i in 0..20_000 {
+        assert_eq!(map.insert(i, i * 2), None);
+    }
+    assert_eq!(map.insert(7, 0), Some(14));
+    for i in (0..20_000).step_by(2) {
+        assert!(map.remove(&i).is_some());
+    }
+    assert_eq!(map.len(), 10_000);
+    for i in 0..20_000 {
+        let expected = (i % 2 == 1).then_some(if i == 7 { 0 } else { i * 2 });
+        assert_eq!(map.get(&i).copied(), expected);
+    }
+
+    let stats = map.structure_stats();
+    assert!(stats.fan_out.len() <= node::BRANCHING + 1);
+    assert!(stats.max_depth >= 3, "10k entries need three 16-way levels");
+    assert!(
+        map.partition_by_top_fragment()
+            .iter()
+            .all(|g| !g.is_empty())
+    );
+    assert_eq!(map.debug_invariants(), Ok(()));
+
+    let rebuilt: ChampMap<u64, u64> = map.iter().map(|(k, v)| (*k, *v)).collect();
+    assert_eq!(rebuilt.adhash(), map.adhash());
+}
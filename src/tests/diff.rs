@@ -0,0 +1,93 @@
+use std::collections::BTreeSet;
+
+use crate::diff::Change;
+use crate::{ChampMap, ChampMapSync};
+
+fn sorted<'a>(keys: impl Iterator<Item = &'a u64>) -> Vec<u64> {
+    keys.copied().collect::<BTreeSet<_>>().into_iter().collect()
+}
+
+#[test]
+fn diff_classifies_changes() {
+    let mut map: ChampMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+    let cp = map.checkpoint();
+
+    map.insert(1_000, 0);
+    map.insert(2_000, 0);
+    map.insert(5, 50);
+    map.insert(6, 6); // same value: not a change
+    map.remove(&7);
+    map.remove(&900);
+
+    let changes = map.diff_since(&cp);
+    assert_eq!(changes.len(), 5);
+    assert_eq!(sorted(changes.added()), [1_000, 2_000]);
+    assert_eq!(sorted(changes.removed()), [7, 900]);
+    assert_eq!(sorted(changes.updated()), [5]);
+}
+
+#[test]
+fn diff_without_changes_is_empty() {
+    let mut map: ChampMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    let cp = map.checkpoint();
+    assert!(map.diff_since(&cp).is_empty());
+
+    // Remove and re-insert: a different path, but equal contents.
+    map.remove(&42);
+    map.insert(42, 42);
+    assert!(map.diff_since(&cp).is_empty());
+}
+
+#[test]
+fn diff_from_and_to_empty() {
+    let mut map = ChampMap::new();
+    let empty_cp = map.checkpoint();
+    map.insert(1_u64, 1_u64);
+    map.insert(2, 2);
+    let changes = map.diff_since(&empty_cp);
+    assert_eq!(sorted(changes.added()), [1, 2]);
+
+    let full_cp = map.checkpoint();
+    map.remove(&1);
+    map.remove(&2);
+    let changes: Vec<Change<u64>> = map.diff_since(&full_cp).into_iter().collect();
+    assert_eq!(changes.len(), 2);
+    assert!(changes.iter().all(|c| matches!(c, Change::Removed(_))));
+}
+
+/// A key inlined at the checkpoint and pushed into a subtree since (and the
+/// reverse) is matched across the data/child boundary.
+#[test]
+fn diff_across_subtree_migration() {
+    let mut map: ChampMap<u64, u64> = (0..40).map(|i| (i, i)).collect();
+    let cp = map.checkpoint();
+    for i in 40..400 {
+        map.insert(i, i);
+    }
+    map.insert(3, 30);
+    let changes = map.diff_since(&cp);
+    assert_eq!(sorted(changes.added()), (40..400).collect::<Vec<_>>());
+    assert_eq!(sorted(changes.updated()), [3]);
+    assert_eq!(changes.removed().count(), 0);
+
+    let cp = map.checkpoint();
+    for i in 10..400 {
+        map.remove(&i);
+    }
+    let changes = map.diff_since(&cp);
+    assert_eq!(sorted(changes.removed()), (10..400).collect::<Vec<_>>());
+    assert_eq!(changes.len(), 390);
+}
+
+#[test]
+fn sync_diff_since() {
+    let mut map: ChampMapSync<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    let cp = map.checkpoint();
+    map.insert(100, 0);
+    map.insert(0, 1);
+    map.remove(&1);
+    let changes = map.diff_since(&cp);
+    assert_eq!(sorted(changes.added()), [100]);
+    assert_eq!(sorted(changes.updated()), [0]);
+    assert_eq!(sorted(changes.removed()), [1]);
+}
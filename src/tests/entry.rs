@@ -0,0 +1,171 @@
+use crate::ChampMap;
+use crate::entry::Entry;
+
+/// Word-frequency counting via `entry().or_insert(0)` matches inserting the
+/// final counts directly.
+#[test]
+fn word_frequency_adhash() {
+    let text = "the quick brown fox jumps over the lazy dog the fox";
+    let mut counts = ChampMap::new();
+    for word in text.split_whitespace() {
+        *counts.entry(word).or_insert(0) += 1;
+    }
+
+    let mut expected = ChampMap::new();
+    for (word, n) in [
+        ("the", 3),
+        ("quick", 1),
+        ("brown", 1),
+        ("fox", 2),
+        ("jumps", 1),
+        ("over", 1),
+        ("lazy", 1),
+        ("dog", 1),
+    ] {
+        expected.insert(word, n);
+    }
+
+    assert_eq!(counts.len(), expected.len());
+    assert_eq!(counts.adhash(), expected.adhash());
+    assert_eq!(counts.get(&"the"), Some(&3));
+}
+
+#[test]
+fn and_modify_or_insert() {
+    let mut map = ChampMap::new();
+    map.entry("a").and_modify(|v| *v += 1).or_insert(10);
+    map.entry("a").and_modify(|v| *v += 1).or_insert(10);
+    assert_eq!(map.get(&"a"), Some(&11));
+
+    let mut expected = ChampMap::new();
+    expected.insert("a", 11);
+    assert_eq!(map.adhash(), expected.adhash());
+}
+
+#[test]
+fn or_default_and_or_insert_with() {
+    let mut map: ChampMap<&str, u32> = ChampMap::new();
+    map.entry("x").or_default();
+    map.entry("y").or_insert_with(|| 7);
+    assert_eq!(map.get(&"x"), Some(&0));
+    assert_eq!(map.get(&"y"), Some(&7));
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn or_insert_with_key_derives_default() {
+    let mut map: ChampMap<String, usize> = ChampMap::new();
+    map.insert("fixed".to_string(), 99);
+    for word in ["alpha", "be", "fixed"] {
+        map.entry(word.to_string()).or_insert_with_key(String::len);
+    }
+
+    let expected: ChampMap<String, usize> = [("alpha", 5), ("be", 2), ("fixed", 99)]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+    assert_eq!(map, expected);
+    assert_eq!(map.adhash(), expected.adhash());
+
+    // An occupied entry never calls the closure.
+    let before = map.adhash();
+    map.entry("be".to_string())
+        .or_insert_with_key(|_| unreachable!());
+    assert_eq!(map.adhash(), before);
+}
+
+#[test]
+fn entry_key_and_variant() {
+    let mut map = ChampMap::new();
+    map.insert(1, "one");
+    assert!(matches!(map.entry(1), Entry::Occupied(_)));
+    assert!(matches!(map.entry(2), Entry::Vacant(_)));
+    assert_eq!(*map.entry(2).key(), 2);
+}
+
+/// Reading an occupied entry without writing allocates nothing.
+#[test]
+fn occupied_read_does_not_allocate() {
+    let mut map = ChampMap::new();
+    for i in 0_u64..100 {
+        map.insert(i, i);
+    }
+    let before = map.arena_len();
+    if let Entry::Occupied(e) = map.entry(42) {
+        assert_eq!(*e.get(), 42);
+    }
+    assert_eq!(map.arena_len(), before);
+}
+
+#[test]
+fn get_or_insert_with_calls_f_once_on_miss() {
+    let mut map: ChampMap<u64, u64> = (0..500).map(|i| (i, i)).collect();
+    let mut calls = 0;
+    let v = *map.get_or_insert_with(1_000, || {
+        calls += 1;
+        7
+    });
+    assert_eq!(v, 7);
+    assert_eq!(calls, 1);
+    assert_eq!(map.len(), 501);
+
+    let v = *map.get_or_insert_with(1_000, || {
+        calls += 1;
+        8
+    });
+    assert_eq!(v, 7);
+    let v = *map.get_or_insert_with(3, || {
+        calls += 1;
+        9
+    });
+    assert_eq!(v, 3);
+    assert_eq!(calls, 1, "f must not run for present keys");
+
+    let mut expected: ChampMap<u64, u64> = (0..500).map(|i| (i, i)).collect();
+    expected.insert(1_000, 7);
+    assert_eq!(map.adhash(), expected.adhash());
+    assert_eq!(map, expected);
+}
+
+#[test]
+fn get_or_insert_with_returns_new_value_everywhere() {
+    let mut map = ChampMap::new();
+    assert_eq!(*map.get_or_insert_with(0_u64, || 100_u64), 100);
+    for i in 1..2_000 {
+        assert_eq!(*map.get_or_insert_with(i, || i + 100), i + 100);
+    }
+    assert_eq!(map.len(), 2_000);
+    let expected: ChampMap<u64, u64> = (0..2_000).map(|i| (i, i + 100)).collect();
+    assert_eq!(map, expected);
+}
+
+#[test]
+fn try_insert_vacant() {
+    let mut map: ChampMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    assert_eq!(map.try_insert(500, 5).ok(), Some(&5));
+    assert_eq!(map.len(), 101);
+    assert_eq!(map.get(&500), Some(&5));
+
+    let mut expected: ChampMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    expected.insert(500, 5);
+    assert_eq!(map.adhash(), expected.adhash());
+}
+
+#[test]
+fn try_insert_occupied_keeps_existing() {
+    let mut map: ChampMap<&str, String> = ChampMap::new();
+    map.insert("a", "old".to_string());
+    let adhash = map.adhash();
+    let (before_nodes, _, _) = map.arena_len();
+
+    let err = map.try_insert("a", "new".to_string()).unwrap_err();
+    assert_eq!(err.key, "a");
+    assert_eq!(err.value, "new");
+    assert_eq!(err.existing, "old");
+    assert!(err.to_string().contains("already exists"));
+
+    assert_eq!(map.get(&"a").map(String::as_str), Some("old"));
+    assert_eq!(map.adhash(), adhash);
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.arena_len().0, before_nodes);
+}
@@ -0,0 +1,59 @@
+use crate::ChampForest;
+use crate::ChampMap;
+use crate::adhash::EMPTY;
+use crate::forest::RootHandle;
+
+/// 100 versions, each one key more than the last: every version reads back
+/// exactly its own prefix, and its `AdHash` matches a standalone map.
+#[test]
+fn hundred_versions_share_one_arena() {
+    let mut forest = ChampForest::new();
+    let mut versions = vec![RootHandle::empty()];
+    for i in 0..100_u32 {
+        let last = *versions.last().unwrap();
+        versions.push(forest.insert(last, i, i * 10));
+    }
+
+    for (n, &v) in versions.iter().enumerate() {
+        assert_eq!(v.len(), n);
+        let expected: ChampMap<u32, u32> = (0..).take(n).map(|i| (i, i * 10)).collect();
+        assert_eq!(v.adhash(), expected.adhash());
+        assert_eq!(forest.iter(v).count(), n);
+        for i in 0..100_u32 {
+            let present = (i as usize) < n;
+            assert_eq!(forest.get(v, &i), present.then_some(&(i * 10)));
+        }
+    }
+    let mut adhashes: Vec<u64> = versions.iter().map(RootHandle::adhash).collect();
+    adhashes.sort_unstable();
+    adhashes.dedup();
+    assert_eq!(adhashes.len(), 101);
+
+    // Path copying shares structure: far fewer entries than Σ len = 5050.
+    assert!(forest.arena_len().1 < 2000);
+}
+
+/// Branching versions are independent; converging writes give equal
+/// `AdHash`es.
+#[test]
+fn branches_and_removal() {
+    let mut forest = ChampForest::new();
+    let base = forest.insert(RootHandle::empty(), "x", 1);
+    let base = forest.insert(base, "y", 2);
+
+    let shadowed = forest.insert(base, "x", 10);
+    let without = forest.remove(base, "x");
+    assert_eq!(forest.get(base, "x"), Some(&1));
+    assert_eq!(forest.get(shadowed, "x"), Some(&10));
+    assert!(!forest.contains_key(without, "x"));
+    assert_eq!(without.len(), 1);
+    assert_eq!(shadowed.len(), 2);
+
+    let restored = forest.insert(without, "x", 1);
+    assert_eq!(restored.adhash(), base.adhash());
+    let unchanged = forest.remove(base, "z");
+    assert_eq!(unchanged.adhash(), base.adhash());
+    let empty = forest.remove(without, "y");
+    assert!(empty.is_empty());
+    assert_eq!(forest.remove(empty, "y").adhash(), EMPTY);
+}
@@ -0,0 +1,133 @@
+use crate::{ChampMap, ChampMapSync};
+
+#[test]
+fn get_mut_writes_value() {
+    let mut map = ChampMap::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+
+    *map.get_mut(&"a").unwrap() += 10;
+
+    assert_eq!(map.get(&"a"), Some(&11));
+    assert_eq!(map.get(&"b"), Some(&2));
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn get_mut_missing_key() {
+    let mut map = ChampMap::new();
+    map.insert(1, 10);
+    assert!(map.get_mut(&2).is_none());
+}
+
+/// `AdHash` after a `get_mut` write equals a map built with the final value.
+#[test]
+fn get_mut_adhash_matches_rebuilt() {
+    let mut map = ChampMap::new();
+    for i in 0_u64..500 {
+        map.insert(i, i);
+    }
+    for i in (0_u64..500).step_by(3) {
+        *map.get_mut(&i).unwrap() *= 2;
+    }
+
+    let mut expected = ChampMap::new();
+    for i in 0_u64..500 {
+        expected.insert(i, if i % 3 == 0 { i * 2 } else { i });
+    }
+
+    assert_eq!(map.adhash(), expected.adhash());
+    assert_eq!(map.len(), expected.len());
+}
+
+/// A checkpoint taken before `get_mut` still rolls back the write.
+#[test]
+fn get_mut_rollback() {
+    let mut map = ChampMap::new();
+    map.insert("k", 1);
+    map.insert("j", 2);
+    let cp = map.checkpoint();
+    let saved = map.adhash();
+
+    *map.get_mut(&"k").unwrap() = 100;
+    assert_eq!(map.get(&"k"), Some(&100));
+
+    map.rollback(cp);
+    assert_eq!(map.get(&"k"), Some(&1));
+    assert_eq!(map.adhash(), saved);
+}
+
+#[test]
+fn sync_get_mut() {
+    let mut map = ChampMapSync::new();
+    map.insert(1, 10);
+    *map.get_mut(&1).unwrap() = 20;
+
+    let mut expected = ChampMapSync::new();
+    expected.insert(1, 20);
+    assert_eq!(map.adhash(), expected.adhash());
+}
+
+#[test]
+fn update_adhash_matches_rebuilt() {
+    let mut map: ChampMap<u64, u64> = (0..200).map(|i| (i, i)).collect();
+    assert!(map.update(&42, |v| *v += 1));
+    assert_eq!(map.get(&42), Some(&43));
+
+    let expected: ChampMap<u64, u64> = (0..200)
+        .map(|i| (i, if i == 42 { 43 } else { i }))
+        .collect();
+    assert_eq!(map.adhash(), expected.adhash());
+    assert_eq!(map, expected);
+}
+
+#[test]
+fn update_missing_key() {
+    let mut map: ChampMap<u64, u64> = (0..10).map(|i| (i, i)).collect();
+    let adhash = map.adhash();
+    let (nodes, _, _) = map.arena_len();
+    assert!(!map.update(&99, |_| unreachable!()));
+    assert_eq!(map.adhash(), adhash);
+    assert_eq!(map.arena_len().0, nodes);
+}
+
+#[test]
+fn update_sync() {
+    let mut map: ChampMapSync<String, u64> = ChampMapSync::new();
+    map.insert("a".to_string(), 1);
+    assert!(map.update("a", |v| *v *= 10));
+    assert_eq!(map.get("a"), Some(&10));
+    assert!(!map.update("b", |v| *v *= 10));
+}
+
+#[test]
+fn iter_mut_doubles_every_value() {
+    let mut map: ChampMap<u32, u32> = (0..2_000).map(|i| (i, i)).collect();
+    let cp = map.checkpoint();
+    {
+        let mut iter = map.iter_mut();
+        assert_eq!(iter.len(), 2_000);
+        while let Some((k, v)) = iter.next() {
+            assert_eq!(k, v);
+            *v *= 2;
+        }
+        assert!(iter.is_empty());
+    }
+    let doubled: ChampMap<u32, u32> = (0..2_000).map(|i| (i, i * 2)).collect();
+    assert_eq!(map.adhash(), doubled.adhash());
+    assert_eq!(map, doubled);
+    assert_eq!(map.debug_invariants(), Ok(()));
+
+    // Stopping early writes back only the values handed out.
+    let mut iter = map.iter_mut();
+    let (first, v) = iter.next().expect("non-empty");
+    let first = *first;
+    *v = 0;
+    drop(iter);
+    assert_eq!(map.get(&first), Some(&0));
+    assert_eq!(map.iter().filter(|&(k, v)| *v == k * 2).count(), 1_999);
+    assert!(map.verify_adhash());
+
+    map.rollback(cp);
+    assert!(map.iter().all(|(k, v)| k == v));
+}
@@ -0,0 +1,234 @@
+use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+
+use crate::adhash::{DefaultHashBuilder, EMPTY, hash_one};
+use crate::transient::Transient;
+use crate::{ChampMap, ChampMapSync};
+
+/// 64-bit FNV-1a: deterministic and unseeded.
+#[derive(Default)]
+struct Fnv(u64);
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        if self.0 == 0 {
+            self.0 = 0xCBF2_9CE4_8422_2325;
+        }
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01B3);
+        }
+    }
+}
+
+/// Hashes every key to the same value.
+#[derive(Default)]
+struct Constant;
+
+impl Hasher for Constant {
+    fn finish(&self) -> u64 {
+        0x5EED
+    }
+
+    fn write(&mut self, _: &[u8]) {}
+}
+
+type FnvBuild = BuildHasherDefault<Fnv>;
+
+/// Seeded FNV-1a over little-endian integers: the same hashes on every
+/// platform and in every process.
+#[derive(Clone, Copy)]
+struct SeededFnv(u64);
+
+impl BuildHasher for SeededFnv {
+    type Hasher = Fnv;
+
+    fn build_hasher(&self) -> Fnv {
+        Fnv(0xCBF2_9CE4_8422_2325 ^ self.0)
+    }
+}
+
+fn seeded_map(seed: u64, keys: impl Iterator<Item = u64>) -> ChampMap<u64, u64, SeededFnv> {
+    let mut map = ChampMap::with_hasher(SeededFnv(seed));
+    for k in keys {
+        map.insert(k, k * 3);
+    }
+    map
+}
+
+#[test]
+fn default_hasher_matches_hash_one() {
+    for key in ["", "a", "champ"] {
+        assert_eq!(DefaultHashBuilder::default().hash_one(key), hash_one(key));
+    }
+}
+
+#[test]
+fn custom_hasher_lookups() {
+    let mut map: ChampMap<u64, u64, FnvBuild> = (0..5_000).map(|i| (i, i * 2)).collect();
+    for i in 0..5_000 {
+        assert_eq!(map.get(&i), Some(&(i * 2)));
+    }
+    assert_eq!(map.get(&5_000), None);
+
+    for i in (0..5_000).step_by(2) {
+        assert_eq!(map.remove(&i), Some(i * 2));
+    }
+    *map.entry(1).or_insert(0) += 1;
+    assert_eq!(map.insert_many((5_000..5_100).map(|i| (i, i))), 100);
+    assert_eq!(map[&1], 3);
+    assert_eq!(map.len(), 2_600);
+    assert!(map.verify_adhash());
+    assert_eq!(map.debug_invariants(), Ok(()));
+
+    // Same contents, different hasher: same entries, different AdHash.
+    let std_hashed: ChampMap<u64, u64> = map.iter().map(|(k, v)| (*k, *v)).collect();
+    assert_ne!(map.adhash(), std_hashed.adhash());
+    assert!(std_hashed.iter().all(|(k, v)| map.get(k) == Some(v)));
+}
+
+#[test]
+fn custom_hasher_is_canonical() {
+    let forward: ChampMap<String, usize, FnvBuild> = (0..500).map(|i| (i.to_string(), i)).collect();
+    let mut backward = ChampMap::<String, usize, FnvBuild>::default();
+    for i in (0..500).rev() {
+        backward.insert(i.to_string(), i);
+    }
+    assert_eq!(forward, backward);
+    assert_eq!(forward.adhash(), backward.adhash());
+    assert_eq!(forward.get("42"), Some(&42));
+    assert!(forward.is_subset(&backward));
+}
+
+#[test]
+fn degenerate_hasher_falls_back_to_collisions() {
+    let mut map: ChampMap<u32, u32, BuildHasherDefault<Constant>> =
+        (0..100).map(|i| (i, i)).collect();
+    let stats = map.structure_stats();
+    assert_eq!(stats.collision_nodes, 1);
+    assert_eq!(stats.collision_sizes, [100]);
+    for i in 0..100 {
+        assert_eq!(map.get(&i), Some(&i));
+    }
+    for i in 0..99 {
+        assert_eq!(map.remove(&i), Some(i));
+    }
+    assert_eq!(map.debug_invariants(), Ok(()));
+    assert_eq!(map.get(&99), Some(&99));
+}
+
+#[test]
+fn sync_and_transient_with_custom_hasher() {
+    let mut transient = Transient::<u64, u64, FnvBuild>::default();
+    transient.extend((0..1_000).map(|i| (i, i)));
+    let map = transient.freeze_sync();
+    let collected: ChampMapSync<u64, u64, FnvBuild> = (0..1_000).map(|i| (i, i)).collect();
+    assert_eq!(map, collected);
+    assert_eq!(map.get(&999), Some(&999));
+    assert!(map.verify_adhash());
+}
+
+#[test]
+fn seeded_hasher_gives_reproducible_adhash() {
+    let forward = seeded_map(42, 0..1_000);
+    let backward = seeded_map(42, (0..1_000).rev());
+    assert_eq!(forward.adhash(), backward.adhash());
+    assert_eq!(forward, backward);
+
+    let mut transient = Transient::<u64, u64, _>::with_hasher(SeededFnv(42));
+    transient.extend((0..1_000).map(|k| (k, k * 3)));
+    assert_eq!(transient.freeze_sync().adhash(), forward.adhash());
+
+    // Pinned: a content address must not change between runs or platforms.
+    #[cfg(not(feature = "strong-hash"))]
+    assert_eq!(forward.adhash(), 0x7F03_41F2_C115_5DE9);
+
+    assert_ne!(seeded_map(43, 0..1_000).adhash(), forward.adhash());
+    assert_eq!(forward.hasher().0, 42);
+}
+
+/// Hashes everything to 0.
+#[derive(Default)]
+struct Zero;
+
+impl Hasher for Zero {
+    fn finish(&self) -> u64 {
+        0
+    }
+
+    fn write(&mut self, _: &[u8]) {}
+}
+
+/// An entry whose key and value both hash to 0 still contributes to the
+/// `AdHash`, so maps differing only by it compare unequal.
+#[test]
+fn zero_hashes_still_change_adhash() {
+    assert_ne!(crate::adhash::entry_adhash(0, 0), 0);
+
+    let mut map: ChampMap<u32, u32, BuildHasherDefault<Zero>> = ChampMap::default();
+    map.insert(1, 1);
+    let one = map.clone();
+    map.insert(2, 2);
+    assert_ne!(map.adhash(), one.adhash());
+    assert_ne!(one.adhash(), EMPTY);
+    assert_ne!(map, one);
+    assert_eq!(map.debug_invariants(), Ok(()));
+}
+
+#[test]
+fn random_seed_maps_agree_on_contents_not_adhash() {
+    let build = || {
+        let mut map = ChampMap::with_random_seed();
+        map.extend((0..1_000u32).map(|i| (i, i * 2)));
+        map
+    };
+    let (a, b) = (build(), build());
+    assert!(a.verify_adhash() && b.verify_adhash());
+    assert_eq!(a.as_sorted_vec(), b.as_sorted_vec());
+    assert!((0..1_000).all(|i| a.get(&i) == Some(&(i * 2)) && b.get(&i) == a.get(&i)));
+    assert_eq!(a, b);
+    assert_eq!(hash_one(&a), hash_one(&b));
+
+    let sync = ChampMapSync::<u32, u32, _>::with_random_seed();
+    assert!(sync.is_empty());
+}
+
+/// Two seeds of one hasher type give unrelated tries and `AdHash` values;
+/// equality, `Hash`, subset tests and set operations between such maps
+/// still go by contents.
+#[test]
+fn differently_seeded_maps_compare_by_contents() {
+    let a = seeded_map(1, 0..1_000);
+    let b = seeded_map(2, (0..1_000).rev());
+    assert_ne!(a.adhash(), b.adhash());
+    assert_eq!(a, b);
+    assert_eq!(hash_one(&a), hash_one(&b));
+    assert!(a.is_subset(&b) && b.is_superset(&a));
+
+    let evens = seeded_map(3, (0..1_000).step_by(2));
+    let odds = seeded_map(4, (1..1_000).step_by(2));
+    let mut changed = seeded_map(5, 0..1_000);
+    changed.insert(0, 1);
+    assert_ne!(a, evens);
+    assert_ne!(a, changed);
+    assert!(evens.is_subset(&a) && !a.is_subset(&evens));
+    assert!(!changed.is_subset(&a));
+
+    let union = evens.union(&odds, |x, _| *x);
+    assert_eq!(union, a);
+    assert_eq!(union.hasher().0, 3);
+    assert!(union.verify_adhash());
+    assert_eq!(union.debug_invariants(), Ok(()));
+    assert_eq!(a.intersection(&evens), evens);
+    assert_eq!(a.difference(&evens), odds);
+    assert_eq!(a.symmetric_difference(&evens), odds);
+    assert_eq!(a.difference(&evens).debug_invariants(), Ok(()));
+    assert_eq!(changed.merge_with(&b, |x, y| x + y).get(&0), Some(&1));
+}
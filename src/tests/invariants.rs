@@ -0,0 +1,53 @@
+use crate::ChampMap;
+use crate::invariants::InvariantError;
+
+#[test]
+fn empty_and_singleton_hold() {
+    let mut map = ChampMap::new();
+    assert_eq!(map.debug_invariants(), Ok(()));
+    map.insert(1_u64, 1_u64);
+    assert_eq!(map.debug_invariants(), Ok(()));
+    map.remove(&1);
+    assert_eq!(map.debug_invariants(), Ok(()));
+}
+
+/// Removing most keys repeatedly collapses subtrees back into their parents.
+#[test]
+fn hold_after_inlining_removals() {
+    let mut map: ChampMap<u64, u64> = (0..2_000).map(|i| (i, i)).collect();
+    assert_eq!(map.debug_invariants(), Ok(()));
+    for step in [2, 3, 5, 7] {
+        for i in (0..2_000).step_by(step) {
+            map.remove(&i);
+        }
+        assert_eq!(
+            map.debug_invariants(),
+            Ok(()),
+            "after removing every {step}th key"
+        );
+    }
+    let rest: Vec<u64> = map.keys().copied().collect();
+    for (n, k) in rest.iter().enumerate() {
+        map.remove(k);
+        if n % 50 == 0 {
+            assert_eq!(map.debug_invariants(), Ok(()));
+        }
+    }
+    assert!(map.is_empty());
+    assert_eq!(map.debug_invariants(), Ok(()));
+}
+
+#[test]
+fn hold_after_set_operations() {
+    let a: ChampMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+    let b: ChampMap<u64, u64> = (500..1_500).map(|i| (i, i)).collect();
+    assert_eq!(a.union(&b, |x, _| *x).debug_invariants(), Ok(()));
+    assert_eq!(a.intersection(&b).debug_invariants(), Ok(()));
+    assert_eq!(a.difference(&b).debug_invariants(), Ok(()));
+}
+
+#[test]
+fn error_display_names_node() {
+    let err = InvariantError::NotInlined { node: 7 };
+    assert_eq!(err.to_string(), "node 7: single-entry subtree not inlined");
+}
@@ -0,0 +1,371 @@
+use std::collections::HashSet;
+
+use crate::ChampMap;
+use crate::adhash::EMPTY;
+
+#[test]
+fn iter_yields_every_entry_once() {
+    let map: ChampMap<u64, u64> = (0..1_000).map(|i| (i, i + 1)).collect();
+    let seen: HashSet<u64> = map
+        .iter()
+        .map(|(k, v)| {
+            assert_eq!(*v, k + 1);
+            *k
+        })
+        .collect();
+    assert_eq!(seen.len(), 1_000);
+}
+
+#[test]
+fn iter_exact_size_counts_down() {
+    let map: ChampMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    let mut it = map.iter();
+    assert_eq!(it.len(), 100);
+    it.next();
+    it.next();
+    assert_eq!(it.len(), 98);
+    assert_eq!(it.by_ref().count(), 98);
+    assert_eq!(it.len(), 0);
+    assert!(it.next().is_none());
+}
+
+#[test]
+fn iter_early_exit() {
+    let map: ChampMap<u64, u64> = (0..10_000).map(|i| (i, i)).collect();
+    assert_eq!(map.iter().take(5).count(), 5);
+    assert!(map.iter().any(|(k, _)| *k == 1234));
+}
+
+#[test]
+fn iter_empty() {
+    let map: ChampMap<u64, u64> = ChampMap::new();
+    assert!(map.iter().next().is_none());
+    assert_eq!(map.iter().len(), 0);
+}
+
+#[test]
+fn keys_count_matches_len() {
+    let map: ChampMap<u64, u64> = (0..500).map(|i| (i, i * 3)).collect();
+    assert_eq!(map.keys().len(), map.len());
+    assert_eq!(map.keys().count(), map.len());
+}
+
+#[test]
+fn keys_and_values_follow_iter_order() {
+    let map: ChampMap<u64, u64> = (0..500).map(|i| (i, i * 3)).collect();
+    let pairs: Vec<(&u64, &u64)> = map.iter().collect();
+    let keys: Vec<&u64> = map.keys().collect();
+    let values: Vec<&u64> = map.values().collect();
+    assert_eq!(keys, pairs.iter().map(|&(k, _)| k).collect::<Vec<_>>());
+    assert_eq!(values, pairs.iter().map(|&(_, v)| v).collect::<Vec<_>>());
+}
+
+#[test]
+fn into_iter_moves_live_entries() {
+    let mut map = ChampMap::new();
+    for i in 0..200 {
+        map.insert(i, format!("v{i}"));
+    }
+    // Leave dead COW copies behind: overwrites and removals.
+    for i in (0..200).step_by(2) {
+        map.insert(i, format!("w{i}"));
+    }
+    for i in 150..200 {
+        map.remove(&i);
+    }
+
+    let it = map.into_iter();
+    assert_eq!(it.len(), 150);
+    let mut pairs: Vec<(i32, String)> = it.collect();
+    pairs.sort();
+
+    let mut expected: Vec<(i32, String)> = (0..150)
+        .map(|i| {
+            (
+                i,
+                if i % 2 == 0 {
+                    format!("w{i}")
+                } else {
+                    format!("v{i}")
+                },
+            )
+        })
+        .collect();
+    expected.sort();
+    assert_eq!(pairs, expected);
+}
+
+#[test]
+fn into_iter_for_loop() {
+    let map: ChampMap<u64, u64> = (0..10).map(|i| (i, i)).collect();
+    let mut sum = 0;
+    for (k, v) in map {
+        sum += k + v;
+    }
+    assert_eq!(sum, 90);
+}
+
+#[test]
+fn into_keys_and_into_values_move_live_entries() {
+    let mut map: ChampMap<u32, String> = (0..100).map(|i| (i, format!("v{i}"))).collect();
+    map.insert(7, "w7".to_string());
+    map.remove(&99);
+
+    let keys = map.clone().into_keys();
+    assert_eq!(keys.len(), 99);
+    let mut keys: Vec<u32> = keys.collect();
+    keys.sort_unstable();
+    assert_eq!(keys, (0..99).collect::<Vec<_>>());
+
+    let values: Vec<String> = map.clone().into_values().collect();
+    let expected: Vec<String> = map.into_iter().map(|(_, v)| v).collect();
+    assert_eq!(values, expected);
+    assert!(values.contains(&"w7".to_string()));
+}
+
+#[test]
+fn iter_rev_reverses_forward_order() {
+    let map: ChampMap<u64, u64> = (0..2_000).map(|i| (i, i)).collect();
+    let forward: Vec<_> = map.iter().collect();
+    let mut backward: Vec<_> = map.iter().rev().collect();
+    backward.reverse();
+    assert_eq!(forward, backward);
+
+    let keys: Vec<_> = map.keys().rev().collect();
+    assert_eq!(keys.len(), 2_000);
+    assert_eq!(keys.first(), forward.last().map(|(k, _)| k));
+}
+
+/// Mixing both ends yields every entry exactly once, then stops.
+#[test]
+fn iter_meets_in_the_middle() {
+    let map: ChampMap<u64, u64> = (0..501).map(|i| (i, i)).collect();
+    let mut it = map.iter();
+    let mut seen = Vec::new();
+    loop {
+        match (it.next(), it.next_back()) {
+            (Some(a), Some(b)) => {
+                seen.push(*a.0);
+                seen.push(*b.0);
+            }
+            (Some(a), None) | (None, Some(a)) => seen.push(*a.0),
+            (None, None) => break,
+        }
+        assert_eq!(it.len(), 501 - seen.len());
+    }
+    seen.sort_unstable();
+    assert_eq!(seen, (0..501).collect::<Vec<_>>());
+}
+
+#[test]
+fn iter_sorted_is_strictly_increasing() {
+    let map: ChampMap<u64, u64> = (0..3_000).map(|i| (i * 7 % 3_001, i)).collect();
+    let sorted: Vec<_> = map.iter_sorted().collect();
+    assert_eq!(sorted.len(), map.len());
+    assert_eq!(map.iter_sorted().len(), map.len());
+    assert!(sorted.windows(2).all(|w| w[0].0 < w[1].0));
+    assert!(sorted.iter().all(|(k, v)| map.get(k) == Some(v)));
+}
+
+#[test]
+fn keys_and_values_sorted() {
+    let map: ChampMap<&str, u32> = [("pear", 3), ("apple", 1), ("fig", 2)]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        map.keys_sorted().copied().collect::<Vec<_>>(),
+        ["apple", "fig", "pear"]
+    );
+    assert_eq!(map.values_sorted().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(map.keys_sorted().next_back(), Some(&"pear"));
+}
+
+#[test]
+fn range_bounds() {
+    use std::ops::Bound::{Excluded, Included};
+
+    let map: ChampMap<u64, u64> = (0..100).map(|i| (i, i * 2)).collect();
+    let keys = |it: crate::iter::SortedIter<'_, u64, u64>| it.map(|(k, _)| *k).collect::<Vec<_>>();
+
+    assert_eq!(keys(map.range(10..20)), (10..20).collect::<Vec<_>>());
+    assert_eq!(keys(map.range(10..=20)), (10..=20).collect::<Vec<_>>());
+    assert_eq!(keys(map.range(..5)), [0, 1, 2, 3, 4]);
+    assert_eq!(keys(map.range(95..)), [95, 96, 97, 98, 99]);
+    assert_eq!(map.range(..).len(), 100);
+    assert_eq!(map.range(10..20).next(), Some((&10, &20)));
+    assert_eq!(keys(map.range((Excluded(10), Included(13)))), [11, 12, 13]);
+}
+
+#[test]
+fn range_empty_and_out_of_bounds() {
+    let map: ChampMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    assert_eq!(map.range(10..10).count(), 0);
+    assert_eq!(map.range(200..300).count(), 0);
+    assert_eq!(map.range(50..500).count(), 50);
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = map.range(20..10);
+    assert_eq!(reversed.count(), 0);
+    assert_eq!(ChampMap::<u64, u64>::new().range(..).count(), 0);
+}
+
+#[test]
+fn range_by_borrowed_key() {
+    use std::ops::Bound::{Included, Unbounded};
+
+    let map: ChampMap<String, u32> = ["a", "b", "c", "d"]
+        .into_iter()
+        .zip(0..)
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+    let keys: Vec<&str> = map
+        .range::<str, _>((Included("b"), Unbounded))
+        .map(|(k, _)| k.as_str())
+        .collect();
+    assert_eq!(keys, ["b", "c", "d"]);
+}
+
+#[test]
+fn drain_into_vec_empties_map() {
+    let mut map: ChampMap<u64, String> = (0..1_000).map(|i| (i, i.to_string())).collect();
+    map.insert(5, "five".to_string());
+    let mut drained: Vec<(u64, String)> = map.drain().collect();
+    drained.sort_unstable();
+
+    assert_eq!(drained.len(), 1_000);
+    assert_eq!(drained[5], (5, "five".to_string()));
+    assert_eq!(drained[999], (999, "999".to_string()));
+    assert!(map.is_empty());
+    assert_eq!(map.adhash(), EMPTY);
+    assert_eq!(map.get(&5), None);
+}
+
+#[test]
+fn partial_drain_still_clears() {
+    let mut map: ChampMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    {
+        let mut drain = map.drain();
+        assert_eq!(drain.len(), 100);
+        assert!(drain.next().is_some());
+        assert_eq!(drain.len(), 99);
+    }
+    assert!(map.is_empty());
+    assert_eq!(map.adhash(), EMPTY);
+    assert_eq!(map.iter().count(), 0);
+
+    map.insert(1, 1);
+    assert_eq!(map.len(), 1);
+    assert_eq!(map, std::iter::once((1, 1)).collect::<ChampMap<_, _>>());
+}
+
+#[test]
+fn first_and_last_key_value() {
+    let map: ChampMap<i64, i64> = (-500..500).map(|i| (i * 7, i)).collect();
+    assert_eq!(map.first_key_value(), Some((&-3_500, &-500)));
+    assert_eq!(map.last_key_value(), Some((&3_493, &499)));
+
+    let single: ChampMap<i64, i64> = std::iter::once((42, 1)).collect();
+    assert_eq!(single.first_key_value(), Some((&42, &1)));
+    assert_eq!(single.last_key_value(), Some((&42, &1)));
+
+    let empty: ChampMap<i64, i64> = ChampMap::new();
+    assert_eq!(empty.first_key_value(), None);
+    assert_eq!(empty.last_key_value(), None);
+}
+
+#[test]
+fn pop_first_drains_ascending() {
+    let mut map: ChampMap<u64, u64> = (0..300).rev().map(|i| (i, i * 2)).collect();
+    let mut popped = Vec::new();
+    while let Some((k, v)) = map.pop_first() {
+        assert_eq!(v, k * 2);
+        popped.push(k);
+        if k % 50 == 0 {
+            assert!(map.verify_adhash());
+            assert_eq!(map.len(), 300 - popped.len());
+        }
+    }
+    assert_eq!(popped, (0..300).collect::<Vec<_>>());
+    assert!(map.is_empty());
+    assert_eq!(map.adhash(), EMPTY);
+}
+
+#[test]
+fn pop_last_takes_largest() {
+    let mut map: ChampMap<u64, u64> = (0..10).map(|i| (i, i)).collect();
+    assert_eq!(map.pop_last(), Some((9, 9)));
+    assert_eq!(map.pop_last(), Some((8, 8)));
+    assert_eq!(map.len(), 8);
+
+    let expected: ChampMap<u64, u64> = (0..8).map(|i| (i, i)).collect();
+    assert_eq!(map.adhash(), expected.adhash());
+    assert_eq!(ChampMap::<u64, u64>::new().pop_last(), None);
+}
+
+#[test]
+fn contains_value_short_circuits() {
+    let map: ChampMap<u32, String> = (0..200).map(|i| (i, format!("v{i}"))).collect();
+    assert!(map.contains_value(&"v0".to_string()));
+    assert!(map.contains_value(&"v199".to_string()));
+    assert!(!map.contains_value(&"v200".to_string()));
+    assert!(!ChampMap::<u32, String>::new().contains_value(&String::new()));
+
+    let sync: crate::ChampMapSync<u32, u32> = (0..50).map(|i| (i, i * 3)).collect();
+    assert!(sync.contains_value(&147));
+    assert!(!sync.contains_value(&148));
+    assert!(!crate::ChampMapSync::<u32, u32>::new().contains_value(&0));
+}
+
+#[test]
+fn find_key_returns_dfs_first_match() {
+    let forward: ChampMap<u32, &str> = (0..100)
+        .map(|i| (i, if i == 17 || i == 83 { "dup" } else { "other" }))
+        .collect();
+    let mut backward = ChampMap::new();
+    for (k, v) in forward.iter().collect::<Vec<_>>().into_iter().rev() {
+        backward.insert(*k, *v);
+    }
+
+    let dfs_first = forward.iter().find(|(_, v)| **v == "dup").map(|(k, _)| k);
+    assert!(dfs_first == Some(&17) || dfs_first == Some(&83));
+    assert_eq!(forward.find_key(&"dup"), dfs_first);
+    assert_eq!(backward.find_key(&"dup"), dfs_first);
+    assert_eq!(forward.find_key(&"missing"), None);
+    assert_eq!(ChampMap::<u32, &str>::new().find_key(&"dup"), None);
+}
+
+#[test]
+fn iter_chunks_batches_in_iter_order() {
+    let map: ChampMap<u32, u32> = (0..1000).map(|i| (i, i)).collect();
+    let chunks: Vec<Vec<(&u32, &u32)>> = map.iter_chunks(256).collect();
+    let sizes: Vec<usize> = chunks.iter().map(Vec::len).collect();
+    assert_eq!(sizes, [256, 256, 256, 232]);
+    assert!(chunks.into_iter().flatten().eq(map.iter()));
+
+    assert_eq!(map.iter_chunks(1000).count(), 1);
+    assert_eq!(ChampMap::<u32, u32>::new().iter_chunks(8).count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "chunk size must be non-zero")]
+fn iter_chunks_rejects_zero() {
+    let _ = ChampMap::<u32, u32>::new().iter_chunks(0);
+}
+
+#[test]
+fn sorted_vec_collectors() {
+    let map: ChampMap<u32, u32> = (0..500).rev().map(|i| (i * 7 % 500, i)).collect();
+    let sorted = map.as_sorted_vec();
+    assert_eq!(sorted.len(), 500);
+    assert!(sorted.windows(2).all(|w| w[0].0 < w[1].0));
+    assert!(sorted.iter().all(|(k, v)| map.get(k) == Some(v)));
+
+    let owned = map.clone().into_sorted_vec();
+    assert_eq!(
+        owned,
+        sorted.iter().map(|(k, v)| (**k, **v)).collect::<Vec<_>>()
+    );
+
+    let empty: ChampMap<u32, u32> = ChampMap::new();
+    assert!(empty.as_sorted_vec().is_empty());
+    assert!(empty.into_sorted_vec().is_empty());
+}
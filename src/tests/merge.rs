@@ -0,0 +1,255 @@
+use crate::ChampMap;
+use crate::adhash::{self, EMPTY};
+
+/// Naive reference: insert every entry of `b` into a copy of `a`.
+fn naive_union(
+    a: &ChampMap<u64, u64>,
+    b: &ChampMap<u64, u64>,
+    resolve: impl Fn(&u64, &u64) -> u64,
+) -> ChampMap<u64, u64> {
+    let mut out = a.clone();
+    for (k, v) in b {
+        let merged = a.get(k).map_or(*v, |av| resolve(av, v));
+        out.insert(*k, merged);
+    }
+    out
+}
+
+#[test]
+fn union_disjoint() {
+    let a: ChampMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    let b: ChampMap<u64, u64> = (100..250).map(|i| (i, i)).collect();
+    let u = a.union(&b, |x, _| *x);
+    assert_eq!(u.len(), 250);
+    assert_eq!(u, naive_union(&a, &b, |x, _| *x));
+    assert_eq!(u.adhash(), adhash::combine(a.adhash(), b.adhash()));
+}
+
+#[test]
+fn union_overlapping_resolves() {
+    let a: ChampMap<u64, u64> = (0..300).map(|i| (i, i)).collect();
+    let b: ChampMap<u64, u64> = (200..500).map(|i| (i, i * 2)).collect();
+    let u = a.union(&b, |x, y| *x.max(y));
+    let expected = naive_union(&a, &b, |x, y| *x.max(y));
+    assert_eq!(u.len(), 500);
+    assert_eq!(u.adhash(), expected.adhash());
+    assert_eq!(u, expected);
+    assert_eq!(u.get(&250), Some(&500));
+}
+
+#[test]
+fn union_with_empty() {
+    let a: ChampMap<u64, u64> = (0..10).map(|i| (i, i)).collect();
+    let empty = ChampMap::new();
+    assert_eq!(a.union(&empty, |x, _| *x), a);
+    assert_eq!(empty.union(&a, |x, _| *x), a);
+}
+
+/// Heavily overlapping maps share equal-`AdHash` subtrees: the result's
+/// arena grows by a handful of paths, not by the size of the maps.
+#[test]
+fn union_shares_identical_subtrees() {
+    let a: ChampMap<u64, u64> = (0..10_000).map(|i| (i, i)).collect();
+    let mut b = a.clone();
+    for i in 0..5 {
+        b.insert(i * 1_000, 0);
+    }
+    b.insert(20_000, 1);
+
+    let u = a.union(&b, |_, y| *y);
+    assert_eq!(u, b);
+
+    let (n0, e0, c0) = a.arena_len();
+    let (n1, e1, c1) = u.arena_len();
+    let growth = (n1 - n0) + (e1 - e0) + (c1 - c0);
+    assert!(growth < 2_000, "union grew the arena by {growth} items");
+}
+
+/// Naive reference: rebuild from the entries of `a` that pass `keep`.
+fn naive_filter(a: &ChampMap<u64, u64>, keep: impl Fn(&u64) -> bool) -> ChampMap<u64, u64> {
+    a.iter()
+        .filter(|(k, _)| keep(k))
+        .map(|(k, v)| (*k, *v))
+        .collect()
+}
+
+#[test]
+fn intersection_overlapping_ranges() {
+    let a: ChampMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+    let b: ChampMap<u64, u64> = (500..2_000).map(|i| (i, i + 1)).collect();
+    let r = a.intersection(&b);
+    let expected = naive_filter(&a, |k| b.contains_key(k));
+    assert_eq!(r.len(), 500);
+    assert_eq!(r.adhash(), expected.adhash());
+    assert_eq!(r, expected);
+    assert_eq!(r.get(&700), Some(&700), "values come from self");
+    assert!(!r.contains_key(&499));
+}
+
+#[test]
+fn difference_overlapping_ranges() {
+    let a: ChampMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+    let b: ChampMap<u64, u64> = (500..2_000).map(|i| (i, i + 1)).collect();
+    let r = a.difference(&b);
+    let expected = naive_filter(&a, |k| !b.contains_key(k));
+    assert_eq!(r.len(), 500);
+    assert_eq!(r.adhash(), expected.adhash());
+    assert_eq!(r, expected);
+    assert!(r.contains_key(&499));
+    assert!(!r.contains_key(&500));
+}
+
+#[test]
+fn intersection_and_difference_partition() {
+    let a: ChampMap<u64, u64> = (0..3_000).map(|i| (i * 7, i)).collect();
+    let b: ChampMap<u64, u64> = (0..3_000).filter(|i| i % 3 == 0).map(|i| (i, 0)).collect();
+    let common = a.intersection(&b);
+    let unique = a.difference(&b);
+    assert_eq!(common.len() + unique.len(), a.len());
+    assert_eq!(
+        adhash::combine(common.adhash(), unique.adhash()),
+        a.adhash()
+    );
+    assert_eq!(common.union(&unique, |x, _| *x), a);
+}
+
+#[test]
+fn intersection_difference_with_self_and_empty() {
+    let a: ChampMap<u64, u64> = (0..200).map(|i| (i, i)).collect();
+    let empty = ChampMap::new();
+    assert_eq!(a.intersection(&a), a);
+    assert!(a.difference(&a).is_empty());
+    assert_eq!(a.difference(&a).adhash(), EMPTY);
+    assert!(a.intersection(&empty).is_empty());
+    assert_eq!(a.difference(&empty), a);
+    assert!(empty.intersection(&a).is_empty());
+}
+
+/// Shrinking to a single entry must leave a canonical single-entry root.
+#[test]
+fn filter_down_to_single_entry_is_canonical() {
+    let a: ChampMap<u64, u64> = (0..500).map(|i| (i, i)).collect();
+    let b: ChampMap<u64, u64> = std::iter::once((42, 0)).collect();
+    let common = a.intersection(&b);
+    let expected: ChampMap<u64, u64> = std::iter::once((42, 42)).collect();
+    assert_eq!(common, expected);
+    assert_eq!(common.adhash(), expected.adhash());
+
+    let rest: ChampMap<u64, u64> = (0..499).map(|i| (i, i)).collect();
+    let single = a.difference(&rest);
+    assert_eq!(single.len(), 1);
+    assert_eq!(single.get(&499), Some(&499));
+}
+
+#[test]
+fn subset_proper_and_equal() {
+    let small: ChampMap<u64, u64> = (0..300).map(|i| (i, i)).collect();
+    let large: ChampMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+    assert!(small.is_subset(&large));
+    assert!(large.is_superset(&small));
+    assert!(!large.is_subset(&small));
+    assert!(!small.is_superset(&large));
+
+    let same: ChampMap<u64, u64> = (0..1_000).rev().map(|i| (i, i)).collect();
+    assert!(large.is_subset(&same));
+    assert!(large.is_superset(&same));
+
+    let empty = ChampMap::new();
+    assert!(empty.is_subset(&large));
+    assert!(!large.is_subset(&empty));
+    assert!(empty.is_subset(&empty));
+}
+
+#[test]
+fn subset_rejects_single_value_difference() {
+    let a: ChampMap<u64, u64> = (0..500).map(|i| (i, i)).collect();
+    let mut b = a.clone();
+    b.insert(250, 0);
+    assert!(!a.is_subset(&b));
+    assert!(!b.is_subset(&a));
+
+    // Same length, disjoint key sets.
+    let c: ChampMap<u64, u64> = (500..1_000).map(|i| (i, i)).collect();
+    assert!(!a.is_subset(&c));
+
+    // A proper subset until one shared key gets a different value.
+    let mut d: ChampMap<u64, u64> = (0..2_000).map(|i| (i, i)).collect();
+    assert!(a.is_subset(&d));
+    d.insert(499, 1);
+    assert!(!a.is_subset(&d));
+}
+
+#[test]
+fn join_yields_common_keys_with_both_values() {
+    let small: ChampMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    let large: ChampMap<u64, String> = (50..1000).map(|i| (i, format!("{i}"))).collect();
+
+    let mut joined: Vec<(u64, u64, String)> = small
+        .join(&large)
+        .map(|(k, v, w)| (*k, *v, w.clone()))
+        .collect();
+    joined.sort_unstable();
+    let expected: Vec<(u64, u64, String)> = (50..100).map(|i| (i, i, format!("{i}"))).collect();
+    assert_eq!(joined, expected);
+
+    // Driven from the larger side, values still come out in (self, other) order.
+    let mut reversed: Vec<(u64, String, u64)> = large
+        .join(&small)
+        .map(|(k, w, v)| (*k, w.clone(), *v))
+        .collect();
+    reversed.sort_unstable();
+    assert_eq!(reversed.len(), 50);
+    assert!(
+        reversed
+            .iter()
+            .all(|(k, w, v)| k == v && *w == k.to_string())
+    );
+
+    assert_eq!(small.join(&ChampMap::<u64, u8>::new()).count(), 0);
+}
+
+#[test]
+fn symmetric_difference_of_overlapping_ranges() {
+    let a: ChampMap<u64, u64> = (0..600).map(|i| (i, i)).collect();
+    let mut b: ChampMap<u64, u64> = (400..1000).map(|i| (i, i + 1)).collect();
+    b.insert(500, 500);
+
+    let sym = a.symmetric_difference(&b);
+    let expected: ChampMap<u64, u64> = (0..400)
+        .map(|i| (i, i))
+        .chain((600..1000).map(|i| (i, i + 1)))
+        .collect();
+    assert_eq!(sym.len(), 800);
+    assert_eq!(sym.adhash(), expected.adhash());
+    assert_eq!(sym, expected);
+    assert_eq!(sym.debug_invariants(), Ok(()));
+    assert_eq!(b.symmetric_difference(&a), expected);
+
+    assert!(a.symmetric_difference(&a).is_empty());
+    assert_eq!(a.symmetric_difference(&ChampMap::new()), a);
+    assert_eq!(ChampMap::new().symmetric_difference(&a), a);
+
+    // Down to a single survivor, which must be inlined at the root.
+    let one: ChampMap<u64, u64> = (0..601).map(|i| (i, i)).collect();
+    let single = one.symmetric_difference(&a);
+    assert_eq!(single.len(), 1);
+    assert_eq!(single.get(&600), Some(&600));
+    assert_eq!(single.debug_invariants(), Ok(()));
+}
+
+#[test]
+fn merge_with_sums_counters() {
+    let a: ChampMap<&str, u32> = [("a", 1), ("b", 2)].into_iter().collect();
+    let b: ChampMap<&str, u32> = [("b", 3), ("c", 4)].into_iter().collect();
+    let merged = a.merge_with(&b, |x, y| x + y);
+    let expected: ChampMap<&str, u32> = [("a", 1), ("b", 5), ("c", 4)].into_iter().collect();
+    assert_eq!(merged, expected);
+    assert_eq!(merged.adhash(), expected.adhash());
+
+    // Identical subtrees are combined too, not skipped as `union` would.
+    let counts: ChampMap<u64, u64> = (0..500).map(|i| (i, 1)).collect();
+    let doubled = counts.merge_with(&counts, |x, y| x + y);
+    let expected: ChampMap<u64, u64> = (0..500).map(|i| (i, 2)).collect();
+    assert_eq!(doubled.adhash(), expected.adhash());
+    assert_eq!(doubled.debug_invariants(), Ok(()));
+}
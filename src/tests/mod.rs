@@ -1,10 +1,31 @@
 mod adhash;
 mod basic;
+mod borrow;
 mod canonical;
+mod codec;
 mod collision;
+mod compact;
 mod completeness;
+mod cursor;
+mod diff;
+mod entry;
+mod forest;
+mod get_mut;
+mod hasher;
+mod invariants;
+mod iter;
+mod merge;
 mod nfr;
+mod order;
+#[cfg(feature = "rayon")]
+mod par;
 mod persistence;
+#[cfg(feature = "rand")]
+mod sample;
+mod snapshot;
+mod store;
 mod stress;
 mod sync;
 mod traits;
+mod transform;
+mod transient;
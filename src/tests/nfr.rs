@@ -323,6 +323,28 @@ macro_rules! nfr_tests {
     };
 }
 
+/// Building through `FromIterator` (a transient, frozen once) allocates only
+/// the live set, while repeated COW inserts leave a dead path copy per insert.
+#[test]
+fn transient_build_reduces_allocations() {
+    let n = 100_000_u64;
+    let mut cow = crate::ChampMap::new();
+    for i in 0..n {
+        cow.insert(i, i);
+    }
+    let built: crate::ChampMap<u64, u64> = (0..n).map(|i| (i, i)).collect();
+    assert_eq!(built, cow);
+
+    let total = |(nodes, entries, children): (usize, usize, usize)| nodes + entries + children;
+    let cow_total = total(cow.arena_len());
+    let built_total = total(built.arena_len());
+    assert_eq!(built.arena_len().1, built.len());
+    assert!(
+        built_total * 4 < cow_total,
+        "transient build allocated {built_total} items vs {cow_total} for COW inserts"
+    );
+}
+
 /// Trait to abstract over insert for both map types.
 trait MapInsert {
     fn map_insert(&mut self, key: u64, value: u64);
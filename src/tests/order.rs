@@ -0,0 +1,76 @@
+use crate::ChampMap;
+use crate::adhash::hash_one;
+use crate::node;
+
+#[test]
+fn fragments_follow_numeric_hash_order() {
+    let fragments = |hash: u64| -> Vec<u32> {
+        (0..=node::MAX_SHIFT)
+            .step_by(node::BITS_PER_LEVEL as usize)
+            .map(|shift| node::fragment(hash, shift))
+            .collect()
+    };
+    // The root fragment holds the top bits.
+    assert_eq!(node::fragment(1 << 63, 0), 1 << (node::BITS_PER_LEVEL - 1));
+    assert_eq!(node::fragment(1, 0), 0);
+    assert_eq!(fragments(0), vec![0; node::COLLISION_DEPTH]);
+    for (a, b) in [(0, 1), (1, 2), (7, 1 << 40), (u64::MAX - 1, u64::MAX)] {
+        assert!(fragments(a) < fragments(b), "{a:#x} vs {b:#x}");
+    }
+}
+
+#[test]
+fn min_and_max_hash_entries_match_a_full_scan() {
+    let mut map: ChampMap<u64, u64> = (0..5_000).map(|i| (i, i * 10)).collect();
+    for _ in 0..50 {
+        let min = map.keys().map(hash_one).min().unwrap();
+        let max = map.keys().map(hash_one).max().unwrap();
+        let (&lo, &lo_value) = map.entry_with_min_hash().unwrap();
+        let (&hi, &hi_value) = map.entry_with_max_hash().unwrap();
+        assert_eq!((hash_one(&lo), lo_value), (min, lo * 10));
+        assert_eq!((hash_one(&hi), hi_value), (max, hi * 10));
+        map.remove(&lo);
+        map.remove(&hi);
+    }
+
+    let single: ChampMap<u64, u64> = std::iter::once((7, 70)).collect();
+    assert_eq!(single.entry_with_min_hash(), Some((&7, &70)));
+    assert_eq!(single.entry_with_max_hash(), Some((&7, &70)));
+    assert_eq!(ChampMap::<u64, u64>::new().entry_with_min_hash(), None);
+    assert_eq!(ChampMap::<u64, u64>::new().entry_with_max_hash(), None);
+}
+
+#[test]
+fn successor_by_hash_walks_the_ring() {
+    let map: ChampMap<u64, u64> = (0..2_000).map(|i| (i * 7, i)).collect();
+    let mut ring: Vec<u64> = map.keys().copied().collect();
+    ring.sort_unstable_by_key(hash_one);
+    let expected = |target: u64| {
+        let at = ring.partition_point(|k| hash_one(k) < target);
+        ring.get(at).copied().unwrap_or(ring[0])
+    };
+
+    // Every key is its own successor; probes in between land on the key
+    // with the next larger hash, and probes past the largest hash wrap
+    // around to the smallest.
+    for &k in &ring {
+        assert_eq!(
+            map.successor_by_hash(hash_one(&k)).map(|(k, _)| *k),
+            Some(k)
+        );
+    }
+    for probe in 0..5_000u64 {
+        let target = hash_one(&(probe, "probe"));
+        let (k, _) = map.successor_by_hash(target).unwrap();
+        assert_eq!(*k, expected(target));
+        assert!(hash_one(k) >= target || hash_one(k) == hash_one(&ring[0]));
+    }
+    let last = hash_one(ring.last().unwrap());
+    assert_eq!(
+        map.successor_by_hash(last + 1).map(|(k, _)| *k),
+        Some(ring[0])
+    );
+    assert_eq!(map.successor_by_hash(0).map(|(k, _)| *k), Some(ring[0]));
+
+    assert_eq!(ChampMap::<u64, u64>::new().successor_by_hash(0), None);
+}
@@ -0,0 +1,62 @@
+use rayon::prelude::*;
+
+use crate::ChampMapSync;
+
+#[test]
+fn par_sum_matches_sequential() {
+    let map: ChampMapSync<u64, u64> = (0..100_000).map(|i| (i, i * 3)).collect();
+    let sequential: u64 = map.values().sum();
+    let parallel: u64 = map.par_iter().map(|(_, v)| *v).sum();
+    assert_eq!(parallel, sequential);
+    assert_eq!(map.par_iter().count(), 100_000);
+}
+
+#[test]
+fn par_iter_visits_each_entry_once() {
+    let map: ChampMapSync<u64, u64> = (0..5_000).map(|i| (i, i)).collect();
+    let mut keys: Vec<u64> = map.par_iter().map(|(k, _)| *k).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, (0..5_000).collect::<Vec<_>>());
+}
+
+#[test]
+fn par_iter_empty_and_short_circuit() {
+    let empty: ChampMapSync<u64, u64> = ChampMapSync::new();
+    assert_eq!(empty.par_iter().count(), 0);
+
+    let map: ChampMapSync<u64, u64> = (0..10_000).map(|i| (i, i)).collect();
+    assert!(map.par_iter().any(|(k, _)| *k == 9_999));
+    assert!(!map.par_iter().any(|(k, _)| *k == 10_000));
+}
+
+#[test]
+fn from_par_iter_matches_sequential_build() {
+    let parallel: ChampMapSync<u64, u64> =
+        (0..100_000_u64).into_par_iter().map(|i| (i, i)).collect();
+    let sequential: ChampMapSync<u64, u64> = (0..100_000).map(|i| (i, i)).collect();
+    assert_eq!(parallel.len(), 100_000);
+    assert_eq!(parallel.adhash(), sequential.adhash());
+    assert_eq!(parallel, sequential);
+    assert_eq!(parallel.debug_invariants(), Ok(()));
+}
+
+#[test]
+fn from_par_iter_last_duplicate_wins() {
+    let map: ChampMapSync<u64, u64> = (0..20_000_u64)
+        .into_par_iter()
+        .map(|i| (i % 100, i))
+        .collect();
+    let expected: ChampMapSync<u64, u64> = (0..20_000).map(|i| (i % 100, i)).collect();
+    assert_eq!(map, expected);
+}
+
+#[test]
+fn par_extend_overwrites() {
+    let mut map: ChampMapSync<u64, u64> = (0..1_000).map(|i| (i, 0)).collect();
+    map.par_extend((500..1_500_u64).into_par_iter().map(|i| (i, i)));
+    let expected: ChampMapSync<u64, u64> = (0..1_500)
+        .map(|i| (i, if i < 500 { 0 } else { i }))
+        .collect();
+    assert_eq!(map.adhash(), expected.adhash());
+    assert_eq!(map, expected);
+}
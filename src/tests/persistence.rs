@@ -1,4 +1,5 @@
 use crate::ChampMap;
+use crate::adhash::EMPTY;
 
 /// Checkpoint + insert + rollback = original state.
 #[test]
@@ -53,7 +54,7 @@ fn rollback_to_empty() {
 
     map.rollback(cp);
     assert!(map.is_empty());
-    assert_eq!(map.adhash(), 0);
+    assert_eq!(map.adhash(), EMPTY);
 }
 
 /// Multiple checkpoints: rollback to the earlier one.
@@ -74,3 +75,211 @@ fn nested_checkpoints() {
     assert_eq!(map.get(&1), Some(&10));
     assert_eq!(map.get(&2), None);
 }
+
+/// Innermost-first rollbacks stay valid, and so does rolling back to the
+/// same checkpoint repeatedly.
+#[test]
+fn nested_rollbacks_innermost_first() {
+    let mut map = ChampMap::new();
+    map.insert(1, 10);
+    let cp1 = map.checkpoint();
+    map.insert(2, 20);
+    let cp2 = map.checkpoint();
+    map.insert(3, 30);
+
+    map.rollback(cp2);
+    map.insert(4, 40);
+    map.rollback(cp2);
+    assert_eq!(map.len(), 2);
+    map.rollback(cp1);
+    assert_eq!(map.len(), 1);
+    map.insert(5, 50);
+    map.rollback(cp1);
+    assert_eq!(map.len(), 1);
+    assert!(map.verify_adhash());
+}
+
+/// A checkpoint taken after the target of an earlier rollback points into
+/// discarded arena space; rolling back to it panics instead of corrupting
+/// the map.
+#[test]
+#[should_panic(expected = "rollback to a checkpoint invalidated by an earlier rollback")]
+fn rollback_to_superseded_checkpoint_panics() {
+    let mut map = ChampMap::new();
+    map.insert(1, 10);
+    let cp1 = map.checkpoint();
+    map.insert(2, 20);
+    let cp2 = map.checkpoint();
+
+    map.rollback(cp1);
+    map.insert(3, 30);
+    map.insert(4, 40);
+    map.rollback(cp2);
+}
+
+/// `compact` swaps in fresh arenas; once they regrow past the old
+/// checkpoint's position, rolling back to it would truncate into unrelated
+/// nodes, so it panics instead.
+#[test]
+#[should_panic(expected = "rollback to a checkpoint invalidated by an earlier rollback")]
+fn rollback_across_compact_panics() {
+    let mut map: ChampMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    for i in 0..100 {
+        map.insert(i, i + 1);
+    }
+    let cp = map.checkpoint();
+    map.compact();
+    for i in 0..4_000 {
+        map.insert(i, i);
+    }
+    map.rollback(cp);
+}
+
+/// `drain` hands its arenas to the iterator; the map's new, empty arenas
+/// reject checkpoints of the old ones.
+#[test]
+#[should_panic(expected = "rollback to a checkpoint invalidated by an earlier rollback")]
+fn rollback_across_drain_panics() {
+    let mut map: ChampMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    let cp = map.checkpoint();
+    assert_eq!(map.drain().count(), 100);
+    map.extend((0..4_000).map(|i| (i, i)));
+    map.rollback(cp);
+}
+
+/// Clone starts with the source's `AdHash`; mutating it leaves the source intact.
+#[test]
+fn clone_is_independent() {
+    let mut original = ChampMap::new();
+    for i in 0..100 {
+        original.insert(i, i * 10);
+    }
+    let saved_len = original.len();
+    let saved_adhash = original.adhash();
+
+    let mut copy = original.clone();
+    assert_eq!(copy.adhash(), saved_adhash);
+    assert_eq!(copy, original);
+
+    copy.insert(1_000, 1);
+    copy.remove(&5);
+    *copy.get_mut(&6).unwrap() = 0;
+
+    assert_eq!(original.len(), saved_len);
+    assert_eq!(original.adhash(), saved_adhash);
+    assert_eq!(original.get(&5), Some(&50));
+    assert_eq!(original.get(&6), Some(&60));
+    assert_eq!(original.get(&1_000), None);
+    assert_ne!(copy.adhash(), saved_adhash);
+}
+
+#[test]
+fn inserted_and_removed_leave_self_unchanged() {
+    use crate::adhash::{entry_adhash, hash_one};
+
+    let m: ChampMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+    let plus = m.inserted(500, 7);
+    assert!(plus.contains_key(&500));
+    assert!(!m.contains_key(&500));
+    assert_eq!(
+        crate::adhash::difference(plus.adhash(), m.adhash()),
+        entry_adhash(hash_one(&500_u32), hash_one(&7_u32))
+    );
+
+    let minus = plus.removed(&500);
+    assert_eq!(minus, m);
+    assert_eq!(plus.len(), 101);
+    assert_eq!(m.removed(&1000), m);
+}
+
+/// A checkpoint can be read from while the map moves on.
+#[test]
+fn read_at_sees_checkpointed_values() {
+    let mut map: ChampMap<u32, u32> = (0..500).map(|i| (i, i)).collect();
+    let cp = map.checkpoint();
+
+    map.insert(7, 700);
+    map.insert(1_000, 1);
+    map.remove(&8);
+    for i in 500..900 {
+        map.insert(i, i);
+    }
+
+    assert_eq!(map.read_at(&cp, &7), Some(&7));
+    assert_eq!(map.get(&7), Some(&700));
+    assert_eq!(map.read_at(&cp, &1_000), None);
+    assert_eq!(map.get(&1_000), Some(&1));
+    assert_eq!(map.read_at(&cp, &8), Some(&8));
+    assert_eq!(map.get(&8), None);
+    assert!((0..500).all(|i| i == 7 || i == 8 || map.read_at(&cp, &i) == map.get(&i)));
+
+    let empty = ChampMap::<u32, u32>::new().checkpoint();
+    assert_eq!(map.read_at(&empty, &7), None);
+}
+
+/// `with_rollback` undoes a failed closure down to the arena items, and
+/// keeps a successful one.
+#[test]
+fn with_rollback_undoes_err_and_keeps_ok() {
+    let mut map: ChampMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+    let before = (map.len(), map.adhash(), map.arena_len());
+
+    let failed: Result<(), &str> = map.with_rollback(|m| {
+        m.insert(1_000, 1);
+        m.remove(&5);
+        m.insert(7, 70);
+        Err("rejected")
+    });
+    assert_eq!(failed, Err("rejected"));
+    assert_eq!((map.len(), map.adhash(), map.arena_len()), before);
+    assert_eq!(map.get(&5), Some(&5));
+    assert_eq!(map.get(&1_000), None);
+
+    let kept = map.with_rollback(|m| Ok::<_, ()>(m.insert(1_000, 1)));
+    assert_eq!(kept, Ok(None));
+    assert_eq!(map.len(), 101);
+    assert_eq!(map.get(&1_000), Some(&1));
+}
+
+/// A dropped transaction leaves the map as it was; a committed one keeps
+/// its edits.
+#[test]
+fn transaction_rolls_back_unless_committed() {
+    let mut map: ChampMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+    let before = (map.len(), map.adhash(), map.arena_len());
+
+    {
+        let mut tx = map.transaction();
+        tx.insert(1_000, 1);
+        tx.remove(&5);
+        assert_eq!(tx.len(), 100);
+        assert_eq!(tx.get(&1_000), Some(&1));
+    }
+    assert_eq!((map.len(), map.adhash(), map.arena_len()), before);
+    assert_eq!(map.get(&5), Some(&5));
+
+    let mut tx = map.transaction();
+    tx.insert(7, 70);
+    tx.abort();
+    assert_eq!((map.len(), map.adhash(), map.arena_len()), before);
+
+    let mut tx = map.transaction();
+    tx.insert(1_000, 1);
+    tx.remove(&5);
+    tx.commit();
+    assert_eq!(map.len(), 100);
+    assert_eq!(map.get(&1_000), Some(&1));
+    assert_eq!(map.get(&5), None);
+
+    // Nested transactions unwind innermost first.
+    let committed = map.adhash();
+    let mut outer = map.transaction();
+    outer.insert(2_000, 2);
+    let mut inner = outer.transaction();
+    inner.insert(3_000, 3);
+    inner.commit();
+    assert_eq!(outer.get(&3_000), Some(&3));
+    drop(outer);
+    assert_eq!(map.adhash(), committed);
+    assert_eq!(map.get(&2_000), None);
+}
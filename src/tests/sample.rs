@@ -0,0 +1,37 @@
+use std::collections::HashSet;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::ChampMap;
+
+#[test]
+fn sample_returns_min_n_len_distinct_entries() {
+    let map: ChampMap<u32, u32> = (0..1_000).map(|i| (i, i * 2)).collect();
+    let mut rng = StdRng::seed_from_u64(7);
+    for n in [0, 1, 10, 999, 1_000, 5_000] {
+        let picked = map.sample(n, &mut rng);
+        assert_eq!(picked.len(), n.min(map.len()));
+        let keys: HashSet<u32> = picked.iter().map(|(k, _)| **k).collect();
+        assert_eq!(keys.len(), picked.len());
+        assert!(picked.iter().all(|(k, v)| map.get(k) == Some(v)));
+    }
+    assert!(ChampMap::<u32, u32>::new().sample(3, &mut rng).is_empty());
+}
+
+/// Each of 20 entries should land in a 5-entry sample a quarter of the time.
+#[test]
+fn sample_is_roughly_uniform() {
+    let map: ChampMap<u32, ()> = (0..20).map(|i| (i, ())).collect();
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut hits = [0u32; 20];
+    for _ in 0..20_000 {
+        for (k, ()) in map.sample(5, &mut rng) {
+            hits[*k as usize] += 1;
+        }
+    }
+    // Expected 5000 per key; the standard deviation is about 61.
+    for (k, &h) in hits.iter().enumerate() {
+        assert!((4_600..=5_400).contains(&h), "key {k} sampled {h} times");
+    }
+}
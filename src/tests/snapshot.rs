@@ -0,0 +1,91 @@
+use crate::{ChampMap, ChampMapSync};
+
+/// Writes after a snapshot — inserts, overwrites and removals — leave the
+/// snapshot's length, contents and `AdHash` unchanged.
+#[test]
+fn snapshot_is_frozen_across_mutation() {
+    let mut map: ChampMap<u32, u32> = (0..1000).map(|i| (i, i * 2)).collect();
+    let snap = map.snapshot();
+    let adhash = map.adhash();
+
+    for i in 1000..2000 {
+        map.insert(i, i);
+    }
+    for i in 0..100 {
+        map.remove(&i);
+    }
+    map.insert(500, 0);
+
+    assert_eq!(snap.len(), 1000);
+    assert_eq!(snap.adhash(), adhash);
+    let view = map.view(&snap);
+    assert_eq!(view.len(), 1000);
+    assert_eq!(view.adhash(), adhash);
+    assert_eq!(view.get(&5), Some(&10));
+    assert_eq!(view.get(&500), Some(&1000));
+    assert!(!view.contains_key(&1500));
+    assert_eq!(view.iter().count(), 1000);
+    assert!(view.iter().all(|(k, v)| *v == k * 2));
+
+    assert_eq!(map.len(), 1900);
+    assert_eq!(map.get(&500), Some(&0));
+    assert_eq!(map.get(&5), None);
+}
+
+/// Rolling back to a checkpoint taken after the snapshot keeps it valid, and
+/// the snapshot's contents match a map rebuilt from them.
+#[test]
+fn snapshot_survives_later_rollback() {
+    let mut map: ChampMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+    let snap = map.snapshot();
+    map.insert(100, 100);
+    let cp = map.checkpoint();
+    map.remove(&0);
+    map.rollback(cp);
+
+    let rebuilt: ChampMap<u32, u32> = map.view(&snap).iter().map(|(&k, &v)| (k, v)).collect();
+    assert_eq!(rebuilt.len(), 100);
+    assert_eq!(rebuilt.adhash(), snap.adhash());
+    assert!(!rebuilt.contains_key(&100));
+}
+
+#[test]
+fn sync_snapshot_and_empty_snapshot() {
+    let mut map = ChampMapSync::new();
+    let empty = map.snapshot();
+    map.insert("a", 1);
+    let one = map.snapshot();
+    map.insert("a", 2);
+    map.insert("b", 3);
+
+    assert!(map.view(&empty).is_empty());
+    assert_eq!(map.view(&empty).iter().next(), None);
+    assert_eq!(map.view(&empty).get("a"), None);
+    assert_eq!(map.view(&one).get("a"), Some(&1));
+    assert_eq!(map.view(&one).len(), 1);
+    assert_eq!(map.get("a"), Some(&2));
+}
+
+/// Rolling back past a snapshot discards its nodes; later writes reuse their
+/// slots, so viewing it panics rather than reading them.
+#[test]
+#[should_panic(expected = "view of a snapshot invalidated by a rollback or a cleared arena")]
+fn view_after_rollback_past_snapshot_panics() {
+    let mut map: ChampMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+    let cp = map.checkpoint();
+    map.insert(100, 100);
+    let snap = map.snapshot();
+    map.rollback(cp);
+    map.insert(200, 200);
+    let _ = map.view(&snap);
+}
+
+#[test]
+#[should_panic(expected = "view of a snapshot invalidated by a rollback or a cleared arena")]
+fn view_after_compact_panics() {
+    let mut map: ChampMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+    map.insert(0, 1);
+    let snap = map.snapshot();
+    map.compact();
+    let _ = map.view(&snap);
+}
@@ -0,0 +1,313 @@
+use std::hash::BuildHasher;
+
+use safe_bump::{Checkpoint, Idx};
+
+use crate::adhash::DefaultHashBuilder;
+use crate::arena::ChampArena;
+use crate::node::{Entry, Node};
+use crate::ops::get::try_get_recursive;
+use crate::ops::insert::{insert_recursive, singleton};
+use crate::store::{ChampStore, Generation, Generations, StoreCheckpoint, StoreError};
+use crate::{ChampMap, ChampMapGeneric};
+
+/// Arena whose fallible reads fail for one node index, as a backend over
+/// damaged storage would.
+struct FailingStore {
+    inner: ChampArena<u64, u64>,
+    bad_node: Option<usize>,
+}
+
+impl ChampStore<u64, u64> for FailingStore {
+    type IntoEntries = <ChampArena<u64, u64> as ChampStore<u64, u64>>::IntoEntries;
+
+    fn alloc_node(&mut self, node: Node<u64, u64>) -> Idx<Node<u64, u64>> {
+        self.inner.alloc_node(node)
+    }
+
+    fn get_node(&self, idx: Idx<Node<u64, u64>>) -> &Node<u64, u64> {
+        self.inner.get_node(idx)
+    }
+
+    fn alloc_entries(
+        &mut self,
+        iter: impl IntoIterator<Item = Entry<u64, u64>>,
+    ) -> Option<Idx<Entry<u64, u64>>> {
+        self.inner.alloc_entries(iter)
+    }
+
+    fn get_entry(&self, idx: Idx<Entry<u64, u64>>) -> &Entry<u64, u64> {
+        self.inner.get_entry(idx)
+    }
+
+    fn alloc_children(
+        &mut self,
+        iter: impl IntoIterator<Item = Idx<Node<u64, u64>>>,
+    ) -> Option<Idx<Idx<Node<u64, u64>>>> {
+        self.inner.alloc_children(iter)
+    }
+
+    fn get_child(&self, idx: Idx<Idx<Node<u64, u64>>>) -> &Idx<Node<u64, u64>> {
+        self.inner.get_child(idx)
+    }
+
+    fn try_get_node(&self, idx: Idx<Node<u64, u64>>) -> Result<&Node<u64, u64>, StoreError> {
+        if self.bad_node == Some(idx.into_raw()) {
+            return Err(StoreError::Node(idx.into_raw()));
+        }
+        Ok(self.get_node(idx))
+    }
+
+    fn checkpoint(&self) -> StoreCheckpoint<u64, u64> {
+        self.inner.checkpoint()
+    }
+
+    fn rollback(&mut self, cp: StoreCheckpoint<u64, u64>) {
+        self.inner.rollback(cp);
+    }
+
+    fn is_valid(&self, generation: Generation) -> bool {
+        self.inner.is_valid(generation)
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    fn supersede(&mut self, old: &Self) {
+        self.inner.supersede(&old.inner);
+    }
+
+    fn arena_len(&self) -> (usize, usize, usize) {
+        self.inner.arena_len()
+    }
+
+    fn arena_capacity(&self) -> (usize, usize, usize) {
+        self.inner.arena_capacity()
+    }
+
+    fn reserve(&mut self, nodes: usize, entries: usize, children: usize) {
+        self.inner.reserve(nodes, entries, children);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+    }
+
+    fn into_entries(self) -> Self::IntoEntries {
+        self.inner.into_entries()
+    }
+}
+
+#[test]
+fn try_get_surfaces_store_errors() {
+    let hasher = DefaultHashBuilder::new();
+    let mut store = FailingStore {
+        inner: ChampArena::new(),
+        bad_node: None,
+    };
+    let entry = |k: u64| Entry {
+        hash: hasher.hash_one(k),
+        key: k,
+        value: k * 2,
+    };
+    let mut root = singleton(&mut store, &hasher, entry(0));
+    for k in 1..1_000 {
+        root = insert_recursive(&mut store, &hasher, root, entry(k), 0).node;
+    }
+    let lookup = |store: &FailingStore, k: u64| {
+        try_get_recursive(store, root, hasher.hash_one(k), &k, 0).map(|e| e.map(|e| e.value))
+    };
+    assert_eq!(lookup(&store, 7), Ok(Some(14)));
+    assert_eq!(lookup(&store, 5_000), Ok(None));
+
+    store.bad_node = Some(root.into_raw());
+    assert_eq!(lookup(&store, 7), Err(StoreError::Node(root.into_raw())));
+    assert_eq!(StoreError::Node(3).to_string(), "node 3 could not be read");
+
+    // The built-in arenas use the infallible defaults.
+    let map: ChampMap<u64, u64> = (0..1_000).map(|k| (k, k * 2)).collect();
+    assert_eq!(map.try_get(&7), Ok(Some(&14)));
+    assert_eq!(map.try_get(&5_000), Ok(None));
+    assert_eq!(ChampMap::<u64, u64>::new().try_get(&7), Ok(None));
+}
+
+/// Plain `Vec`-backed store, as the simplest custom backend a user could
+/// supply. Checkpoints record the three lengths; rollback truncates to them.
+struct VecStore<K, V> {
+    nodes: Vec<Node<K, V>>,
+    entries: Vec<Entry<K, V>>,
+    children: Vec<Idx<Node<K, V>>>,
+    generations: Generations,
+}
+
+impl<K, V> Default for VecStore<K, V> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            entries: Vec::new(),
+            children: Vec::new(),
+            generations: Generations::new(),
+        }
+    }
+}
+
+/// Appends `iter` to `vec`, returning the index of the first new item.
+fn push_all<T, U>(vec: &mut Vec<T>, iter: impl IntoIterator<Item = T>) -> Option<Idx<U>> {
+    let start = vec.len();
+    vec.extend(iter);
+    (vec.len() > start).then(|| Idx::from_raw(start))
+}
+
+impl<K, V> ChampStore<K, V> for VecStore<K, V> {
+    type IntoEntries = std::vec::IntoIter<Entry<K, V>>;
+
+    fn alloc_node(&mut self, node: Node<K, V>) -> Idx<Node<K, V>> {
+        self.nodes.push(node);
+        Idx::from_raw(self.nodes.len() - 1)
+    }
+
+    fn get_node(&self, idx: Idx<Node<K, V>>) -> &Node<K, V> {
+        &self.nodes[idx.into_raw()]
+    }
+
+    fn alloc_entries(
+        &mut self,
+        iter: impl IntoIterator<Item = Entry<K, V>>,
+    ) -> Option<Idx<Entry<K, V>>> {
+        push_all(&mut self.entries, iter)
+    }
+
+    fn get_entry(&self, idx: Idx<Entry<K, V>>) -> &Entry<K, V> {
+        &self.entries[idx.into_raw()]
+    }
+
+    fn alloc_children(
+        &mut self,
+        iter: impl IntoIterator<Item = Idx<Node<K, V>>>,
+    ) -> Option<Idx<Idx<Node<K, V>>>> {
+        push_all(&mut self.children, iter)
+    }
+
+    fn get_child(&self, idx: Idx<Idx<Node<K, V>>>) -> &Idx<Node<K, V>> {
+        &self.children[idx.into_raw()]
+    }
+
+    fn checkpoint(&self) -> StoreCheckpoint<K, V> {
+        let (nodes, entries, children) = self.arena_len();
+        StoreCheckpoint {
+            nodes: Checkpoint::from_len(nodes),
+            entries: Checkpoint::from_len(entries),
+            children: Checkpoint::from_len(children),
+            generation: self.generations.stamp(nodes + entries + children),
+        }
+    }
+
+    fn rollback(&mut self, cp: StoreCheckpoint<K, V>) {
+        self.generations.rollback(cp.generation);
+        self.nodes.truncate(cp.nodes.len());
+        self.entries.truncate(cp.entries.len());
+        self.children.truncate(cp.children.len());
+    }
+
+    fn is_valid(&self, generation: Generation) -> bool {
+        self.generations.is_valid(generation)
+    }
+
+    fn clear(&mut self) {
+        self.generations.clear();
+        self.nodes.clear();
+        self.entries.clear();
+        self.children.clear();
+    }
+
+    fn supersede(&mut self, old: &Self) {
+        self.generations = old.generations.cleared();
+    }
+
+    fn arena_len(&self) -> (usize, usize, usize) {
+        (self.nodes.len(), self.entries.len(), self.children.len())
+    }
+
+    fn arena_capacity(&self) -> (usize, usize, usize) {
+        (
+            self.nodes.capacity(),
+            self.entries.capacity(),
+            self.children.capacity(),
+        )
+    }
+
+    fn reserve(&mut self, nodes: usize, entries: usize, children: usize) {
+        self.nodes.reserve(nodes);
+        self.entries.reserve(entries);
+        self.children.reserve(children);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+        self.entries.shrink_to_fit();
+        self.children.shrink_to_fit();
+    }
+
+    fn into_entries(self) -> Self::IntoEntries {
+        self.entries.into_iter()
+    }
+}
+
+#[test]
+fn custom_store_runs_basic_crud() {
+    let mut map: ChampMapGeneric<u64, u64, VecStore<u64, u64>> = ChampMapGeneric::default();
+    let mut reference = ChampMap::new();
+    assert!(map.is_empty());
+
+    for k in 0..2_000 {
+        assert_eq!(map.insert(k, k * 3), None);
+        reference.insert(k, k * 3);
+    }
+    assert_eq!(map.insert(7, 70), Some(21));
+    reference.insert(7, 70);
+    for k in (0..2_000).step_by(3) {
+        assert_eq!(map.remove(&k), reference.remove(&k));
+    }
+    *map.entry(1).or_insert(0) += 1;
+    *reference.entry(1).or_insert(0) += 1;
+
+    assert_eq!(map.len(), reference.len());
+    assert_eq!(map.adhash(), reference.adhash());
+    assert_eq!(map.get(&7), Some(&70));
+    assert_eq!(map.get(&1), Some(&4));
+    assert_eq!(map.get(&3), None);
+    assert!(map.iter().all(|(k, v)| reference.get(k) == Some(v)));
+    map.debug_invariants().unwrap();
+
+    let stats = map.compact();
+    assert!(stats.reclaimed() > 0);
+    assert_eq!(map.adhash(), reference.adhash());
+
+    let collected: ChampMapGeneric<u64, u64, VecStore<u64, u64>> =
+        reference.clone().into_iter().collect();
+    assert_eq!(collected, map);
+    assert_eq!(map.into_sorted_vec(), reference.into_sorted_vec());
+}
+
+#[test]
+fn custom_store_rolls_back_to_checkpoint() {
+    let mut map: ChampMapGeneric<u64, u64, VecStore<u64, u64>> = (0..500).map(|k| (k, k)).collect();
+    let before = (map.len(), map.adhash(), map.arena_len());
+
+    let cp = map.checkpoint();
+    for k in 400..900 {
+        map.insert(k, k + 1);
+    }
+    map.remove(&3);
+    map.rollback(cp);
+    assert_eq!((map.len(), map.adhash(), map.arena_len()), before);
+    assert_eq!(map.get(&3), Some(&3));
+    map.debug_invariants().unwrap();
+
+    let failed: Result<(), ()> = map.with_rollback(|m| {
+        m.insert(10_000, 1);
+        Err(())
+    });
+    assert_eq!(failed, Err(()));
+    assert_eq!((map.len(), map.adhash(), map.arena_len()), before);
+}
@@ -1,4 +1,5 @@
 use crate::ChampMap;
+use crate::adhash::EMPTY;
 
 /// 1000 entries: insert all, verify all, remove all.
 #[test]
@@ -13,11 +14,16 @@ fn thousand_entries() {
         assert_eq!(map.get(&i), Some(&(i * 3)), "missing key {i}");
     }
 
+    assert!(map.verify_adhash());
+
     for i in 0_u64..1000 {
         assert!(map.remove(&i).is_some(), "failed to remove key {i}");
+        if i % 100 == 0 {
+            assert!(map.verify_adhash(), "adhash drift after removing {i}");
+        }
     }
     assert!(map.is_empty());
-    assert_eq!(map.adhash(), 0);
+    assert_eq!(map.adhash(), EMPTY);
 }
 
 /// Deep trie: keys with shared hash prefixes force deeper nodes.
@@ -54,4 +60,29 @@ fn interleaved_operations() {
     for i in (0_u64..200).step_by(2) {
         assert_eq!(map.get(&i), Some(&(i + 1000)));
     }
+    assert!(map.verify_adhash());
+}
+
+/// Pseudo-random insert/overwrite/remove sequence: the maintained `AdHash`
+/// never drifts from a full recomputation.
+#[test]
+fn random_operations_keep_adhash() {
+    let mut map = ChampMap::new();
+    let mut state = 0x9E37_79B9_7F4A_7C15_u64;
+    for step in 0..5_000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let key = state % 512;
+        if state & (1 << 40) == 0 {
+            map.remove(&key);
+        } else {
+            map.insert(key, state >> 48);
+        }
+        if step % 250 == 0 {
+            assert!(map.verify_adhash(), "adhash drift at step {step}");
+        }
+    }
+    assert!(map.verify_adhash());
 }
@@ -1,10 +1,11 @@
 use crate::ChampMapSync;
+use crate::adhash::EMPTY;
 
 #[test]
 fn sync_empty() {
     let map: ChampMapSync<i32, i32> = ChampMapSync::new();
     assert!(map.is_empty());
-    assert_eq!(map.adhash(), 0);
+    assert_eq!(map.adhash(), EMPTY);
 }
 
 #[test]
@@ -77,3 +78,248 @@ fn sync_stress_100() {
         assert_eq!(map.get(&i), Some(&(i * 5)));
     }
 }
+
+#[test]
+fn sync_eq() {
+    let a: ChampMapSync<u64, u64> = (0..50).map(|i| (i, i)).collect();
+    let b: ChampMapSync<u64, u64> = (0..50).rev().map(|i| (i, i)).collect();
+    assert_eq!(a, b);
+}
+
+#[test]
+fn sync_keys_values() {
+    let map: ChampMapSync<u64, u64> = (0..50).map(|i| (i, i * 2)).collect();
+    assert_eq!(map.keys().len(), 50);
+    assert_eq!(
+        map.values().copied().sum::<u64>(),
+        (0..50).map(|i| i * 2).sum()
+    );
+}
+
+#[test]
+fn sync_clear() {
+    let mut map: ChampMapSync<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(map.adhash(), EMPTY);
+    map.insert(1, 1);
+    assert_eq!(map.get(&1), Some(&1));
+    assert_eq!(map.len(), 1);
+}
+
+#[test]
+fn sync_drain() {
+    let mut map: ChampMapSync<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    let mut drained: Vec<_> = map.drain().collect();
+    drained.sort_unstable();
+    assert_eq!(drained, (0..100).map(|i| (i, i)).collect::<Vec<_>>());
+    assert!(map.is_empty());
+}
+
+#[test]
+fn sync_try_insert() {
+    let mut map = ChampMapSync::new();
+    assert_eq!(map.try_insert(1, 10).ok(), Some(&10));
+    let err = map.try_insert(1, 20).unwrap_err();
+    assert_eq!((err.key, err.value, *err.existing), (1, 20, 10));
+    assert_eq!(map.get(&1), Some(&10));
+}
+
+/// Parent and fork are both writable, and neither sees the other's writes.
+#[test]
+fn sync_fork_is_isolated() {
+    let mut parent: ChampMapSync<u32, u32> = (0..500).map(|i| (i, i)).collect();
+    for i in 0..100 {
+        parent.insert(i, i + 1);
+    }
+    let arena = parent.arena_len();
+    let mut fork = parent.fork();
+    assert_eq!(fork, parent);
+    assert_eq!(fork.adhash(), parent.adhash());
+    assert_eq!(fork.arena_len(), arena);
+    let before = parent.adhash();
+
+    fork.insert(1000, 1);
+    fork.remove(&0);
+    assert_eq!(parent.adhash(), before);
+    assert_eq!(parent.len(), 500);
+    assert_eq!(parent.get(&0), Some(&1));
+
+    let forked = fork.adhash();
+    parent.insert(2000, 2);
+    assert_eq!(fork.adhash(), forked);
+    assert!(!fork.contains_key(&2000));
+    assert_ne!(parent.adhash(), fork.adhash());
+}
+
+/// Forks written from two threads at once allocate into the same arenas;
+/// each keeps a well-formed trie with exactly its own writes.
+#[test]
+fn sync_forks_write_concurrently() {
+    let base: ChampMapSync<u32, u32> = (0..1_000).map(|i| (i, i)).collect();
+    let (mut a, mut b) = (base.fork(), base.fork());
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            for i in 0..5_000 {
+                a.insert(i, 1);
+            }
+        });
+        scope.spawn(|| {
+            for i in 0..500 {
+                b.remove(&i);
+            }
+            for i in 10_000..15_000 {
+                b.insert(i, 2);
+            }
+        });
+    });
+
+    assert_eq!(a.len(), 5_000);
+    assert!(a.iter().all(|(_, &v)| v == 1));
+    assert_eq!(b.len(), 5_500);
+    assert_eq!(b.get(&700), Some(&700));
+    assert_eq!(b.get(&12_345), Some(&2));
+    assert_eq!(base.len(), 1_000);
+    assert!(base.iter().all(|(k, v)| k == v));
+    for map in [&base, &a, &b] {
+        map.debug_invariants().unwrap();
+        assert!(map.verify_adhash());
+    }
+}
+
+/// Rolling back, clearing or consuming one fork leaves the other's nodes
+/// in place; once a map holds the arena alone, rollback frees memory again.
+#[test]
+fn sync_fork_survives_rollback_clear_and_into_iter() {
+    let mut parent: ChampMapSync<u32, String> = (0..300).map(|i| (i, i.to_string())).collect();
+    let cp = parent.checkpoint();
+    let mut fork = parent.fork();
+
+    fork.insert(1_000, "x".into());
+    parent.insert(2_000, "y".into());
+    parent.rollback(cp);
+    assert_eq!(parent.len(), 300);
+    assert_eq!(fork.get(&1_000).map(String::as_str), Some("x"));
+
+    let mut owned: Vec<_> = parent.clone().into_iter().collect();
+    owned.sort_unstable();
+    assert_eq!(owned.len(), 300);
+    let mut moved: Vec<_> = fork.clone().fork().into_iter().collect();
+    moved.sort_unstable();
+    assert_eq!(moved.len(), 301);
+
+    let mut consumed: Vec<_> = parent.into_iter().collect();
+    consumed.sort_unstable();
+    assert_eq!(consumed, owned);
+    assert_eq!(fork.len(), 301);
+    assert_eq!(fork.get(&7).map(String::as_str), Some("7"));
+
+    let grown = fork.arena_len();
+    fork.insert(3_000, "z".into());
+    fork.rollback(cp);
+    assert_eq!(fork.len(), 300);
+    assert!(fork.arena_len().1 < grown.1);
+
+    let other = fork.fork();
+    fork.clear();
+    assert!(fork.is_empty());
+    assert_eq!(other.len(), 300);
+    assert!(other.verify_adhash());
+}
+
+/// Reader threads share one frozen map through cheap clones.
+#[test]
+fn frozen_map_serves_concurrent_readers() {
+    let frozen = (0..10_000_u32)
+        .map(|i| (i, i * 2))
+        .collect::<ChampMapSync<u32, u32>>()
+        .freeze();
+    let adhash = frozen.adhash();
+
+    std::thread::scope(|scope| {
+        for t in 0..4 {
+            let reader = frozen.clone();
+            assert!(crate::FrozenChampMap::ptr_eq(&reader, &frozen));
+            scope.spawn(move || {
+                for i in (t..10_000).step_by(4) {
+                    assert_eq!(reader.get(&i), Some(&(i * 2)));
+                }
+                assert_eq!(reader.len(), 10_000);
+                assert_eq!(reader.iter().count(), 10_000);
+                assert_eq!(reader.adhash(), adhash);
+            });
+        }
+    });
+
+    // A writer thaws a private map over the same arena; the frozen version
+    // is unchanged.
+    let reader = frozen.clone();
+    let mut writer = frozen.thaw();
+    assert_eq!(writer.arena_len(), reader.arena_len());
+    writer.insert(10_000, 0);
+    let next = writer.freeze();
+    assert_eq!(reader.len(), 10_000);
+    assert_eq!(next.len(), 10_001);
+    assert_eq!(reader.adhash(), adhash);
+}
+
+/// A committed write is a new version; readers of the old one still see it.
+#[test]
+fn writer_commits_new_frozen_version() {
+    let a = (0..100_u32)
+        .map(|i| (i, i))
+        .collect::<ChampMapSync<u32, u32>>()
+        .freeze();
+    let reader = a.clone();
+    let arena = a.arena_len();
+
+    let mut writer = a.as_writer();
+    assert_eq!(writer.arena_len(), arena);
+    writer.insert(100, 100);
+    writer.remove(&0);
+    writer.insert(0, 0);
+    let b = writer.commit();
+
+    assert_eq!(reader.len(), 100);
+    assert!(!reader.contains_key(&100));
+    assert_eq!(b.len(), 101);
+    assert_eq!(b.get(&100), Some(&100));
+
+    let expected: ChampMapSync<u32, u32> = (0..=100).map(|i| (i, i)).collect();
+    assert_eq!(b.adhash(), expected.adhash());
+    assert_eq!(b.debug_invariants(), Ok(()));
+    assert!(!crate::FrozenChampMap::ptr_eq(&a, &b));
+}
+
+/// A writer appends to the frozen arena while readers on other threads
+/// keep reading the version it started from.
+#[test]
+fn writer_shares_arena_with_concurrent_readers() {
+    let a = (0..2_000_u32)
+        .map(|i| (i, i))
+        .collect::<ChampMapSync<u32, u32>>()
+        .freeze();
+    let adhash = a.adhash();
+
+    let b = std::thread::scope(|scope| {
+        for _ in 0..2 {
+            let reader = a.clone();
+            scope.spawn(move || {
+                for _ in 0..20 {
+                    assert!(reader.iter().all(|(k, v)| k == v));
+                    assert_eq!(reader.adhash(), adhash);
+                }
+            });
+        }
+        let mut writer = a.as_writer();
+        for i in 0..2_000 {
+            writer.insert(i, i + 1);
+        }
+        writer.commit()
+    });
+
+    assert_eq!(b.arena_len(), a.arena_len());
+    assert!(b.iter().all(|(k, v)| *v == k + 1));
+    assert!(a.iter().all(|(k, v)| k == v));
+    assert_eq!(b.debug_invariants(), Ok(()));
+}
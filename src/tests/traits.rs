@@ -1,10 +1,11 @@
 use crate::ChampMap;
+use crate::adhash::EMPTY;
 
 #[test]
 fn default_is_empty() {
     let map: ChampMap<i32, i32> = ChampMap::default();
     assert!(map.is_empty());
-    assert_eq!(map.adhash(), 0);
+    assert_eq!(map.adhash(), EMPTY);
 }
 
 #[test]
@@ -15,6 +16,19 @@ fn debug_format() {
     assert!(dbg.contains("len"));
 }
 
+#[test]
+fn debug_entries_lists_pairs() {
+    let map: ChampMap<&str, i32> = vec![("a", 1), ("b", 2)].into_iter().collect();
+    let dbg = format!("{:?}", map.debug_entries());
+    assert!(dbg.starts_with('{') && dbg.ends_with('}'));
+    assert!(dbg.contains("\"a\": 1"));
+    assert!(dbg.contains("\"b\": 2"));
+    assert_eq!(
+        format!("{:?}", ChampMap::<i32, i32>::new().debug_entries()),
+        "{}"
+    );
+}
+
 #[test]
 fn from_iterator() {
     let map: ChampMap<i32, i32> = vec![(1, 10), (2, 20), (3, 30)].into_iter().collect();
@@ -30,6 +44,25 @@ fn extend_trait() {
     assert_eq!(map.len(), 3);
 }
 
+#[test]
+fn extend_from_references() {
+    let mut dest: ChampMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+    let src: ChampMap<u32, u32> = (50..150).map(|i| (i, i + 1)).collect();
+    dest.extend(src.iter());
+
+    let expected: ChampMap<u32, u32> = (0..50)
+        .map(|i| (i, i))
+        .chain((50..150).map(|i| (i, i + 1)))
+        .collect();
+    assert_eq!(dest.len(), 150);
+    assert_eq!(dest.adhash(), expected.adhash());
+    assert_eq!(src.len(), 100);
+
+    let mut sync: crate::ChampMapSync<u32, u32> = crate::ChampMapSync::new();
+    sync.extend(&src);
+    assert_eq!(sync.adhash(), src.adhash());
+}
+
 #[test]
 fn index_existing() {
     let mut map = ChampMap::new();
@@ -43,3 +76,94 @@ fn index_missing_panics() {
     let map: ChampMap<i32, i32> = ChampMap::new();
     let _ = map[&999];
 }
+
+#[test]
+fn eq_ignores_insertion_order() {
+    let a: ChampMap<i32, i32> = (0..100).map(|i| (i, i * 2)).collect();
+    let b: ChampMap<i32, i32> = (0..100).rev().map(|i| (i, i * 2)).collect();
+    assert_eq!(a, b);
+}
+
+#[test]
+fn ne_on_different_value() {
+    let a: ChampMap<i32, i32> = vec![(1, 10), (2, 20)].into_iter().collect();
+    let b: ChampMap<i32, i32> = vec![(1, 10), (2, 21)].into_iter().collect();
+    assert_ne!(a, b);
+}
+
+#[test]
+fn ne_on_different_len() {
+    let a: ChampMap<i32, i32> = vec![(1, 10)].into_iter().collect();
+    let b: ChampMap<i32, i32> = vec![(1, 10), (2, 20)].into_iter().collect();
+    assert_ne!(a, b);
+}
+
+#[test]
+fn hash_set_dedups_equal_maps() {
+    use std::collections::HashSet;
+
+    let a: ChampMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+    let b: ChampMap<u32, u32> = (0..100).rev().map(|i| (i, i)).collect();
+    let c: ChampMap<u32, u32> = (0..100).map(|i| (i, i + 1)).collect();
+    let empty = ChampMap::new();
+
+    let set: HashSet<ChampMap<u32, u32>> = [a, b, c, empty.clone(), empty].into_iter().collect();
+    assert_eq!(set.len(), 3);
+}
+
+#[test]
+fn btree_set_orders_by_sorted_contents() {
+    use std::collections::BTreeSet;
+
+    let map = |pairs: &[(i32, i32)]| pairs.iter().copied().collect::<ChampMap<i32, i32>>();
+    let a = map(&[(1, 1), (2, 2)]);
+    let a_rev = map(&[(2, 2), (1, 1)]);
+    let b = map(&[(1, 1), (2, 3)]);
+    let c = map(&[(1, 1)]);
+    let d = map(&[(0, 9)]);
+
+    let set: BTreeSet<ChampMap<i32, i32>> = [
+        b.clone(),
+        a.clone(),
+        d.clone(),
+        a_rev,
+        c.clone(),
+        ChampMap::new(),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(set.len(), 5);
+    let order: Vec<_> = set.into_iter().collect();
+    assert_eq!(order, [ChampMap::new(), d, c, a, b]);
+}
+
+#[test]
+fn eq_std_hash_map() {
+    use std::collections::HashMap;
+
+    let pairs: Vec<(u32, String)> = (0..100).map(|i| (i, i.to_string())).collect();
+    let map: ChampMap<u32, String> = pairs.iter().cloned().collect();
+    let mut expected: HashMap<u32, String> = pairs.into_iter().collect();
+    assert_eq!(map, expected);
+    assert_eq!(expected, map);
+
+    expected.insert(7, "seven".to_string());
+    assert_ne!(map, expected);
+    expected.insert(7, "7".to_string());
+    expected.insert(100, "100".to_string());
+    assert_ne!(expected, map);
+}
+
+#[test]
+fn from_std_maps_round_trip() {
+    use std::collections::{BTreeMap, HashMap};
+
+    let source: HashMap<u32, String> = (0..500).map(|i| (i, i.to_string())).collect();
+    let map = ChampMap::from(source.clone());
+    let sorted = ChampMap::from(source.clone().into_iter().collect::<BTreeMap<_, _>>());
+    assert_eq!(map.adhash(), sorted.adhash());
+    assert_eq!(map, sorted);
+
+    let back: HashMap<u32, String> = map.into();
+    assert_eq!(back, source);
+}
@@ -0,0 +1,146 @@
+use crate::adhash::EMPTY;
+use crate::{ChampMap, ChampMapSync};
+
+#[test]
+fn map_values_in_place_matches_rebuilt() {
+    let mut map: ChampMap<u64, u64> = (0..2_000).map(|i| (i, i)).collect();
+    map.map_values_in_place(|k, v| *v = *v * 3 + k % 7);
+
+    let expected: ChampMap<u64, u64> = (0..2_000).map(|i| (i, i * 3 + i % 7)).collect();
+    assert_eq!(map.len(), 2_000);
+    assert_eq!(map.adhash(), expected.adhash());
+    assert_eq!(map, expected);
+}
+
+#[test]
+fn map_values_in_place_empty_map() {
+    let mut map: ChampMap<u64, u64> = ChampMap::new();
+    map.map_values_in_place(|_, v| *v += 1);
+    assert!(map.is_empty());
+    assert_eq!(map.adhash(), EMPTY);
+}
+
+#[test]
+fn map_values_in_place_rollback() {
+    let mut map: ChampMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    let before = map.clone();
+    let cp = map.checkpoint();
+    map.map_values_in_place(|_, v| *v = 0);
+    assert_eq!(map.get(&50), Some(&0));
+    map.rollback(cp);
+    assert_eq!(map, before);
+    assert_eq!(map.adhash(), before.adhash());
+}
+
+#[test]
+fn sync_map_values_in_place() {
+    let mut map: ChampMapSync<u64, u64> = (0..300).map(|i| (i, i)).collect();
+    map.map_values_in_place(|_, v| *v *= 2);
+    let expected: ChampMapSync<u64, u64> = (0..300).map(|i| (i, i * 2)).collect();
+    assert_eq!(map, expected);
+}
+
+#[test]
+fn map_values_changes_value_type() {
+    let counts: ChampMap<&str, u32> = [("a", 1), ("b", 3), ("c", 4)].into_iter().collect();
+    let total = f64::from(counts.values().sum::<u32>());
+    let freq = counts.map_values(|c| (f64::from(*c) / total).to_bits());
+
+    let expected: ChampMap<&str, u64> = [("a", 0.125), ("b", 0.375), ("c", 0.5)]
+        .into_iter()
+        .map(|(k, f)| (k, f64::to_bits(f)))
+        .collect();
+    assert_eq!(freq.len(), 3);
+    assert_eq!(freq.adhash(), expected.adhash());
+    assert_eq!(freq, expected);
+}
+
+/// The output keeps the input's trie shape: a map with no dead COW copies
+/// maps to one with identical arena sizes.
+#[test]
+fn map_values_preserves_structure() {
+    let built: ChampMap<u64, u64> = (0..5_000).map(|i| (i, i)).collect();
+    let compact = built.map_values(|v| *v);
+    assert_eq!(compact, built);
+
+    let mapped = compact.map_values(ToString::to_string);
+    assert_eq!(mapped.arena_len(), compact.arena_len());
+    assert_eq!(mapped.len(), compact.len());
+    assert_eq!(mapped.get(&4_321).map(String::as_str), Some("4321"));
+
+    let expected: ChampMap<u64, String> = (0..5_000).map(|i| (i, i.to_string())).collect();
+    assert_eq!(mapped.adhash(), expected.adhash());
+}
+
+#[test]
+fn sync_map_values() {
+    let map: ChampMapSync<u64, u64> = (0..300).map(|i| (i, i)).collect();
+    let mapped = map.map_values(|v| v % 2 == 0);
+    let expected: ChampMapSync<u64, bool> = (0..300).map(|i| (i, i % 2 == 0)).collect();
+    assert_eq!(mapped, expected);
+}
+
+#[test]
+fn modify_many_matches_repeated_inserts() {
+    let base: ChampMap<u64, u64> = (0..10_000).map(|i| (i, i)).collect();
+    // Every seventh key, two absent keys, and one key given twice.
+    let updates: Vec<(u64, u64)> = (0..10_000)
+        .step_by(7)
+        .map(|k| (k, k + 1))
+        .chain([(20_000, 0), (7, 100), (30_000, 0)])
+        .collect();
+
+    let mut batched = base.clone();
+    let mut inserted = base.clone();
+    assert_eq!(batched.modify_many(updates.iter().copied()), 1_429);
+    for &(k, v) in &updates {
+        if inserted.contains_key(&k) {
+            inserted.insert(k, v);
+        }
+    }
+
+    assert_eq!(batched.debug_invariants(), Ok(()));
+    assert_eq!(batched.len(), 10_000);
+    assert_eq!(batched.get(&7), Some(&100));
+    assert_eq!(batched.adhash(), inserted.adhash());
+    assert_eq!(batched, inserted);
+
+    let grown = |m: &ChampMap<u64, u64>| m.arena_len().0 - base.arena_len().0;
+    assert!(grown(&batched) < grown(&inserted));
+
+    // Nothing to apply: the trie is left as it was.
+    let before = batched.arena_len();
+    assert_eq!(batched.modify_many([(50_000, 1)]), 0);
+    assert_eq!(batched.arena_len(), before);
+}
+
+#[test]
+fn intern_values_shares_equal_values() {
+    use std::sync::Arc;
+
+    let configs = ["alpha", "beta", "gamma", "delta", "epsilon"];
+    let build = || -> ChampMap<u32, Arc<String>> {
+        (0..10_000)
+            .map(|i| (i, Arc::new(configs[i as usize % 5].repeat(100))))
+            .collect()
+    };
+    let mut map = build();
+    let before = build();
+    let distinct_allocations = |m: &ChampMap<u32, Arc<String>>| {
+        let mut ptrs: Vec<*const String> = m.values().map(Arc::as_ptr).collect();
+        ptrs.sort_unstable();
+        ptrs.dedup();
+        ptrs.len()
+    };
+    assert_eq!(distinct_allocations(&map), 10_000);
+
+    assert_eq!(map.intern_values(), 5);
+    map.compact();
+    assert_eq!(distinct_allocations(&map), 5);
+    // 10k heap strings of ~500 bytes shrink to five, shared.
+    assert_eq!(Arc::strong_count(map.get(&0).unwrap()), 2_000);
+
+    assert_eq!(map.adhash(), before.adhash());
+    assert_eq!(map, before);
+    assert_eq!(map.debug_invariants(), Ok(()));
+}
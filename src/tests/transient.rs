@@ -0,0 +1,184 @@
+use crate::adhash::EMPTY;
+use crate::transient::Transient;
+use crate::{ChampMap, ChampMapSync};
+
+fn cow_built(n: u64) -> ChampMap<u64, u64> {
+    let mut map = ChampMap::new();
+    for i in 0..n {
+        map.insert(i, i * 3);
+    }
+    map
+}
+
+#[test]
+fn freeze_matches_cow_build() {
+    let mut t = Transient::new();
+    for i in 0_u64..10_000 {
+        t.insert(i, i * 3);
+    }
+    assert_eq!(t.len(), 10_000);
+    let frozen = t.freeze();
+    let cow = cow_built(10_000);
+
+    assert_eq!(frozen.len(), cow.len());
+    assert_eq!(frozen.adhash(), cow.adhash());
+    assert_eq!(frozen, cow);
+}
+
+/// The frozen trie holds exactly the live set of the COW-built trie.
+#[test]
+fn freeze_allocates_live_set_only() {
+    let frozen: ChampMap<u64, u64> = (0..10_000).map(|i| (i, i * 3)).collect();
+    let live = cow_built(10_000).map_values(|v| *v);
+    assert_eq!(frozen.arena_len(), live.arena_len());
+    assert_eq!(frozen.arena_len().1, 10_000);
+}
+
+#[test]
+fn transient_insert_replaces() {
+    let mut t = Transient::new();
+    assert_eq!(t.insert("a", 1), None);
+    assert_eq!(t.insert("b", 2), None);
+    assert_eq!(t.insert("a", 10), Some(1));
+    assert_eq!(t.len(), 2);
+
+    let frozen = t.freeze();
+    let mut expected = ChampMap::new();
+    expected.insert("b", 2);
+    expected.insert("a", 10);
+    assert_eq!(frozen.adhash(), expected.adhash());
+    assert_eq!(frozen, expected);
+}
+
+#[test]
+fn empty_transient_freezes_to_empty_map() {
+    let t: Transient<u64, u64> = Transient::new();
+    assert!(t.is_empty());
+    let map = t.freeze();
+    assert!(map.is_empty());
+    assert_eq!(map.adhash(), EMPTY);
+    assert_eq!(map.arena_len(), (0, 0, 0));
+}
+
+#[test]
+fn frozen_map_supports_cow_mutation() {
+    let mut map: ChampMap<u64, u64> = (0..1_000).map(|i| (i, i)).collect();
+    let cp = map.checkpoint();
+    map.insert(5_000, 1);
+    map.remove(&7);
+    assert_eq!(map.len(), 1_000);
+    map.rollback(cp);
+    assert_eq!(map, (0..1_000).map(|i| (i, i)).collect::<ChampMap<_, _>>());
+}
+
+#[test]
+fn freeze_sync_matches_cow_build() {
+    let mut t = Transient::new();
+    t.extend((0_u64..2_000).map(|i| (i, i * 3)));
+    let frozen: ChampMapSync<u64, u64> = t.freeze_sync();
+
+    let mut cow = ChampMapSync::new();
+    for i in 0_u64..2_000 {
+        cow.insert(i, i * 3);
+    }
+    assert_eq!(frozen.adhash(), cow.adhash());
+    assert_eq!(frozen, cow);
+}
+
+#[test]
+fn insert_many_matches_sequential_inserts() {
+    let base: ChampMap<u64, u64> = (0..10_000).map(|i| (i, i)).collect();
+    let pairs: Vec<(u64, u64)> = (5_000..15_000).map(|i| (i, i * 2)).collect();
+
+    let mut batched = base.clone();
+    let added = batched.insert_many(pairs.iter().copied());
+    let mut looped = base.clone();
+    for (k, v) in &pairs {
+        looped.insert(*k, *v);
+    }
+
+    assert_eq!(added, 5_000);
+    assert_eq!(batched.len(), 15_000);
+    assert_eq!(batched.adhash(), looped.adhash());
+    assert_eq!(batched, looped);
+    assert_eq!(batched.debug_invariants(), Ok(()));
+
+    // Narrower nodes make each looped path copy cheaper.
+    let factor = if cfg!(feature = "branch-16") { 2 } else { 3 };
+    let grown = |m: &ChampMap<u64, u64>| m.arena_len().1 - base.arena_len().1;
+    assert!(
+        grown(&batched) * factor < grown(&looped),
+        "batched {} vs looped {} entry slots",
+        grown(&batched),
+        grown(&looped)
+    );
+}
+
+#[test]
+fn insert_many_into_empty_and_with_repeats() {
+    let mut map: ChampMap<u64, u64> = ChampMap::new();
+    assert_eq!(map.insert_many((0..100).map(|i| (i % 10, i))), 10);
+    let expected: ChampMap<u64, u64> = (90..100).map(|i| (i % 10, i)).collect();
+    assert_eq!(map, expected);
+
+    assert_eq!(map.insert_many(std::iter::empty()), 0);
+    assert_eq!(map.insert_many([(0, 0), (10, 10)]), 1);
+    assert_eq!(map.get(&0), Some(&0));
+    assert!(map.verify_adhash());
+}
+
+#[test]
+fn insert_or_merge_folds_in_place() {
+    let mut t = Transient::new();
+    t.insert_or_merge("a", vec![1], |v, mut more| v.append(&mut more));
+    t.insert_or_merge("b", vec![2], |v, mut more| v.append(&mut more));
+    t.insert_or_merge("a", vec![3], |v, mut more| v.append(&mut more));
+    assert_eq!(t.len(), 2);
+
+    let frozen = t.freeze();
+    let mut expected = ChampMap::new();
+    expected.insert("a", vec![1, 3]);
+    expected.insert("b", vec![2]);
+    assert_eq!(frozen.adhash(), expected.adhash());
+    assert_eq!(frozen, expected);
+}
+
+#[test]
+fn count_occurrences_is_order_independent() {
+    let stream: Vec<u64> = (0..20_000).map(|i| (i * i) % 1_009).collect();
+    let mut shuffled = stream.clone();
+    let mut state = 0x2545_F491_4F6C_DD1D_u64;
+    for i in (1..shuffled.len()).rev() {
+        // xorshift64, then Fisher–Yates
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let j = usize::try_from(state % (i as u64 + 1)).expect("fits");
+        shuffled.swap(i, j);
+    }
+    assert_ne!(stream, shuffled);
+
+    let counts = ChampMap::count_occurrences(stream.iter().copied());
+    let shuffled_counts = ChampMap::count_occurrences(shuffled);
+    assert_eq!(counts.adhash(), shuffled_counts.adhash());
+    assert_eq!(counts, shuffled_counts);
+    assert_eq!(counts.values().sum::<usize>(), stream.len());
+    assert_eq!(counts[&0], stream.iter().filter(|&&k| k == 0).count());
+    assert!(counts.verify_adhash());
+
+    let sync = ChampMapSync::count_occurrences(stream);
+    assert_eq!(sync.adhash(), counts.adhash());
+}
+
+#[test]
+fn from_unique_unchecked_matches_from_iter() {
+    let pairs = || (0_u64..10_000).map(|i| (i, i * 3));
+    let bulk = ChampMap::<u64, u64>::from_unique_unchecked(pairs());
+    let collected: ChampMap<u64, u64> = pairs().collect();
+
+    assert_eq!(bulk.debug_invariants(), Ok(()));
+    assert_eq!(bulk.len(), collected.len());
+    assert_eq!(bulk.adhash(), collected.adhash());
+    assert_eq!(bulk, collected);
+    assert_eq!(bulk.arena_len(), collected.arena_len());
+}
@@ -0,0 +1,78 @@
+//! Scoped speculative editing.
+//!
+//! A [`Transaction`] takes a checkpoint when it is created and rolls the map
+//! back to it when dropped, unless [`commit`](Transaction::commit) was
+//! called first. Early returns, `?` and panics therefore all discard the
+//! edits made through the guard.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use crate::ChampCheckpoint;
+use crate::adhash::DefaultHashBuilder;
+use crate::arena::ChampArena;
+use crate::map::ChampMapGeneric;
+use crate::store::ChampStore;
+
+/// Guard over a map's speculative edits, created by
+/// [`ChampMap::transaction`](crate::ChampMap::transaction).
+///
+/// Derefs to the map, so every read and write goes through it. Dropping the
+/// guard without calling [`commit`](Self::commit) rolls the map back to its
+/// state at creation, releasing the arena items the edits allocated. The
+/// edits must not [`compact`](ChampMapGeneric::compact) or
+/// [`clear`](ChampMapGeneric::clear) the map, or roll it back past the
+/// guard's checkpoint: the rollback on drop would then panic.
+pub struct Transaction<'a, K, V, St = ChampArena<K, V>, S = DefaultHashBuilder>
+where
+    St: ChampStore<K, V>,
+{
+    map: &'a mut ChampMapGeneric<K, V, St, S>,
+    checkpoint: Option<ChampCheckpoint<K, V>>,
+}
+
+impl<'a, K, V, St: ChampStore<K, V>, S> Transaction<'a, K, V, St, S> {
+    pub(crate) fn new(map: &'a mut ChampMapGeneric<K, V, St, S>) -> Self {
+        let checkpoint = Some(map.checkpoint());
+        Self { map, checkpoint }
+    }
+
+    /// Keeps the edits made through the guard.
+    pub fn commit(mut self) {
+        self.checkpoint = None;
+    }
+
+    /// Discards the edits made through the guard. Same as dropping it.
+    pub fn abort(self) {}
+}
+
+impl<K, V, St: ChampStore<K, V>, S> Deref for Transaction<'_, K, V, St, S> {
+    type Target = ChampMapGeneric<K, V, St, S>;
+
+    fn deref(&self) -> &Self::Target {
+        self.map
+    }
+}
+
+impl<K, V, St: ChampStore<K, V>, S> DerefMut for Transaction<'_, K, V, St, S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.map
+    }
+}
+
+impl<K, V, St: ChampStore<K, V>, S> Drop for Transaction<'_, K, V, St, S> {
+    fn drop(&mut self) {
+        if let Some(cp) = self.checkpoint.take() {
+            self.map.rollback(cp);
+        }
+    }
+}
+
+impl<K, V, St: ChampStore<K, V>, S> fmt::Debug for Transaction<'_, K, V, St, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transaction")
+            .field("map", &self.map)
+            .field("committed", &self.checkpoint.is_none())
+            .finish()
+    }
+}
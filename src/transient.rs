@@ -0,0 +1,455 @@
+//! Transient builder — in-place trie construction without COW.
+//!
+//! A [`Transient`] owns its nodes directly and mutates them in place, so
+//! building a map leaves no dead path copies behind. Freezing allocates each
+//! live node, entry block and children block exactly once, bottom-up, into a
+//! fresh arena. The frozen trie has the same canonical shape and `AdHash` as
+//! one built by repeated [`ChampMap::insert`](crate::ChampMap::insert).
+
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+
+use safe_bump::Idx;
+
+use crate::ChampMap;
+use crate::ChampMapSync;
+use crate::adhash::{self, DefaultHashBuilder};
+use crate::node::{self, Entry, Node};
+use crate::ops::insert::alloc_or_sentinel;
+use crate::store::ChampStore;
+
+/// Mutable map under construction, frozen into a [`ChampMap`] or
+/// [`ChampMapSync`] once complete.
+///
+/// Keys and values are hashed with `S`, which the frozen map inherits.
+pub struct Transient<K, V, S = DefaultHashBuilder> {
+    root: Option<TNode<K, V>>,
+    size: usize,
+    adhash: u64,
+    hasher: S,
+}
+
+/// A frozen builder's `(root, len, adhash, hasher)`.
+pub(crate) type Frozen<K, V, S> = (Option<Idx<Node<K, V>>>, usize, u64, S);
+
+/// Owned, in-place mutable counterpart of [`Node`].
+enum TNode<K, V> {
+    Inner {
+        data_map: u32,
+        node_map: u32,
+        entries: Vec<Entry<K, V>>,
+        children: Vec<Self>,
+        adhash: u64,
+    },
+    Collision {
+        hash: u64,
+        entries: Vec<Entry<K, V>>,
+        adhash: u64,
+    },
+}
+
+// ---------------------------------------------------------------------------
+// Construction & accessors — no trait bounds
+// ---------------------------------------------------------------------------
+
+impl<K, V> Transient<K, V> {
+    /// Creates an empty builder.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::new())
+    }
+}
+
+impl<K, V, S> Transient<K, V, S> {
+    /// Creates an empty builder that hashes keys and values with `hasher`.
+    #[must_use]
+    pub const fn with_hasher(hasher: S) -> Self {
+        Self {
+            root: None,
+            size: 0,
+            adhash: adhash::EMPTY,
+            hasher,
+        }
+    }
+
+    /// Returns the number of key-value pairs inserted so far.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if nothing has been inserted.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Freezes the builder into a single-threaded map.
+    #[must_use]
+    pub fn freeze(self) -> ChampMap<K, V, S> {
+        ChampMap::from(self)
+    }
+
+    /// Freezes the builder into a thread-safe map.
+    #[must_use]
+    pub fn freeze_sync(self) -> ChampMapSync<K, V, S> {
+        ChampMapSync::from(self)
+    }
+
+    /// Allocates the trie into `store`, reserving the exact room it needs
+    /// first: one node per owned node, one child slot per non-root node.
+    pub(crate) fn freeze_into<T: ChampStore<K, V>>(self, store: &mut T) -> Frozen<K, V, S> {
+        if let Some(root) = &self.root {
+            let nodes = count_nodes(root);
+            store.reserve(nodes, self.size, nodes - 1);
+        }
+        let root = self.root.map(|root| freeze_node(store, root));
+        (root, self.size, self.adhash, self.hasher)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Write operations — K: Hash + Eq, V: Hash
+// ---------------------------------------------------------------------------
+
+impl<K: Hash + Eq, V: Hash, S: BuildHasher> Transient<K, V, S> {
+    /// Inserts a key-value pair in place.
+    ///
+    /// Returns `None` if the key was new, or `Some(old_value)` if an existing
+    /// value was replaced.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_entry(key, value, |old, new| mem::replace(old, new))
+    }
+
+    /// Inserts `value` if `key` is absent; otherwise folds it into the
+    /// stored value in place with `merge(&mut stored, value)`.
+    ///
+    /// Handy for accumulating, e.g. counting with `|n, one| *n += one`.
+    pub fn insert_or_merge<F: FnOnce(&mut V, V)>(&mut self, key: K, value: V, merge: F) {
+        self.insert_entry(key, value, merge);
+    }
+
+    /// Inserts a pair whose key is known to be absent, without comparing
+    /// keys.
+    ///
+    /// Skips the equality probe [`insert`](Self::insert) makes wherever the
+    /// new key meets a stored one. If the key is in fact present, both
+    /// entries are kept: no memory unsafety, but the map is logically
+    /// corrupt — its `len` over-counts and lookups see only one of them.
+    pub fn insert_unique(&mut self, key: K, value: V) {
+        let hash = self.hasher.hash_one(&key);
+        let entry = Entry { hash, key, value };
+        let delta = contribution(&self.hasher, &entry);
+        if let Some(root) = &mut self.root {
+            push_node(&self.hasher, root, entry, 0, delta);
+        } else {
+            self.root = Some(TNode::Inner {
+                data_map: node::mask(node::fragment(hash, 0)),
+                node_map: 0,
+                entries: vec![entry],
+                children: Vec::new(),
+                adhash: delta,
+            });
+        }
+        self.adhash = adhash::combine(self.adhash, delta);
+        self.size += 1;
+    }
+
+    /// Shared driver for [`insert`](Self::insert) and
+    /// [`insert_or_merge`](Self::insert_or_merge): returns `combine`'s result
+    /// if the key was present.
+    fn insert_entry<R, F>(&mut self, key: K, value: V, combine: F) -> Option<R>
+    where
+        F: FnOnce(&mut V, V) -> R,
+    {
+        let hash = self.hasher.hash_one(&key);
+        let entry = Entry { hash, key, value };
+
+        let (delta, old) = if let Some(root) = &mut self.root {
+            insert_node(&self.hasher, root, entry, 0, combine)
+        } else {
+            let contribution = contribution(&self.hasher, &entry);
+            self.root = Some(TNode::Inner {
+                data_map: node::mask(node::fragment(hash, 0)),
+                node_map: 0,
+                entries: vec![entry],
+                children: Vec::new(),
+                adhash: contribution,
+            });
+            (contribution, None)
+        };
+        self.adhash = adhash::combine(self.adhash, delta);
+        if old.is_none() {
+            self.size += 1;
+        }
+        old
+    }
+}
+
+impl<K, V, S: Default> Default for Transient<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Hash + Eq, V: Hash, S: BuildHasher> Extend<(K, V)> for Transient<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V, S> fmt::Debug for Transient<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transient")
+            .field("len", &self.size)
+            .field("adhash", &format_args!("{:#018x}", self.adhash))
+            .finish_non_exhaustive()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// In-place insert
+// ---------------------------------------------------------------------------
+
+/// Inserts `entry` into `node`, mirroring the COW insert's shape decisions.
+/// An existing entry's value is updated with `combine(&mut old, new)`.
+/// Returns the wrapping `AdHash` delta and `combine`'s result, if it ran.
+fn insert_node<K, V, H, R, F>(
+    hasher: &H,
+    node: &mut TNode<K, V>,
+    entry: Entry<K, V>,
+    shift: u32,
+    combine: F,
+) -> (u64, Option<R>)
+where
+    K: Eq,
+    V: Hash,
+    H: BuildHasher,
+    F: FnOnce(&mut V, V) -> R,
+{
+    match node {
+        TNode::Inner {
+            data_map,
+            node_map,
+            entries,
+            children,
+            adhash,
+        } => {
+            let bit = node::mask(node::fragment(entry.hash, shift));
+            let (delta, old) = if *data_map & bit != 0 {
+                let pos = node::index(*data_map, bit);
+                if entries[pos].hash == entry.hash && entries[pos].key == entry.key {
+                    // Same key → update value.
+                    combine_value(hasher, &mut entries[pos], entry.value, combine)
+                } else {
+                    // Different key at same position → push both into a subtree.
+                    let delta = contribution(hasher, &entry);
+                    let existing = entries.remove(pos);
+                    *data_map &= !bit;
+                    *node_map |= bit;
+                    let child = pair(hasher, existing, entry, shift + node::BITS_PER_LEVEL);
+                    children.insert(node::index(*node_map, bit), child);
+                    (delta, None)
+                }
+            } else if *node_map & bit != 0 {
+                let pos = node::index(*node_map, bit);
+                insert_node(
+                    hasher,
+                    &mut children[pos],
+                    entry,
+                    shift + node::BITS_PER_LEVEL,
+                    combine,
+                )
+            } else {
+                let delta = contribution(hasher, &entry);
+                *data_map |= bit;
+                entries.insert(node::index(*data_map, bit), entry);
+                (delta, None)
+            };
+            *adhash = adhash::combine(*adhash, delta);
+            (delta, old)
+        }
+        TNode::Collision {
+            entries, adhash, ..
+        } => {
+            let (delta, old) = if let Some(e) = entries.iter_mut().find(|e| e.key == entry.key) {
+                combine_value(hasher, e, entry.value, combine)
+            } else {
+                let delta = contribution(hasher, &entry);
+                entries.push(entry);
+                (delta, None)
+            };
+            *adhash = adhash::combine(*adhash, delta);
+            (delta, old)
+        }
+    }
+}
+
+/// Adds `entry`, known to be new, to `node` without comparing keys. Its
+/// `AdHash` contribution `delta` is added along the way down.
+fn push_node<K, V: Hash, H: BuildHasher>(
+    hasher: &H,
+    node: &mut TNode<K, V>,
+    entry: Entry<K, V>,
+    shift: u32,
+    delta: u64,
+) {
+    match node {
+        TNode::Inner {
+            data_map,
+            node_map,
+            entries,
+            children,
+            adhash,
+        } => {
+            *adhash = adhash::combine(*adhash, delta);
+            let bit = node::mask(node::fragment(entry.hash, shift));
+            if *data_map & bit != 0 {
+                let existing = entries.remove(node::index(*data_map, bit));
+                *data_map &= !bit;
+                *node_map |= bit;
+                let child = pair(hasher, existing, entry, shift + node::BITS_PER_LEVEL);
+                children.insert(node::index(*node_map, bit), child);
+            } else if *node_map & bit != 0 {
+                let pos = node::index(*node_map, bit);
+                push_node(
+                    hasher,
+                    &mut children[pos],
+                    entry,
+                    shift + node::BITS_PER_LEVEL,
+                    delta,
+                );
+            } else {
+                *data_map |= bit;
+                entries.insert(node::index(*data_map, bit), entry);
+            }
+        }
+        TNode::Collision {
+            entries, adhash, ..
+        } => {
+            *adhash = adhash::combine(*adhash, delta);
+            entries.push(entry);
+        }
+    }
+}
+
+/// Updates the value of `e` with `combine`, returning the `AdHash` delta and
+/// `combine`'s result.
+fn combine_value<K, V, H, R, F>(
+    hasher: &H,
+    e: &mut Entry<K, V>,
+    value: V,
+    combine: F,
+) -> (u64, Option<R>)
+where
+    V: Hash,
+    H: BuildHasher,
+    F: FnOnce(&mut V, V) -> R,
+{
+    let old_contribution = contribution(hasher, e);
+    let out = combine(&mut e.value, value);
+    (
+        adhash::difference(contribution(hasher, e), old_contribution),
+        Some(out),
+    )
+}
+
+/// Builds the subtree for two entries whose fragments match above `shift`.
+///
+/// Owned counterpart of [`create_subtree`](crate::ops::insert::create_subtree).
+fn pair<K, V: Hash, H: BuildHasher>(
+    hasher: &H,
+    e1: Entry<K, V>,
+    e2: Entry<K, V>,
+    shift: u32,
+) -> TNode<K, V> {
+    let adhash = adhash::combine(contribution(hasher, &e1), contribution(hasher, &e2));
+    if shift > node::MAX_SHIFT {
+        return TNode::Collision {
+            hash: e1.hash,
+            entries: vec![e1, e2],
+            adhash,
+        };
+    }
+
+    let f1 = node::fragment(e1.hash, shift);
+    let f2 = node::fragment(e2.hash, shift);
+    if f1 == f2 {
+        TNode::Inner {
+            data_map: 0,
+            node_map: node::mask(f1),
+            entries: Vec::new(),
+            children: vec![pair(hasher, e1, e2, shift + node::BITS_PER_LEVEL)],
+            adhash,
+        }
+    } else {
+        TNode::Inner {
+            data_map: node::mask(f1) | node::mask(f2),
+            node_map: 0,
+            entries: if f1 < f2 { vec![e1, e2] } else { vec![e2, e1] },
+            children: Vec::new(),
+            adhash,
+        }
+    }
+}
+
+/// Returns the `AdHash` contribution of an entry.
+fn contribution<K, V: Hash, H: BuildHasher>(hasher: &H, e: &Entry<K, V>) -> u64 {
+    adhash::entry_adhash(e.hash, hasher.hash_one(&e.value))
+}
+
+// ---------------------------------------------------------------------------
+// Freeze
+// ---------------------------------------------------------------------------
+
+/// Returns the number of nodes in the subtree rooted at `node`.
+fn count_nodes<K, V>(node: &TNode<K, V>) -> usize {
+    match node {
+        TNode::Inner { children, .. } => 1 + children.iter().map(count_nodes).sum::<usize>(),
+        TNode::Collision { .. } => 1,
+    }
+}
+
+/// Allocates `node` and its subtree into `store`, children first.
+fn freeze_node<K, V, S: ChampStore<K, V>>(store: &mut S, node: TNode<K, V>) -> Idx<Node<K, V>> {
+    match node {
+        TNode::Inner {
+            data_map,
+            node_map,
+            entries,
+            children,
+            adhash,
+        } => {
+            let children: Vec<Idx<Node<K, V>>> = children
+                .into_iter()
+                .map(|child| freeze_node(store, child))
+                .collect();
+            let data_start = alloc_or_sentinel(store.alloc_entries(entries));
+            let children_start = alloc_or_sentinel(store.alloc_children(children));
+            store.alloc_node(Node::Inner {
+                data_map,
+                node_map,
+                data_start,
+                children_start,
+                adhash,
+            })
+        }
+        TNode::Collision {
+            hash,
+            entries,
+            adhash,
+        } => {
+            let entries_len = u32::try_from(entries.len()).expect("collision node overflow");
+            let entries_start = store.alloc_entries(entries).expect("non-empty");
+            store.alloc_node(Node::Collision {
+                hash,
+                entries_start,
+                entries_len,
+                adhash,
+            })
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! Write guard for in-place value mutation.
+
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::ops::{Deref, DerefMut};
+
+use safe_bump::Idx;
+
+use crate::adhash::{self, DefaultHashBuilder};
+use crate::arena::ChampArena;
+use crate::node::{Entry, Node};
+use crate::ops::insert::{self, insert_recursive};
+use crate::ops::path::{self, Path};
+use crate::ops::transform;
+use crate::store::ChampStore;
+
+/// Mutable borrow of the fields of a map that a deferred write touches.
+pub(crate) struct MapParts<'a, K, V, S, H> {
+    pub store: &'a mut S,
+    pub root: &'a mut Option<Idx<Node<K, V>>>,
+    pub size: &'a mut usize,
+    pub adhash: &'a mut u64,
+    pub hasher: &'a H,
+}
+
+/// Where the guarded value is written back on drop.
+enum Slot<K, V> {
+    /// Existing entry at position `pos` within the data block of `node`.
+    Occupied { node: Idx<Node<K, V>>, pos: usize },
+    /// New entry, inserted at the path's target node.
+    Vacant { hash: u64, key: K },
+}
+
+/// Mutable access to a single value, returned by
+/// [`ChampMap::get_mut`](crate::ChampMap::get_mut) and the
+/// [`Entry`](crate::entry::Entry) API.
+///
+/// The guard holds a private copy of the value. On drop, the copy is written
+/// back via COW path-copy and the map's `AdHash` is corrected by the entry's
+/// contribution delta. An existing entry is only rewritten if the value was
+/// mutably dereferenced. Nodes reachable from earlier checkpoints are never
+/// modified, so rollback remains valid.
+pub struct ValueMut<'a, K, V, S = ChampArena<K, V>, H = DefaultHashBuilder>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    parts: MapParts<'a, K, V, S, H>,
+    path: Option<Path<K, V>>,
+    pending: Option<(Slot<K, V>, V)>,
+    dirty: bool,
+}
+
+impl<'a, K, V, S, H> ValueMut<'a, K, V, S, H>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    /// Creates a guard for the existing entry at `pos` within `path.node`.
+    pub(crate) fn occupied(parts: MapParts<'a, K, V, S, H>, path: Path<K, V>, pos: usize) -> Self {
+        let value = parts
+            .store
+            .get_entry(path::entry_at(parts.store, path.node, pos))
+            .value
+            .clone();
+        let node = path.node;
+        Self {
+            parts,
+            path: Some(path),
+            pending: Some((Slot::Occupied { node, pos }, value)),
+            dirty: false,
+        }
+    }
+
+    /// Creates a guard that inserts `key` with `value` on drop.
+    ///
+    /// `path` is the descent for `hash`, or `None` if the map is empty.
+    pub(crate) const fn vacant(
+        parts: MapParts<'a, K, V, S, H>,
+        path: Option<Path<K, V>>,
+        hash: u64,
+        key: K,
+        value: V,
+    ) -> Self {
+        Self {
+            parts,
+            path,
+            pending: Some((Slot::Vacant { hash, key }, value)),
+            dirty: true,
+        }
+    }
+
+    /// Returns a reference to the entry's key.
+    #[must_use]
+    pub fn key(&self) -> &K {
+        match &self.pending {
+            Some((Slot::Vacant { key, .. }, _)) => key,
+            Some((Slot::Occupied { node, pos }, _)) => {
+                &self
+                    .parts
+                    .store
+                    .get_entry(path::entry_at(self.parts.store, *node, *pos))
+                    .key
+            }
+            None => unreachable!("pending until drop"),
+        }
+    }
+}
+
+impl<K, V, S, H> Deref for ValueMut<'_, K, V, S, H>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        &self.pending.as_ref().expect("pending until drop").1
+    }
+}
+
+impl<K, V, S, H> DerefMut for ValueMut<'_, K, V, S, H>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    fn deref_mut(&mut self) -> &mut V {
+        self.dirty = true;
+        &mut self.pending.as_mut().expect("pending until drop").1
+    }
+}
+
+impl<K, V, S, H> Drop for ValueMut<'_, K, V, S, H>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    fn drop(&mut self) {
+        let Some((slot, value)) = self.pending.take() else {
+            return;
+        };
+        if !self.dirty {
+            return;
+        }
+        let store = &mut *self.parts.store;
+        let hasher = self.parts.hasher;
+
+        match slot {
+            Slot::Occupied { node, pos } => {
+                let path = self.path.as_ref().expect("occupied has path");
+                let (hash, key, old_contrib) = {
+                    let e = store.get_entry(path::entry_at(store, node, pos));
+                    let contrib = adhash::entry_adhash(e.hash, hasher.hash_one(&e.value));
+                    (e.hash, e.key.clone(), contrib)
+                };
+                let new_contrib = adhash::entry_adhash(hash, hasher.hash_one(&value));
+                let delta = adhash::difference(new_contrib, old_contrib);
+
+                let leaf = path::replace_entry(store, node, pos, Entry { hash, key, value }, delta);
+                *self.parts.root = Some(path::rebuild_spine(store, &path.frames, leaf, delta));
+                *self.parts.adhash = adhash::combine(*self.parts.adhash, delta);
+            }
+            Slot::Vacant { hash, key } => {
+                let entry = Entry { hash, key, value };
+                let (root, delta) = if let Some(path) = &self.path {
+                    let outcome = insert_recursive(store, hasher, path.node, entry, path.shift);
+                    let delta = outcome.adhash_delta;
+                    (
+                        path::rebuild_spine(store, &path.frames, outcome.node, delta),
+                        delta,
+                    )
+                } else {
+                    let root = insert::singleton(store, hasher, entry);
+                    (root, store.get_node(root).adhash())
+                };
+                *self.parts.root = Some(root);
+                *self.parts.size += 1;
+                *self.parts.adhash = adhash::combine(*self.parts.adhash, delta);
+            }
+        }
+    }
+}
+
+impl<K, V, S, H> fmt::Debug for ValueMut<'_, K, V, S, H>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Clone + fmt::Debug,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ValueMut").field(&**self).finish()
+    }
+}
+
+/// Mutable access to every value, returned by
+/// [`ChampMap::iter_mut`](crate::ChampMap::iter_mut).
+///
+/// A lending iterator: each `(&K, &mut V)` pair borrows the `IterMut`, so
+/// drive it with `while let Some((k, v)) = iter.next()` rather than `for`.
+/// Values are handed out as private copies. When the `IterMut` is dropped,
+/// the whole trie is path-copied once with the new values and every
+/// `AdHash` is recomputed, so the cost is O(n) clones whatever was changed.
+/// Nothing is rewritten if no value was handed out. Checkpoints taken
+/// before the call remain valid for rollback.
+pub struct IterMut<'a, K, V, S = ChampArena<K, V>, H = DefaultHashBuilder>
+where
+    K: Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    parts: MapParts<'a, K, V, S, H>,
+    entries: Vec<Idx<Entry<K, V>>>,
+    values: Vec<V>,
+}
+
+impl<'a, K, V, S, H> IterMut<'a, K, V, S, H>
+where
+    K: Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    /// Creates an iterator over every entry of the map behind `parts`.
+    pub(crate) fn new(parts: MapParts<'a, K, V, S, H>) -> Self {
+        let mut entries = Vec::with_capacity(*parts.size);
+        if let Some(root) = *parts.root {
+            transform::entry_indices(&*parts.store, root, &mut entries);
+        }
+        Self {
+            parts,
+            entries,
+            values: Vec::new(),
+        }
+    }
+
+    /// Returns the next key with mutable access to its value, in the same
+    /// order as [`iter`](crate::ChampMap::iter).
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(&K, &mut V)> {
+        let &idx = self.entries.get(self.values.len())?;
+        let e = self.parts.store.get_entry(idx);
+        self.values.push(e.value.clone());
+        Some((&e.key, self.values.last_mut()?))
+    }
+
+    /// Returns the number of entries not yet visited.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.entries.len() - self.values.len()
+    }
+
+    /// Returns `true` if every entry has been visited.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K, V, S, H> Drop for IterMut<'_, K, V, S, H>
+where
+    K: Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    fn drop(&mut self) {
+        let (Some(root), false) = (*self.parts.root, self.values.is_empty()) else {
+            return;
+        };
+        let mut values = std::mem::take(&mut self.values).into_iter();
+        let mut write_back = |_: &K, v: &mut V| {
+            if let Some(new) = values.next() {
+                *v = new;
+            }
+        };
+        let store = &mut *self.parts.store;
+        let root = transform::update_values(store, self.parts.hasher, root, &mut write_back);
+        *self.parts.root = Some(root);
+        *self.parts.adhash = store.get_node(root).adhash();
+    }
+}
+
+impl<K, V, S, H> fmt::Debug for IterMut<'_, K, V, S, H>
+where
+    K: Clone,
+    V: Hash + Clone,
+    S: ChampStore<K, V>,
+    H: BuildHasher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IterMut")
+            .field("remaining", &self.len())
+            .finish_non_exhaustive()
+    }
+}